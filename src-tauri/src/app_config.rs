@@ -1,4 +1,11 @@
+use crate::control::HttpApiConfig;
+use crate::export::ExportColumn;
+use crate::hooks::{ShellHook, Webhook};
+use crate::stats::DailyGoal;
+use crate::vault::TimeFormat;
+use chrono::Weekday;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -6,6 +13,67 @@ use std::path::PathBuf;
 pub struct AppConfig {
     pub vault_path: Option<String>,
     pub autostart: bool,
+    /// Overrides the hostname shown for this device's records
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    pub daily_goal: Option<DailyGoal>,
+    /// Weekly budgets in minutes keyed by project README path; these take
+    /// precedence over `weekly_budget` in the README frontmatter
+    #[serde(default)]
+    pub project_budgets: HashMap<String, u32>,
+    #[serde(default)]
+    pub week_start: Option<Weekday>,
+    /// Default columns for record exports
+    #[serde(default)]
+    pub export_columns: Option<Vec<ExportColumn>>,
+    /// `.ics` file kept up to date after every session, for calendar apps
+    #[serde(default)]
+    pub calendar_sync_path: Option<String>,
+    /// How many days of history the synced calendar file covers
+    #[serde(default)]
+    pub calendar_sync_days: Option<u32>,
+    /// Markdown template for weekly and monthly reviews; relative paths are
+    /// inside the vault. The built-in template is used when unset.
+    #[serde(default)]
+    pub review_template_path: Option<String>,
+    #[serde(default)]
+    pub note_frontmatter: NoteFrontmatter,
+    /// How project times are written in daily notes
+    #[serde(default)]
+    pub time_format: TimeFormat,
+    /// Local HTTP control API for scripts and launchers
+    #[serde(default)]
+    pub http_api: HttpApiConfig,
+    /// Write the timer status to `$XDG_RUNTIME_DIR` every tick, for panel
+    /// widgets; on by default on Linux
+    #[serde(default = "default_status_file")]
+    pub status_file: bool,
+    /// Run JSON commands dropped into the vault's `bar-tomato-inbox` folder.
+    /// Off by default; the folder is only created once it is turned on.
+    #[serde(default)]
+    pub command_inbox: bool,
+    /// Commands run on timer events
+    #[serde(default)]
+    pub shell_hooks: Vec<ShellHook>,
+    /// Endpoints that receive timer events and written records
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+fn default_status_file() -> bool {
+    cfg!(target_os = "linux")
+}
+
+/// Which notes get focus statistics in their frontmatter, for Dataview.
+/// Both are opt-in since they rewrite the user's notes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoteFrontmatter {
+    /// `pomodoros`, `focus_minutes` and `focus_projects` on the daily note
+    pub daily: bool,
+    /// `total_focus_minutes` and `last_focused` on the project README
+    pub readme: bool,
 }
 
 impl Default for AppConfig {
@@ -13,17 +81,37 @@ impl Default for AppConfig {
         Self {
             vault_path: None,
             autostart: false,
+            device_name: None,
+            daily_goal: None,
+            project_budgets: HashMap::new(),
+            week_start: None,
+            export_columns: None,
+            calendar_sync_path: None,
+            calendar_sync_days: None,
+            review_template_path: None,
+            note_frontmatter: NoteFrontmatter::default(),
+            time_format: TimeFormat::default(),
+            http_api: HttpApiConfig::default(),
+            status_file: default_status_file(),
+            command_inbox: false,
+            shell_hooks: Vec::new(),
+            webhooks: Vec::new(),
         }
     }
 }
 
-fn get_config_path() -> PathBuf {
+/// Directory for the config and other app-private files
+pub fn config_dir() -> PathBuf {
     let config_dir = dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("bar-tomato");
 
     fs::create_dir_all(&config_dir).ok();
-    config_dir.join("config.json")
+    config_dir
+}
+
+fn get_config_path() -> PathBuf {
+    config_dir().join("config.json")
 }
 
 pub fn load_config() -> AppConfig {
@@ -48,3 +136,12 @@ pub fn save_config(config: &AppConfig) -> Result<(), String> {
 
     Ok(())
 }
+
+/// This device's display name: the configured override or the hostname
+pub fn device_name(config: &AppConfig) -> String {
+    config
+        .device_name
+        .clone()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(crate::platform::get_device_name)
+}
@@ -1,7 +1,13 @@
+use crate::control::{EventHub, HttpServer, InboxWatcher, IpcServer};
+use crate::app_config::{self, AppConfig};
+use crate::background::Background;
+use crate::hooks::WebhookQueue;
 use crate::timer::TimerState;
-use crate::vault::PomodoroConfig;
+use crate::tray::{MenuShape, RecentTask, TrayIconCache, TRAY_ICON_SIZE};
+use crate::vault::{LiveSession, PomodoroConfig};
+use std::collections::HashSet;
 use std::sync::atomic::AtomicBool;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 pub struct AppState {
     pub timer: Mutex<TimerState>,
@@ -9,16 +15,67 @@ pub struct AppState {
     pub device_hash: String,
     pub config: Mutex<PomodoroConfig>,
     pub dialog_open: AtomicBool,
+    /// Timer updates for the HTTP API's event streams
+    pub events: Arc<EventHub>,
+    pub http_server: Mutex<Option<HttpServer>>,
+    pub ipc_server: Mutex<Option<IpcServer>>,
+    /// Watches the vault's command inbox while one is configured
+    pub inbox_watcher: Mutex<Option<InboxWatcher>>,
+    /// Mirrors `AppConfig.status_file` so the tick loop needn't read config
+    pub status_file: AtomicBool,
+    /// Last session published to the vault for other devices
+    pub live_session: Mutex<Option<LiveSession>>,
+    /// Timers on other devices already warned about, by device and start
+    pub warned_conflicts: Mutex<HashSet<String>>,
+    /// Outgoing webhook deliveries, saved across restarts
+    pub webhooks: Arc<WebhookQueue>,
+    /// What the tray menu was last built for
+    pub tray_shape: Mutex<Option<MenuShape>>,
+    /// Recent tasks listed in the tray menu, by position
+    pub tray_recent: Mutex<Vec<RecentTask>>,
+    /// Rendered progress icons and the one the tray shows
+    pub tray_icon: Mutex<TrayIconCache>,
+    /// Vault work kept off the tick loop
+    pub background: Background,
+    /// The saved `AppConfig`, so commands needn't read the file each time
+    app_config: Mutex<AppConfig>,
 }
 
 impl AppState {
-    pub fn new(device_hash: String) -> Self {
+    pub fn new(device_hash: String, app_config: AppConfig) -> Self {
         Self {
             timer: Mutex::new(TimerState::new()),
             vault_path: Mutex::new(None),
             device_hash,
             config: Mutex::new(PomodoroConfig::default()),
             dialog_open: AtomicBool::new(false),
+            events: Arc::new(EventHub::new()),
+            http_server: Mutex::new(None),
+            ipc_server: Mutex::new(None),
+            inbox_watcher: Mutex::new(None),
+            status_file: AtomicBool::new(false),
+            live_session: Mutex::new(None),
+            warned_conflicts: Mutex::new(HashSet::new()),
+            webhooks: Arc::new(WebhookQueue::load(
+                app_config::config_dir().join("webhook-queue.json"),
+            )),
+            tray_shape: Mutex::new(None),
+            tray_recent: Mutex::new(Vec::new()),
+            tray_icon: Mutex::new(TrayIconCache::new(TRAY_ICON_SIZE)),
+            background: Background::new(),
+            app_config: Mutex::new(app_config),
         }
     }
+
+    pub fn app_config(&self) -> AppConfig {
+        self.app_config.lock().unwrap().clone()
+    }
+
+    /// Write the config file and keep the cached copy in step
+    pub fn save_app_config(&self, config: &AppConfig) -> Result<(), String> {
+        let mut cached = self.app_config.lock().unwrap();
+        app_config::save_config(config)?;
+        *cached = config.clone();
+        Ok(())
+    }
 }
@@ -1,28 +1,23 @@
+use serde::Serialize;
 use tauri::AppHandle;
 
+/// What the OS will do at login, compared with where the app lives now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AutostartState {
+    Enabled,
+    Disabled,
+    /// Registered, but for an executable path that isn't this app's
+    Stale,
+    Unsupported,
+}
+
 pub fn enable_autostart(_app: &AppHandle) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         use std::fs;
-        use std::path::Path;
-
-        // Get the executable path
-        let exe_path = std::env::current_exe()
-            .map_err(|e| format!("Failed to get executable path: {}", e))?;
-
-        // Determine if it's an app bundle or standalone binary
-        let app_bundle_path = if exe_path.ends_with(".app/Contents/MacOS/bar-tomato") {
-            // It's inside an app bundle - get the .app bundle path
-            exe_path
-                .parent()
-                .and_then(Path::parent)
-                .and_then(Path::parent)
-                .ok_or_else(|| "Failed to get app bundle path".to_string())?
-        } else {
-            // It's a standalone binary
-            &exe_path
-        };
 
+        let app_bundle_path = &macos_launch_target()?;
         let is_app_bundle = app_bundle_path.extension().map_or(false, |ext| ext == "app");
 
         // Create LaunchAgents directory
@@ -113,7 +108,26 @@ pub fn enable_autostart(_app: &AppHandle) -> Result<(), String> {
         Ok(())
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(target_os = "linux")]
+    {
+        use std::fs;
+
+        let desktop_path = xdg::desktop_file_path()?;
+        let exe_path =
+            std::env::current_exe().map_err(|e| format!("Failed to get executable path: {}", e))?;
+        let target = xdg::launch_target(std::env::var("APPIMAGE").ok().as_deref(), &exe_path);
+
+        if let Some(dir) = desktop_path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create autostart directory: {}", e))?;
+        }
+        fs::write(&desktop_path, xdg::desktop_entry(&target))
+            .map_err(|e| format!("Failed to write desktop entry: {}", e))?;
+
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
         Err("Autostart not supported on this platform".to_string())
     }
@@ -161,21 +175,41 @@ pub fn disable_autostart(_app: &AppHandle) -> Result<(), String> {
         Ok(())
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(target_os = "linux")]
+    {
+        let desktop_path = xdg::desktop_file_path()?;
+        if desktop_path.exists() {
+            std::fs::remove_file(&desktop_path)
+                .map_err(|e| format!("Failed to remove desktop entry: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
         Err("Autostart not supported on this platform".to_string())
     }
 }
 
-#[allow(dead_code)]
-pub fn is_autostart_enabled() -> Result<bool, String> {
+/// Check the OS's login items against this executable
+pub fn autostart_state() -> Result<AutostartState, String> {
     #[cfg(target_os = "macos")]
     {
         let plist_path = dirs::home_dir()
             .ok_or_else(|| "Failed to get home directory".to_string())?
             .join("Library/LaunchAgents/com.mariozzj.bar-tomato.plist");
 
-        Ok(plist_path.exists())
+        let content = match std::fs::read_to_string(&plist_path) {
+            Ok(content) => content,
+            Err(_) => return Ok(AutostartState::Disabled),
+        };
+        let target = macos_launch_target()?;
+        if content.contains(&format!("<string>{}</string>", target.to_string_lossy())) {
+            Ok(AutostartState::Enabled)
+        } else {
+            Ok(AutostartState::Stale)
+        }
     }
 
     #[cfg(target_os = "windows")]
@@ -186,17 +220,325 @@ pub fn is_autostart_enabled() -> Result<bool, String> {
         let hkcu = RegKey::predef(HKEY_CURRENT_USER);
         let path = r"Software\Microsoft\Windows\CurrentVersion\Run";
 
-        match hkcu.open_subkey(path) {
-            Ok(key) => {
-                let exists = key.get_value::<String, _>("bar-tomato").is_ok();
-                Ok(exists)
-            }
-            Err(_) => Ok(false),
+        let registered = match hkcu
+            .open_subkey(path)
+            .and_then(|key| key.get_value::<String, _>("bar-tomato"))
+        {
+            Ok(value) => value,
+            Err(_) => return Ok(AutostartState::Disabled),
+        };
+        let exe_path =
+            std::env::current_exe().map_err(|e| format!("Failed to get executable path: {}", e))?;
+        // Paths are case-insensitive, and hand-edited values may be quoted
+        if registered
+            .trim_matches('"')
+            .eq_ignore_ascii_case(&exe_path.to_string_lossy())
+        {
+            Ok(AutostartState::Enabled)
+        } else {
+            Ok(AutostartState::Stale)
         }
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(target_os = "linux")]
+    {
+        let desktop_path = xdg::desktop_file_path()?;
+        let content = match std::fs::read_to_string(&desktop_path) {
+            Ok(content) if xdg::entry_enabled(&content) => content,
+            _ => return Ok(AutostartState::Disabled),
+        };
+        let exe_path =
+            std::env::current_exe().map_err(|e| format!("Failed to get executable path: {}", e))?;
+        let target = xdg::launch_target(std::env::var("APPIMAGE").ok().as_deref(), &exe_path);
+        if xdg::exec_matches(&content, &target) {
+            Ok(AutostartState::Enabled)
+        } else {
+            Ok(AutostartState::Stale)
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        Ok(AutostartState::Unsupported)
+    }
+}
+
+/// Where the login item points, or None when there is none
+fn registered_target() -> Result<Option<std::path::PathBuf>, String> {
+    #[cfg(target_os = "macos")]
     {
-        Ok(false)
+        let plist_path = dirs::home_dir()
+            .ok_or_else(|| "Failed to get home directory".to_string())?
+            .join("Library/LaunchAgents/com.mariozzj.bar-tomato.plist");
+        Ok(std::fs::read_to_string(plist_path)
+            .ok()
+            .and_then(|content| plist_target(&content)))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        Ok(hkcu
+            .open_subkey(r"Software\Microsoft\Windows\CurrentVersion\Run")
+            .and_then(|key| key.get_value::<String, _>("bar-tomato"))
+            .ok()
+            .map(|value| std::path::PathBuf::from(value.trim_matches('"'))))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Ok(std::fs::read_to_string(xdg::desktop_file_path()?)
+            .ok()
+            .and_then(|content| xdg::exec_target(&content)))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        Ok(None)
+    }
+}
+
+/// Re-register an entry whose executable no longer exists, e.g. after the
+/// app bundle or AppImage was moved. An entry for another copy that is still
+/// installed is left alone and reported as `Stale`, as are all entries in
+/// debug builds so `cargo tauri dev` never takes over the installed app's.
+/// Returns the state afterwards.
+pub fn reconcile_autostart(app: &AppHandle) -> Result<AutostartState, String> {
+    let state = autostart_state()?;
+    if state != AutostartState::Stale || cfg!(debug_assertions) {
+        return Ok(state);
+    }
+    match registered_target()? {
+        Some(target) if !target.exists() => {
+            disable_autostart(app)?;
+            enable_autostart(app)?;
+            autostart_state()
+        }
+        _ => Ok(state),
+    }
+}
+
+/// The app the launch agent opens: the last absolute path among its strings,
+/// after `/usr/bin/open` for bundles
+#[cfg(target_os = "macos")]
+fn plist_target(content: &str) -> Option<std::path::PathBuf> {
+    content
+        .split("<string>")
+        .skip(1)
+        .filter_map(|s| s.split_once("</string>").map(|(value, _)| value))
+        .filter(|value| value.starts_with('/') && *value != "/usr/bin/open")
+        .last()
+        .map(std::path::PathBuf::from)
+}
+
+/// The `.app` bundle when running from one, otherwise the bare executable
+#[cfg(target_os = "macos")]
+fn macos_launch_target() -> Result<std::path::PathBuf, String> {
+    use std::path::Path;
+
+    // Get the executable path
+    let exe_path =
+        std::env::current_exe().map_err(|e| format!("Failed to get executable path: {}", e))?;
+
+    // Determine if it's an app bundle or standalone binary
+    if exe_path.ends_with(".app/Contents/MacOS/bar-tomato") {
+        // It's inside an app bundle - get the .app bundle path
+        exe_path
+            .parent()
+            .and_then(Path::parent)
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .ok_or_else(|| "Failed to get app bundle path".to_string())
+    } else {
+        // It's a standalone binary
+        Ok(exe_path)
+    }
+}
+
+/// XDG autostart desktop entries, used by GNOME, KDE and most other
+/// Linux desktops
+#[cfg(target_os = "linux")]
+mod xdg {
+    use std::path::{Path, PathBuf};
+
+    pub const DESKTOP_FILE: &str = "bar-tomato.desktop";
+
+    /// `$XDG_CONFIG_HOME/autostart`, or `~/.config/autostart`. A relative
+    /// XDG_CONFIG_HOME is invalid per the spec and ignored.
+    pub fn autostart_dir(xdg_config_home: Option<&str>, home: Option<&Path>) -> Option<PathBuf> {
+        let config_dir = match xdg_config_home.filter(|p| Path::new(p).is_absolute()) {
+            Some(dir) => PathBuf::from(dir),
+            None => home?.join(".config"),
+        };
+        Some(config_dir.join("autostart"))
+    }
+
+    pub fn desktop_file_path() -> Result<PathBuf, String> {
+        let xdg_config_home = std::env::var("XDG_CONFIG_HOME").ok();
+        autostart_dir(xdg_config_home.as_deref(), dirs::home_dir().as_deref())
+            .map(|dir| dir.join(DESKTOP_FILE))
+            .ok_or_else(|| "Failed to get home directory".to_string())
+    }
+
+    /// What to launch at login. Inside an AppImage the executable lives in
+    /// a temporary mount, so the `.AppImage` file itself is used instead.
+    pub fn launch_target(appimage: Option<&str>, current_exe: &Path) -> PathBuf {
+        match appimage.filter(|p| !p.is_empty()) {
+            Some(path) => PathBuf::from(path),
+            None => current_exe.to_path_buf(),
+        }
+    }
+
+    /// Quote a path for the `Exec` key: reserved characters need double
+    /// quotes, and the key's string value escapes backslashes once more
+    pub fn exec_arg(arg: &str) -> String {
+        const RESERVED: &[char] = &[
+            ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#',
+            '(', ')', '`',
+        ];
+        let arg = arg.replace('%', "%%");
+        let quoted = if arg.contains(RESERVED) {
+            let mut quoted = String::from("\"");
+            for c in arg.chars() {
+                if matches!(c, '"' | '`' | '$' | '\\') {
+                    quoted.push('\\');
+                }
+                quoted.push(c);
+            }
+            quoted.push('"');
+            quoted
+        } else {
+            arg
+        };
+        quoted.replace('\\', "\\\\")
+    }
+
+    pub fn desktop_entry(target: &Path) -> String {
+        format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Bar Tomato\n\
+             Comment=A standalone Pomodoro timer that syncs with Obsidian LifeOS\n\
+             Exec={}\n\
+             Terminal=false\n\
+             X-GNOME-Autostart-enabled=true\n",
+            exec_arg(&target.to_string_lossy())
+        )
+    }
+
+    /// Whether the entry's `Exec` launches `target` exactly as we write it
+    pub fn exec_matches(content: &str, target: &Path) -> bool {
+        let expected = format!("Exec={}", exec_arg(&target.to_string_lossy()));
+        content.lines().any(|line| line.trim() == expected)
+    }
+
+    /// The program an entry's `Exec` launches, undoing `exec_arg`'s quoting
+    pub fn exec_target(content: &str) -> Option<PathBuf> {
+        let value = content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Exec="))?
+            .replace("\\\\", "\\");
+
+        let program = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let mut program = String::new();
+                let mut chars = quoted.chars();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => program.push(chars.next()?),
+                        c => program.push(c),
+                    }
+                }
+                program
+            }
+            None => value.split_whitespace().next()?.to_string(),
+        };
+        Some(PathBuf::from(program.replace("%%", "%")))
+    }
+
+    /// `Hidden=true` or `X-GNOME-Autostart-enabled=false` switch an entry
+    /// off without deleting it
+    pub fn entry_enabled(content: &str) -> bool {
+        !content
+            .lines()
+            .map(str::trim)
+            .any(|line| line == "Hidden=true" || line == "X-GNOME-Autostart-enabled=false")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_autostart_dir() {
+            let home = Path::new("/home/me");
+            assert_eq!(
+                autostart_dir(None, Some(home)),
+                Some(PathBuf::from("/home/me/.config/autostart"))
+            );
+            assert_eq!(
+                autostart_dir(Some("/xdg"), Some(home)),
+                Some(PathBuf::from("/xdg/autostart"))
+            );
+            assert_eq!(
+                autostart_dir(Some("relative"), Some(home)),
+                Some(PathBuf::from("/home/me/.config/autostart"))
+            );
+            assert_eq!(autostart_dir(None, None), None);
+        }
+
+        #[test]
+        fn test_launch_target_prefers_appimage() {
+            let exe = Path::new("/tmp/.mount_barXYZ/usr/bin/bar-tomato");
+            assert_eq!(
+                launch_target(Some("/home/me/Apps/Bar Tomato.AppImage"), exe),
+                PathBuf::from("/home/me/Apps/Bar Tomato.AppImage")
+            );
+            assert_eq!(launch_target(Some(""), exe), exe);
+            assert_eq!(launch_target(None, exe), exe);
+        }
+
+        #[test]
+        fn test_desktop_entry() {
+            let entry = desktop_entry(Path::new("/usr/bin/bar-tomato"));
+            assert!(entry.starts_with("[Desktop Entry]\nType=Application\n"));
+            assert!(entry.contains("\nExec=/usr/bin/bar-tomato\n"));
+            assert!(entry_enabled(&entry));
+
+            assert_eq!(
+                exec_arg("/home/me/Apps/Bar Tomato.AppImage"),
+                "\"/home/me/Apps/Bar Tomato.AppImage\""
+            );
+            assert_eq!(exec_arg("/opt/100%/bar"), "/opt/100%%/bar");
+            assert_eq!(exec_arg("/opt/a$b"), "\"/opt/a\\\\$b\"");
+
+            assert!(exec_matches(&entry, Path::new("/usr/bin/bar-tomato")));
+            assert!(!exec_matches(&entry, Path::new("/opt/bar-tomato")));
+
+            for path in [
+                "/usr/bin/bar-tomato",
+                "/home/me/Apps/Bar Tomato.AppImage",
+                "/opt/100%/a$b\\c\"d",
+            ] {
+                assert_eq!(
+                    exec_target(&desktop_entry(Path::new(path))),
+                    Some(PathBuf::from(path))
+                );
+            }
+            assert_eq!(
+                exec_target("Exec=/usr/bin/bar-tomato --hidden\n"),
+                Some(PathBuf::from("/usr/bin/bar-tomato"))
+            );
+
+            assert!(!entry_enabled(&format!("{}Hidden=true\n", entry)));
+            assert!(!entry_enabled(&entry.replace(
+                "X-GNOME-Autostart-enabled=true",
+                "X-GNOME-Autostart-enabled=false"
+            )));
+        }
     }
 }
@@ -0,0 +1,103 @@
+use crate::app_state::AppState;
+use crate::commands;
+use crate::timer::TimerStatus;
+use crate::tray;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+use tauri::{AppHandle, Manager};
+
+/// Vault work handed off by the tick loop and the commands, so a slow or
+/// syncing vault never holds up the timer
+pub enum Job {
+    /// One tick's status: heartbeat the live session, look for conflicting
+    /// timers and rebuild the tray menu if what it shows has changed
+    Tick { status: TimerStatus, now_ms: u64 },
+    /// Records were written, so the menu's recent tasks and totals are stale
+    RecordsChanged,
+    /// The goal progress shown belongs to an earlier day
+    GoalRollover,
+}
+
+/// Jobs waiting when the worker wakes, folded into one pass
+#[derive(Default)]
+struct Batch {
+    tick: Option<(TimerStatus, u64)>,
+    records_changed: bool,
+    goal_rollover: bool,
+}
+
+impl Batch {
+    fn add(&mut self, job: Job) {
+        match job {
+            Job::Tick { status, now_ms } => self.tick = Some((status, now_ms)),
+            Job::RecordsChanged => self.records_changed = true,
+            Job::GoalRollover => self.goal_rollover = true,
+        }
+    }
+
+    fn run(self, app: &AppHandle) {
+        let state = app.state::<AppState>();
+        if self.goal_rollover {
+            commands::refresh_goal_progress(app, &state);
+        }
+
+        let status = match self.tick {
+            Some((status, now_ms)) => {
+                if commands::sync_live_session(&state, &status, &[], now_ms) {
+                    commands::check_timer_conflicts(app, &state, now_ms);
+                }
+                status
+            }
+            None => state.timer.lock().unwrap().status(),
+        };
+        if self.records_changed {
+            // Rebuild even though the phase and session are the same
+            *state.tray_shape.lock().unwrap() = None;
+        }
+        tray::refresh_tray_menu(app, &status);
+    }
+}
+
+/// Runs jobs on one thread. Jobs that piled up while it was busy are run
+/// together, keeping only the latest tick.
+pub struct Background {
+    tx: Sender<Job>,
+    rx: Mutex<Option<Receiver<Job>>>,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            tx,
+            rx: Mutex::new(Some(rx)),
+        }
+    }
+}
+
+impl Background {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn send(&self, job: Job) {
+        let _ = self.tx.send(job);
+    }
+
+    /// Work through jobs for the life of the app; only the first call
+    /// starts a worker
+    pub fn start_worker(&self, app: AppHandle) {
+        let Some(rx) = self.rx.lock().unwrap().take() else {
+            return;
+        };
+        thread::spawn(move || {
+            while let Ok(job) = rx.recv() {
+                let mut batch = Batch::default();
+                batch.add(job);
+                rx.try_iter().for_each(|job| batch.add(job));
+                batch.run(&app);
+            }
+        });
+    }
+}
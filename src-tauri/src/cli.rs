@@ -0,0 +1,501 @@
+//! `bar-tomato-cli`: drive the running tray app from shell scripts and
+//! editor integrations. Timer commands go over the app's local socket;
+//! `today` and `export` read the vault directly, so they also work when
+//! the app isn't running. `bar` prints widget output for Linux panels
+//! from the status file the app keeps in `$XDG_RUNTIME_DIR`.
+
+use crate::app_config;
+use crate::control::{
+    read_status_file, status_file_path, ControlRequest, IpcClient, StartOptions, StatusSnapshot,
+};
+use crate::export::{self, ExportFormat, ExportOptions};
+use crate::platform;
+use crate::timer::{TimerMode, TimerPhase, TimerStatus};
+use crate::vault;
+use chrono::Local;
+use serde::Serialize;
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "用法: bar-tomato-cli [--json] <命令>
+
+命令:
+  status                                 显示计时器状态
+  start [--task 任务] [--project 项目] [--stopwatch]
+                                         开始番茄钟（或正计时）
+  pause | resume | stop | complete       暂停、继续、停止、完成
+  skip-break                             跳过休息
+  today                                  今日专注统计
+  export [--from 日期] [--to 日期] --output 文件 [--format csv|json|jsonLines]
+                                         导出记录，日期默认为今天
+  bar [--format waybar|i3blocks|text] [--watch]
+                                         输出 Waybar、i3blocks 或 Polybar 状态栏内容，
+                                         --watch 每秒输出一行
+
+选项:
+  --json                                 以 JSON 输出";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Help,
+    Control(ControlRequest),
+    Today,
+    Bar {
+        format: BarFormat,
+        watch: bool,
+    },
+    Export {
+        from: Option<String>,
+        to: Option<String>,
+        format: Option<String>,
+        output: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cli {
+    json: bool,
+    command: Command,
+}
+
+/// Run the CLI with the arguments after the program name; returns the
+/// process exit code
+pub fn run(args: Vec<String>) -> i32 {
+    let cli = match parse_args(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    match execute(&cli) {
+        Ok(output) => {
+            println!("{}", output);
+            0
+        }
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            1
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Cli, String> {
+    let json = args.iter().any(|a| a == "--json");
+    let mut rest = args.iter().filter(|a| *a != "--json").map(String::as_str);
+
+    let name = match rest.next() {
+        Some(name) => name,
+        None => {
+            return Ok(Cli {
+                json,
+                command: Command::Help,
+            })
+        }
+    };
+    let mut options = Options::parse(rest)?;
+
+    let command = match name {
+        "help" | "--help" | "-h" => Command::Help,
+        "status" => Command::Control(ControlRequest::Status),
+        "start" => {
+            let start = StartOptions {
+                task: options.value("task"),
+                project: options.value("project"),
+                project_path: None,
+            };
+            if options.flag("stopwatch") {
+                Command::Control(ControlRequest::StartStopwatch(start))
+            } else {
+                Command::Control(ControlRequest::StartPomodoro(start))
+            }
+        }
+        "pause" => Command::Control(ControlRequest::Pause),
+        "resume" => Command::Control(ControlRequest::Resume),
+        "stop" => Command::Control(ControlRequest::Stop),
+        "complete" => Command::Control(ControlRequest::Complete),
+        "skip-break" => Command::Control(ControlRequest::SkipBreak),
+        "today" => Command::Today,
+        "bar" => Command::Bar {
+            format: match options.value("format").as_deref() {
+                None | Some("waybar") => BarFormat::Waybar,
+                Some("i3blocks") => BarFormat::I3blocks,
+                Some("text") | Some("polybar") => BarFormat::Text,
+                Some(other) => return Err(format!("Unknown bar format: {}", other)),
+            },
+            watch: options.flag("watch"),
+        },
+        "export" => Command::Export {
+            from: options.value("from"),
+            to: options.value("to"),
+            format: options.value("format"),
+            output: options
+                .value("output")
+                .ok_or("export needs --output <file>")?,
+        },
+        other => return Err(format!("Unknown command: {}", other)),
+    };
+    options.finish()?;
+    Ok(Cli { json, command })
+}
+
+/// `--name value`, `--name=value` and bare `--flag` options
+struct Options {
+    pairs: Vec<(String, Option<String>)>,
+}
+
+impl Options {
+    fn parse<'a>(args: impl Iterator<Item = &'a str>) -> Result<Self, String> {
+        let mut pairs = Vec::new();
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Unexpected argument: {}", arg))?;
+            let pair = match name.split_once('=') {
+                Some((n, v)) => (n.to_string(), Some(v.to_string())),
+                None => {
+                    let value = args.next_if(|next| !next.starts_with("--"));
+                    (name.to_string(), value.map(String::from))
+                }
+            };
+            pairs.push(pair);
+        }
+        Ok(Self { pairs })
+    }
+
+    fn take(&mut self, name: &str) -> Option<Option<String>> {
+        let idx = self.pairs.iter().position(|(n, _)| n == name)?;
+        Some(self.pairs.remove(idx).1)
+    }
+
+    fn value(&mut self, name: &str) -> Option<String> {
+        self.take(name).flatten()
+    }
+
+    fn flag(&mut self, name: &str) -> bool {
+        self.take(name).is_some()
+    }
+
+    /// Anything not consumed by the command is a mistake
+    fn finish(self) -> Result<(), String> {
+        match self.pairs.first() {
+            Some((name, _)) => Err(format!("Unknown option: --{}", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn execute(cli: &Cli) -> Result<String, String> {
+    match &cli.command {
+        Command::Help => Ok(USAGE.to_string()),
+        Command::Control(request) => match IpcClient::connect() {
+            Some(mut client) => {
+                let status = client.send(request)?;
+                render_status(Some(&status), cli.json)
+            }
+            None if *request == ControlRequest::Status => render_status(None, cli.json),
+            None => Err("Bar Tomato is not running".to_string()),
+        },
+        Command::Today => today(cli.json),
+        Command::Bar { format, watch } => {
+            if *watch {
+                watch_bar(*format);
+            }
+            Ok(bar_line(*format))
+        }
+        Command::Export {
+            from,
+            to,
+            format,
+            output,
+        } => {
+            let today = Local::now().format("%Y-%m-%d").to_string();
+            let from = from.clone().unwrap_or_else(|| today.clone());
+            let options = ExportOptions {
+                to: to.clone().unwrap_or_else(|| from.clone()),
+                from,
+                format: export_format(format.as_deref(), output)?,
+                columns: None,
+                path: output.clone(),
+            };
+            export(&options, cli.json)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StatusOutput<'a> {
+    running: bool,
+    #[serde(flatten)]
+    status: Option<&'a TimerStatus>,
+}
+
+/// `None` means the app isn't running
+fn render_status(status: Option<&TimerStatus>, json: bool) -> Result<String, String> {
+    if json {
+        return serde_json::to_string(&StatusOutput {
+            running: status.is_some(),
+            status,
+        })
+        .map_err(|e| format!("Failed to serialize status: {}", e));
+    }
+    Ok(match status {
+        Some(status) => describe_status(status),
+        None => "Bar Tomato 未运行".to_string(),
+    })
+}
+
+fn clock(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// One line such as `专注中 12:34 · 写论文 [科研-A]`
+pub fn describe_status(status: &TimerStatus) -> String {
+    let time = if status.overtime_secs > 0 {
+        format!("+{}", clock(status.overtime_secs))
+    } else {
+        clock(status.remaining_secs.unwrap_or(status.elapsed_secs))
+    };
+    let mut line = match (status.phase, status.mode) {
+        (TimerPhase::Idle, _) => return "空闲".to_string(),
+        (TimerPhase::Running, TimerMode::Pomodoro) => format!("专注中 {}", time),
+        (TimerPhase::Running, TimerMode::Stopwatch) => format!("正计时 {}", time),
+        (TimerPhase::Paused, _) => format!("已暂停 {}", time),
+        (TimerPhase::ShortBreak, _) => format!("短休息 {}", time),
+        (TimerPhase::LongBreak, _) => format!("长休息 {}", time),
+    };
+    if let Some(ref task) = status.current_task {
+        line.push_str(&format!(" · {}", task));
+    }
+    if let Some(ref project) = status.current_project {
+        line.push_str(&format!(" [{}]", project));
+    }
+    line
+}
+
+/// Output flavours for panel widgets
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BarFormat {
+    /// JSON for a Waybar custom module (`return-type: json`)
+    Waybar,
+    /// JSON for i3blocks (`format=json`)
+    I3blocks,
+    /// Plain title for Polybar and anything else
+    Text,
+}
+
+/// Widget output for the app's current status; a missing or stale status
+/// file means the app isn't running
+fn bar_line(format: BarFormat) -> String {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let snapshot = read_status_file(&status_file_path()).filter(|s| !s.is_stale(now_ms));
+    render_bar(snapshot.as_ref(), format)
+}
+
+/// Print a line whenever the output changes, for Waybar's continuous mode
+fn watch_bar(format: BarFormat) -> ! {
+    let mut last = String::new();
+    loop {
+        let line = bar_line(format);
+        if line != last {
+            let mut stdout = std::io::stdout();
+            if writeln!(stdout, "{}", line)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                // The panel went away
+                std::process::exit(0);
+            }
+            last = line;
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+fn render_bar(snapshot: Option<&StatusSnapshot>, format: BarFormat) -> String {
+    let (text, class, tooltip) = match snapshot {
+        Some(s) => (s.title.as_str(), s.css_class(), describe_status(&s.status)),
+        None => ("", "stopped", "Bar Tomato 未运行".to_string()),
+    };
+    match format {
+        BarFormat::Waybar => {
+            let mut out = serde_json::json!({
+                "text": text,
+                "alt": class,
+                "tooltip": tooltip,
+                "class": class,
+            });
+            if let Some(progress) = snapshot.and_then(|s| s.progress) {
+                out["percentage"] = progress.into();
+            }
+            out.to_string()
+        }
+        BarFormat::I3blocks => serde_json::json!({
+            "full_text": if text.is_empty() { "" } else { tooltip.as_str() },
+            "short_text": text,
+        })
+        .to_string(),
+        BarFormat::Text => text.to_string(),
+    }
+}
+
+fn today(json: bool) -> Result<String, String> {
+    let config = app_config::load_config();
+    let vault_path = config.vault_path.ok_or("Vault not configured")?;
+    let date = Local::now().format("%Y-%m-%d").to_string();
+    let records = vault::read_counted_records(&vault_path, &platform::get_device_hash())?;
+
+    let today: Vec<_> = records.iter().filter(|r| r.record.date == date).collect();
+    let total_minutes: u32 = today.iter().map(|r| r.record.duration).sum();
+    let pomodoro_count = today.iter().filter(|r| r.record.mode == "pomodoro").count();
+
+    if json {
+        return Ok(serde_json::json!({
+            "date": date,
+            "totalMinutes": total_minutes,
+            "pomodoroCount": pomodoro_count,
+        })
+        .to_string());
+    }
+    Ok(format!(
+        "今日专注 {} · {} 个番茄",
+        config.time_format.format(total_minutes),
+        pomodoro_count
+    ))
+}
+
+/// `--format` if given, otherwise guessed from the output file's extension
+fn export_format(format: Option<&str>, output: &str) -> Result<ExportFormat, String> {
+    match format {
+        Some(f) => serde_json::from_value(serde_json::Value::String(f.to_string()))
+            .map_err(|_| format!("Unknown export format: {}", f)),
+        None if output.ends_with(".jsonl") => Ok(ExportFormat::JsonLines),
+        None if output.ends_with(".json") => Ok(ExportFormat::Json),
+        None => Ok(ExportFormat::Csv),
+    }
+}
+
+fn export(options: &ExportOptions, json: bool) -> Result<String, String> {
+    let config = app_config::load_config();
+    let vault_path = config.vault_path.ok_or("Vault not configured")?;
+    let summary = export::export_records(
+        &vault_path,
+        &platform::get_device_hash(),
+        options,
+        config.export_columns.as_deref(),
+    )?;
+
+    if json {
+        return serde_json::to_string(&summary)
+            .map_err(|e| format!("Failed to serialize export summary: {}", e));
+    }
+    Ok(format!(
+        "已导出 {} 条记录到 {}",
+        summary.count, summary.path
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+
+    fn parse(args: &str) -> Result<Cli, String> {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse("start --task 写论文 --project=科研 --json").unwrap(),
+            Cli {
+                json: true,
+                command: Command::Control(ControlRequest::StartPomodoro(StartOptions {
+                    task: Some("写论文".to_string()),
+                    project: Some("科研".to_string()),
+                    project_path: None,
+                })),
+            }
+        );
+        assert!(matches!(
+            parse("start --stopwatch").unwrap().command,
+            Command::Control(ControlRequest::StartStopwatch(_))
+        ));
+        assert_eq!(parse("").unwrap().command, Command::Help);
+        assert_eq!(
+            parse("export --output out.csv").unwrap().command,
+            Command::Export {
+                from: None,
+                to: None,
+                format: None,
+                output: "out.csv".to_string(),
+            }
+        );
+
+        assert!(parse("export").is_err());
+        assert!(parse("pause --task x").is_err());
+        assert!(parse("launch").is_err());
+    }
+
+    #[test]
+    fn test_export_format() {
+        assert_eq!(export_format(None, "a.jsonl"), Ok(ExportFormat::JsonLines));
+        assert_eq!(export_format(None, "a.json"), Ok(ExportFormat::Json));
+        assert_eq!(export_format(None, "a.csv"), Ok(ExportFormat::Csv));
+        assert_eq!(
+            export_format(Some("jsonLines"), "a"),
+            Ok(ExportFormat::JsonLines)
+        );
+        assert!(export_format(Some("xml"), "a").is_err());
+    }
+
+    #[test]
+    fn test_render_bar() {
+        let mut timer = TimerState::new();
+        timer.start_pomodoro();
+        timer.pause_elapsed = std::time::Duration::from_secs(15 * 60);
+        let snapshot = StatusSnapshot::new(timer.status(), &timer.tray_title(), 0);
+
+        let waybar: serde_json::Value =
+            serde_json::from_str(&render_bar(Some(&snapshot), BarFormat::Waybar)).unwrap();
+        assert_eq!(waybar["text"], "10:00");
+        assert_eq!(waybar["class"], "running");
+        assert_eq!(waybar["percentage"], 60);
+        assert_eq!(waybar["tooltip"], "专注中 10:00");
+
+        let i3: serde_json::Value =
+            serde_json::from_str(&render_bar(Some(&snapshot), BarFormat::I3blocks)).unwrap();
+        assert_eq!(i3["full_text"], "专注中 10:00");
+        assert_eq!(i3["short_text"], "10:00");
+
+        let stopped: serde_json::Value =
+            serde_json::from_str(&render_bar(None, BarFormat::Waybar)).unwrap();
+        assert_eq!(stopped["class"], "stopped");
+        assert!(stopped.get("percentage").is_none());
+        assert_eq!(render_bar(None, BarFormat::Text), "");
+    }
+
+    #[test]
+    fn test_render_status() {
+        let mut timer = TimerState::new();
+        assert_eq!(describe_status(&timer.status()), "空闲");
+
+        timer.current_task = Some("写论文".to_string());
+        timer.current_project = Some("科研-A".to_string());
+        timer.start_pomodoro();
+        timer.pause();
+        assert_eq!(
+            describe_status(&timer.status()),
+            "已暂停 25:00 · 写论文 [科研-A]"
+        );
+
+        let json = render_status(Some(&timer.status()), true).unwrap();
+        assert!(json.starts_with(r#"{"running":true,"phase":"paused""#));
+        assert_eq!(render_status(None, true).unwrap(), r#"{"running":false}"#);
+    }
+}
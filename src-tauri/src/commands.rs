@@ -1,15 +1,27 @@
-use crate::app_config;
+use crate::app_config::{self, AppConfig};
 use crate::app_state::AppState;
-use crate::autostart;
+use crate::autostart::{self, AutostartState};
+use crate::background;
+use crate::control::{self, AppControl, HttpServer};
+use crate::export::{self, ExportOptions, ExportSummary};
+use crate::hooks::{self, EventPayload, ShellHook, TimerEvent, Webhook, WebhookPayload};
+use crate::import::{self, ImportOptions, ImportSummary};
 use crate::platform;
+use crate::stats::{
+    self, BudgetSummary, DailyGoal, FocusDistribution, GoalProgress, GroupBy, HeatmapDay,
+    ReviewOptions, ReviewSummary, StatsQuery, StatsReport, StreakStats, TrendPoint,
+};
+use crate::timer::TimerState;
 use crate::timer::{TimerMode, TimerPhase, TimerStatus};
 use crate::vault::{
-    self, check_pomodoro_habit, update_project_time, PomodoroConfig, PomodoroRecord, Project,
-    VaultTask,
+    self, BudgetStatus, ConflictChoice, DeviceRecord, ManualEntry, PomodoroConfig, PomodoroRecord,
+    Project, RecordUpdate, TimeFormat, TimeFormatMigration, VaultTask,
 };
-use chrono::Local;
+use chrono::{Local, Weekday};
+use std::collections::BTreeSet;
 use std::sync::atomic::Ordering;
-use tauri::{AppHandle, State};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
 use uuid::Uuid;
 
 #[tauri::command]
@@ -19,97 +31,133 @@ pub fn get_timer_status(state: State<AppState>) -> TimerStatus {
 
 #[tauri::command]
 pub fn start_pomodoro(
+    app: AppHandle,
     state: State<AppState>,
     task: Option<String>,
     project: Option<String>,
     project_path: Option<String>,
 ) {
+    let budget = project_path
+        .as_deref()
+        .and_then(|pp| compute_project_budget(&state, pp));
+
     let mut timer = state.timer.lock().unwrap();
     timer.current_task = task;
     timer.current_project = project;
     timer.current_project_path = project_path;
+    timer.project_budget = budget;
+    let before = timer.status();
     timer.start_pomodoro();
+    let after = timer.status();
+    drop(timer);
+
+    on_timer_transition(&app, &state, &before, &after);
 }
 
 #[tauri::command]
 pub fn start_stopwatch(
+    app: AppHandle,
     state: State<AppState>,
     task: Option<String>,
     project: Option<String>,
     project_path: Option<String>,
 ) {
+    let budget = project_path
+        .as_deref()
+        .and_then(|pp| compute_project_budget(&state, pp));
+
     let mut timer = state.timer.lock().unwrap();
     timer.current_task = task;
     timer.current_project = project;
     timer.current_project_path = project_path;
+    timer.project_budget = budget;
+    let before = timer.status();
     timer.start_stopwatch();
+    let after = timer.status();
+    drop(timer);
+
+    on_timer_transition(&app, &state, &before, &after);
 }
 
 #[tauri::command]
-pub fn pause_timer(state: State<AppState>) {
-    state.timer.lock().unwrap().pause();
+pub fn pause_timer(app: AppHandle, state: State<AppState>) {
+    change_timer(&app, &state, TimerState::pause);
 }
 
 #[tauri::command]
-pub fn resume_timer(state: State<AppState>) {
-    state.timer.lock().unwrap().resume();
+pub fn resume_timer(app: AppHandle, state: State<AppState>) {
+    change_timer(&app, &state, TimerState::resume);
 }
 
 #[tauri::command]
 pub fn stop_timer(app: AppHandle, state: State<AppState>) -> Result<(), String> {
     let mut timer = state.timer.lock().unwrap();
+    let before = timer.status();
     let elapsed_mins = (timer.elapsed().as_secs() / 60) as u32;
+    let mode = timer.mode;
 
     // Only record if there's meaningful elapsed time
-    if elapsed_mins > 0 || timer.mode == TimerMode::Pomodoro {
-        let vault_path = state.vault_path.lock().unwrap().clone();
-        if let Some(ref vp) = vault_path {
-            let date = Local::now().format("%Y-%m-%d").to_string();
-            let record = PomodoroRecord {
+    let recorded = elapsed_mins > 0 || mode == TimerMode::Pomodoro;
+    let vault_path = state.vault_path.lock().unwrap().clone();
+    let record = match vault_path {
+        Some(vp) if recorded => Some((
+            vp,
+            PomodoroRecord {
                 id: Uuid::new_v4().to_string(),
-                date: date.clone(),
+                date: Local::now().format("%Y-%m-%d").to_string(),
                 start_time: timer.start_timestamp_ms.unwrap_or(0),
                 end_time: std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
                     .as_millis() as u64,
                 duration: elapsed_mins,
-                mode: match timer.mode {
+                mode: match mode {
                     TimerMode::Pomodoro => "pomodoro".to_string(),
                     TimerMode::Stopwatch => "stopwatch".to_string(),
                 },
                 status: "completed".to_string(),
                 project_path: timer.current_project_path.clone(),
                 task_text: timer.current_task.clone(),
-                pomodoro_index: if timer.mode == TimerMode::Pomodoro {
+                pomodoro_index: if mode == TimerMode::Pomodoro {
                     Some(timer.pomodoro_count + 1)
                 } else {
                     None
                 },
-            };
+                source: None,
+            },
+        )),
+        _ => None,
+    };
+    let project = timer.current_project.clone();
+    let project_path = timer.current_project_path.clone();
 
-            // Write record
-            let records_path = vault::get_records_file_path(vp, &state.device_hash);
-            vault::append_record(&records_path, record)?;
-
-            // Update daily note project time
-            if let (Some(ref pp), Some(ref pn)) =
-                (&timer.current_project_path, &timer.current_project)
-            {
-                let _ = update_project_time(vp, &date, pp, pn, elapsed_mins);
-            }
+    timer.stop();
+    let after = timer.status();
+    // The vault may be slow or syncing; don't hold up the tick while writing
+    drop(timer);
 
-            // Check pomodoro habit
-            let _ = check_pomodoro_habit(vp, &date);
-        }
+    let saved = record.map(|(vp, record)| {
+        vault::save_session(
+            &vp,
+            &state.device_hash,
+            record.clone(),
+            project.as_deref(),
+            state.app_config().time_format,
+        )
+        .map(|_| record)
+    });
 
-        // Send notification for stopwatch
-        if timer.mode == TimerMode::Stopwatch {
-            let _ = platform::send_stopwatch_stopped(&app, elapsed_mins);
-        }
+    // Send notification for stopwatch
+    if recorded && mode == TimerMode::Stopwatch {
+        let _ = platform::send_stopwatch_stopped(&app, elapsed_mins);
     }
 
-    timer.stop();
+    on_timer_transition(&app, &state, &before, &after);
+    let saved = saved.transpose()?;
+    if recorded {
+        on_records_changed(&state, saved.as_slice());
+        refresh_after_record(&app, &state, project_path, elapsed_mins);
+    }
     Ok(())
 }
 
@@ -120,6 +168,7 @@ pub fn complete_pomodoro(app: AppHandle, state: State<AppState>) -> Result<(), S
     if timer.phase != TimerPhase::Running || timer.mode != TimerMode::Pomodoro {
         return Err("No pomodoro running".to_string());
     }
+    let before = timer.status();
 
     let vault_path = state.vault_path.lock().unwrap().clone();
     let date = Local::now().format("%Y-%m-%d").to_string();
@@ -131,49 +180,129 @@ pub fn complete_pomodoro(app: AppHandle, state: State<AppState>) -> Result<(), S
     let actual_duration_mins = (timer.elapsed().as_secs() / 60).max(1) as u32;
 
     // Create record
-    if let Some(ref vp) = vault_path {
-        let record = PomodoroRecord {
-            id: Uuid::new_v4().to_string(),
-            date: date.clone(),
-            start_time: timer.start_timestamp_ms.unwrap_or(0),
-            end_time: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            duration: actual_duration_mins,
-            mode: "pomodoro".to_string(),
-            status: "completed".to_string(),
-            project_path: timer.current_project_path.clone(),
-            task_text: timer.current_task.clone(),
-            pomodoro_index: Some(pomodoro_index),
-        };
+    let record = PomodoroRecord {
+        id: Uuid::new_v4().to_string(),
+        date,
+        start_time: timer.start_timestamp_ms.unwrap_or(0),
+        end_time: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64,
+        duration: actual_duration_mins,
+        mode: "pomodoro".to_string(),
+        status: "completed".to_string(),
+        project_path: timer.current_project_path.clone(),
+        task_text: timer.current_task.clone(),
+        pomodoro_index: Some(pomodoro_index),
+        source: None,
+    };
+    let project = timer.current_project.clone();
+    let project_path = timer.current_project_path.clone();
 
-        // Write record
-        let records_path = vault::get_records_file_path(vp, &state.device_hash);
-        vault::append_record(&records_path, record)?;
-
-        // Update daily note project time
-        if let (Some(ref pp), Some(ref pn)) = (&timer.current_project_path, &timer.current_project)
-        {
-            let _ = update_project_time(vp, &date, pp, pn, actual_duration_mins);
-        }
+    // Start break
+    timer.start_break();
+    let after = timer.status();
+    drop(timer);
 
-        // Check pomodoro habit
-        let _ = check_pomodoro_habit(vp, &date);
-    }
+    let saved = match vault_path {
+        Some(ref vp) => vault::save_session(
+            vp,
+            &state.device_hash,
+            record.clone(),
+            project.as_deref(),
+            state.app_config().time_format,
+        )
+        .map(|_| Some(record)),
+        None => Ok(None),
+    };
 
     // Send notification
     let _ = platform::send_pomodoro_complete(&app);
 
-    // Start break
-    timer.start_break();
-
+    on_timer_transition(&app, &state, &before, &after);
+    let saved = saved?;
+    on_records_changed(&state, saved.as_slice());
+    refresh_after_record(&app, &state, project_path, actual_duration_mins);
     Ok(())
 }
 
+/// Recompute goal progress and the project budget after a session was saved.
+/// Called with the timer unlocked so the vault reads don't stall the tick.
+fn refresh_after_record(
+    app: &AppHandle,
+    state: &AppState,
+    project_path: Option<String>,
+    added_minutes: u32,
+) {
+    let goal = compute_goal_progress(state);
+    let budget = project_path
+        .as_deref()
+        .and_then(|pp| compute_project_budget(state, pp));
+    notify_budget_crossing(app, budget.as_ref(), added_minutes);
+
+    let mut timer = state.timer.lock().unwrap();
+    apply_goal_progress(app, state, &mut timer, goal);
+    // A new session may have started on another project in the meantime
+    if timer.current_project_path == project_path {
+        timer.project_budget = budget;
+    }
+}
+
+#[tauri::command]
+pub fn add_manual_entry(
+    app: AppHandle,
+    state: State<AppState>,
+    entry: ManualEntry,
+) -> Result<PomodoroRecord, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+    let format = state.app_config().time_format;
+    let record = vault::add_manual_session(&vault_path, &state.device_hash, &entry, format)?;
+    on_records_changed(&state, std::slice::from_ref(&record));
+    refresh_goal_progress(&app, &state);
+
+    if let Some(ref pp) = record.project_path {
+        let budget = compute_project_budget(&state, pp);
+        notify_budget_crossing(&app, budget.as_ref(), record.duration);
+    }
+    Ok(record)
+}
+
+#[tauri::command]
+pub fn update_record(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+    update: RecordUpdate,
+) -> Result<PomodoroRecord, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+    let records_path = vault::get_records_file_path(&vault_path, &state.device_hash);
+    let previous = vault::read_records(&records_path)?
+        .records
+        .into_iter()
+        .find(|r| r.id == id);
+    let format = state.app_config().time_format;
+    let record = vault::edit_session(&vault_path, &state.device_hash, &id, &update, format)?;
+
+    // The old project's README totals change too when the project moves
+    let touched: Vec<PomodoroRecord> = previous.into_iter().chain([record.clone()]).collect();
+    on_records_changed(&state, &touched);
+    refresh_goal_progress(&app, &state);
+    Ok(record)
+}
+
 #[tauri::command]
-pub fn skip_break(state: State<AppState>) {
-    state.timer.lock().unwrap().skip_break();
+pub fn skip_break(app: AppHandle, state: State<AppState>) {
+    change_timer(&app, &state, TimerState::skip_break);
 }
 
 #[tauri::command]
@@ -181,12 +310,55 @@ pub fn complete_break(app: AppHandle, state: State<AppState>) {
     let mut timer = state.timer.lock().unwrap();
     if timer.phase == TimerPhase::ShortBreak || timer.phase == TimerPhase::LongBreak {
         let _ = platform::send_break_complete(&app);
+        let before = timer.status();
         timer.skip_break();
+        let after = timer.status();
+        drop(timer);
+
+        on_timer_transition(&app, &state, &before, &after);
+    }
+}
+
+/// Apply a change that needs nothing but the timer, then report it
+fn change_timer(app: &AppHandle, state: &AppState, change: impl FnOnce(&mut TimerState)) {
+    let mut timer = state.timer.lock().unwrap();
+    let before = timer.status();
+    change(&mut timer);
+    let after = timer.status();
+    drop(timer);
+
+    on_timer_transition(app, state, &before, &after);
+}
+
+/// Fire hooks and update the live session for a timer change, given the
+/// statuses taken on either side of it under one lock. Called with the timer
+/// unlocked, so each change is reported even when several land within a tick.
+fn on_timer_transition(
+    app: &AppHandle,
+    state: &AppState,
+    before: &TimerStatus,
+    after: &TimerStatus,
+) {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let events = hooks::transition_payloads(before, after, now_ms);
+    if events.is_empty() {
+        return;
+    }
+    on_timer_events(state, &events);
+    if sync_live_session(state, after, &events, now_ms) {
+        check_timer_conflicts(app, state, now_ms);
     }
 }
 
 #[tauri::command]
-pub fn set_vault_path(state: State<AppState>, path: String) -> Result<PomodoroConfig, String> {
+pub fn set_vault_path(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+) -> Result<PomodoroConfig, String> {
     if !vault::check_vault_valid(&path)? {
         return Err("Invalid vault or lifeos-pro plugin not found".to_string());
     }
@@ -204,12 +376,27 @@ pub fn set_vault_path(state: State<AppState>, path: String) -> Result<PomodoroCo
 
     // Store config
     *state.config.lock().unwrap() = config.clone();
-    *state.vault_path.lock().unwrap() = Some(path.clone());
+    let previous_vault = state.vault_path.lock().unwrap().replace(path.clone());
+
+    // The next tick publishes the running session in the new vault
+    if let Some(ref old) = previous_vault {
+        let _ = vault::clear_live_session(old, &state.device_hash);
+    }
+    *state.live_session.lock().unwrap() = None;
 
     // Save to persistent config
-    let mut app_config = app_config::load_config();
-    app_config.vault_path = Some(path);
-    app_config::save_config(&app_config)?;
+    let mut app_config = state.app_config();
+    app_config.vault_path = Some(path.clone());
+    state.save_app_config(&app_config)?;
+
+    let _ = vault::register_device(
+        &path,
+        &state.device_hash,
+        &app_config::device_name(&app_config),
+    );
+
+    refresh_goal_progress(&app, &state);
+    restart_command_inbox(&app, &state);
 
     Ok(config)
 }
@@ -232,7 +419,29 @@ pub fn scan_projects(state: State<AppState>) -> Result<Vec<Project>, String> {
         .unwrap()
         .clone()
         .ok_or("Vault not configured")?;
-    vault::scan_projects(&vault_path)
+    let mut projects = vault::scan_projects(&vault_path)?;
+
+    // Attach this week's budget usage to projects that have a budget
+    let config = state.app_config();
+    let week = current_budget_week(&config);
+    let mut records: Option<Vec<PomodoroRecord>> = None;
+    for project in &mut projects {
+        if let Some(budget) = project_budget_minutes(&vault_path, &config, &project.readme_path) {
+            if records.is_none() {
+                records = Some(all_records(&state)?);
+            }
+            let mut status = stats::budget_status(
+                records.as_deref().unwrap_or(&[]),
+                &project.readme_path,
+                budget,
+                week,
+            );
+            status.project_name = project.display_name.clone();
+            project.budget = Some(status);
+        }
+    }
+
+    Ok(projects)
 }
 
 #[tauri::command]
@@ -252,7 +461,10 @@ pub fn get_tray_title(state: State<AppState>) -> String {
 }
 
 #[tauri::command]
-pub fn get_today_stats(state: State<AppState>) -> Result<TodayStats, String> {
+pub fn get_today_stats(
+    state: State<AppState>,
+    by_device: Option<bool>,
+) -> Result<TodayStats, String> {
     let vault_path = state
         .vault_path
         .lock()
@@ -261,20 +473,47 @@ pub fn get_today_stats(state: State<AppState>) -> Result<TodayStats, String> {
         .ok_or("Vault not configured")?;
 
     let date = Local::now().format("%Y-%m-%d").to_string();
-    let records_path = vault::get_records_file_path(&vault_path, &state.device_hash);
-    let records = vault::read_records(&records_path)?;
+    let records = vault::read_counted_records(&vault_path, &state.device_hash)?;
 
-    let today_records: Vec<_> = records.records.iter().filter(|r| r.date == date).collect();
+    let today_records: Vec<_> = records.iter().filter(|r| r.record.date == date).collect();
 
-    let total_minutes: u32 = today_records.iter().map(|r| r.duration).sum();
+    let total_minutes: u32 = today_records.iter().map(|r| r.record.duration).sum();
     let pomodoro_count = today_records
         .iter()
-        .filter(|r| r.mode == "pomodoro")
+        .filter(|r| r.record.mode == "pomodoro")
         .count() as u32;
 
+    let devices = if by_device.unwrap_or(false) {
+        let names = vault::read_devices(&vault_path).unwrap_or_else(|_| vault::DevicesFile::new());
+        let mut devices: Vec<DeviceStats> = Vec::new();
+        for r in &today_records {
+            let idx = match devices.iter().position(|d| d.device_hash == r.device_hash) {
+                Some(idx) => idx,
+                None => {
+                    devices.push(DeviceStats {
+                        device_hash: r.device_hash.clone(),
+                        device_name: names.name_for(&r.device_hash),
+                        is_current: r.device_hash == state.device_hash,
+                        total_minutes: 0,
+                        pomodoro_count: 0,
+                    });
+                    devices.len() - 1
+                }
+            };
+            devices[idx].total_minutes += r.record.duration;
+            if r.record.mode == "pomodoro" {
+                devices[idx].pomodoro_count += 1;
+            }
+        }
+        Some(devices)
+    } else {
+        None
+    };
+
     Ok(TodayStats {
         total_minutes,
         pomodoro_count,
+        devices,
     })
 }
 
@@ -283,10 +522,904 @@ pub fn get_today_stats(state: State<AppState>) -> Result<TodayStats, String> {
 pub struct TodayStats {
     pub total_minutes: u32,
     pub pomodoro_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devices: Option<Vec<DeviceStats>>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceStats {
+    pub device_hash: String,
+    pub device_name: String,
+    pub is_current: bool,
+    pub total_minutes: u32,
+    pub pomodoro_count: u32,
+}
+
+#[tauri::command]
+pub fn get_stats(state: State<AppState>, query: StatsQuery) -> Result<StatsReport, String> {
+    let records = all_records(&state)?;
+    stats::query_stats(&records, &query)
+}
+
+/// Records from every device with overlaps counted once, for the
+/// statistics commands
+fn all_records(state: &AppState) -> Result<Vec<PomodoroRecord>, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+
+    Ok(
+        vault::read_counted_records(&vault_path, &state.device_hash)?
+            .into_iter()
+            .map(|r| r.record)
+            .collect(),
+    )
+}
+
+#[tauri::command]
+pub fn get_streaks(
+    state: State<AppState>,
+    threshold_minutes: Option<u32>,
+) -> Result<StreakStats, String> {
+    let records = all_records(&state)?;
+    Ok(stats::streaks(
+        &records,
+        Local::now().date_naive(),
+        threshold_minutes.unwrap_or(1),
+    ))
+}
+
+#[tauri::command]
+pub fn get_heatmap(state: State<AppState>, days: Option<u32>) -> Result<Vec<HeatmapDay>, String> {
+    let records = all_records(&state)?;
+    Ok(stats::heatmap(
+        &records,
+        Local::now().date_naive(),
+        days.unwrap_or(365),
+    ))
+}
+
+#[tauri::command]
+pub fn get_focus_distribution(
+    state: State<AppState>,
+    from: String,
+    to: String,
+) -> Result<FocusDistribution, String> {
+    let (from_date, to_date) = stats::parse_range(&from, &to)?;
+    let records = all_records(&state)?;
+    let in_range: Vec<PomodoroRecord> = stats::records_in_range(&records, from_date, to_date)
+        .into_iter()
+        .cloned()
+        .collect();
+    Ok(stats::focus_distribution(&in_range, &Local))
+}
+
+#[tauri::command]
+pub fn get_session_length_trend(
+    state: State<AppState>,
+    from: String,
+    to: String,
+    group_by: GroupBy,
+    week_start: Option<chrono::Weekday>,
+) -> Result<Vec<TrendPoint>, String> {
+    let records = all_records(&state)?;
+    stats::session_length_trend(&records, &from, &to, group_by, week_start)
+}
+
+#[tauri::command]
+pub fn get_daily_goal(state: State<AppState>) -> Option<DailyGoal> {
+    state.app_config().daily_goal
+}
+
+#[tauri::command]
+pub fn set_daily_goal(
+    app: AppHandle,
+    state: State<AppState>,
+    goal: Option<DailyGoal>,
+) -> Result<Option<GoalProgress>, String> {
+    let mut config = state.app_config();
+    config.daily_goal = goal;
+    state.save_app_config(&config)?;
+
+    refresh_goal_progress(&app, &state);
+    Ok(state.timer.lock().unwrap().goal_progress.clone())
+}
+
+/// Today's progress toward the configured daily goal, if there is one
+pub fn compute_goal_progress(state: &AppState) -> Option<GoalProgress> {
+    let goal = state.app_config().daily_goal?;
+    let vault_path = state.vault_path.lock().unwrap().clone()?;
+    let today = Local::now().date_naive();
+    let date = today.format("%Y-%m-%d").to_string();
+
+    let note_override = if goal.use_daily_note {
+        std::fs::read_to_string(vault::daily_note_path(&vault_path, &date))
+            .ok()
+            .and_then(|content| vault::get_frontmatter_value(&content, "focus_goal"))
+            .and_then(|value| stats::parse_goal_value(&value))
+    } else {
+        None
+    };
+
+    let counted = vault::read_counted_records(&vault_path, &state.device_hash).ok()?;
+    let records: Vec<PomodoroRecord> = counted.into_iter().map(|r| r.record).collect();
+    stats::goal_progress(&goal, note_override, &records, today)
+}
+
+/// Store new goal progress on the timer, notifying once when it is reached
+pub fn apply_goal_progress(
+    app: &AppHandle,
+    state: &AppState,
+    timer: &mut TimerState,
+    progress: Option<GoalProgress>,
+) {
+    if stats::goal_just_reached(
+        timer.goal_progress.as_ref(),
+        progress.as_ref(),
+        timer.goal_notified_on.as_deref(),
+    ) {
+        if let Some(ref p) = progress {
+            let _ = platform::send_goal_reached(app, &p.short_label());
+            timer.goal_notified_on = Some(p.date.clone());
+        }
+    }
+    timer.goal_in_tray = state
+        .app_config()
+        .daily_goal
+        .is_some_and(|g| g.show_in_tray);
+    timer.goal_progress = progress;
+}
+
+/// Recompute goal progress; must not be called while holding the timer lock
+pub fn refresh_goal_progress(app: &AppHandle, state: &AppState) {
+    let progress = compute_goal_progress(state);
+    let mut timer = state.timer.lock().unwrap();
+    apply_goal_progress(app, state, &mut timer, progress);
+}
+
+#[tauri::command]
+pub fn get_budget_summary(state: State<AppState>) -> Result<BudgetSummary, String> {
+    let projects = scan_projects(state)?;
+    let config = state.app_config();
+    let statuses = projects.into_iter().filter_map(|p| p.budget).collect();
+    Ok(stats::budget_summary(
+        statuses,
+        current_budget_week(&config),
+    ))
+}
+
+#[tauri::command]
+pub fn set_project_budget(
+    state: State<AppState>,
+    project_path: String,
+    minutes: Option<u32>,
+) -> Result<Option<BudgetStatus>, String> {
+    let mut config = state.app_config();
+    match minutes {
+        Some(m) => config.project_budgets.insert(project_path.clone(), m),
+        None => config.project_budgets.remove(&project_path),
+    };
+    state.save_app_config(&config)?;
+
+    let budget = compute_project_budget(&state, &project_path);
+    let mut timer = state.timer.lock().unwrap();
+    if timer.current_project_path.as_deref() == Some(project_path.as_str()) {
+        timer.project_budget = budget.clone();
+    }
+    Ok(budget)
+}
+
+fn current_budget_week(config: &AppConfig) -> (chrono::NaiveDate, chrono::NaiveDate) {
+    stats::current_week(
+        Local::now().date_naive(),
+        config.week_start.unwrap_or(Weekday::Mon),
+    )
+}
+
+/// Weekly budget for a project: the app config entry, else the README's `weekly_budget`
+fn project_budget_minutes(vault_path: &str, config: &AppConfig, project_path: &str) -> Option<u32> {
+    config
+        .project_budgets
+        .get(project_path)
+        .copied()
+        .or_else(|| {
+            vault::read_readme_budget(vault_path, project_path)
+                .and_then(|value| stats::parse_budget(&value))
+        })
+}
+
+/// This week's budget usage for a project, if it has a budget
+pub fn compute_project_budget(state: &AppState, project_path: &str) -> Option<BudgetStatus> {
+    let vault_path = state.vault_path.lock().unwrap().clone()?;
+    let config = state.app_config();
+    let budget = project_budget_minutes(&vault_path, &config, project_path)?;
+    let records = all_records(state).ok()?;
+
+    let mut status =
+        stats::budget_status(&records, project_path, budget, current_budget_week(&config));
+    status.project_name = vault::project_display_name(&vault_path, project_path);
+    Some(status)
+}
+
+/// Notify when the session that just added `added_minutes` crossed the budget
+fn notify_budget_crossing(app: &AppHandle, budget: Option<&BudgetStatus>, added_minutes: u32) {
+    if let Some(b) = budget {
+        if added_minutes > 0 && stats::crossed_budget(b, added_minutes) {
+            let over = b.used_minutes - b.budget_minutes;
+            let _ = platform::send_budget_exceeded(app, &b.project_name, over);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn export_records(
+    state: State<AppState>,
+    options: ExportOptions,
+) -> Result<ExportSummary, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+
+    let config = state.app_config();
+    export::export_records(
+        &vault_path,
+        &state.device_hash,
+        &options,
+        config.export_columns.as_deref(),
+    )
+}
+
+#[tauri::command]
+pub fn export_calendar(
+    state: State<AppState>,
+    from: String,
+    to: String,
+    path: String,
+) -> Result<ExportSummary, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+    let (from_date, to_date) = stats::parse_range(&from, &to)?;
+    export::export_calendar(&vault_path, &state.device_hash, from_date, to_date, &path)
+}
+
+#[tauri::command]
+pub fn set_calendar_sync(
+    state: State<AppState>,
+    path: Option<String>,
+    days: Option<u32>,
+) -> Result<Option<ExportSummary>, String> {
+    let mut config = state.app_config();
+    config.calendar_sync_path = path;
+    config.calendar_sync_days = days;
+    state.save_app_config(&config)?;
+
+    match (config.calendar_sync_path, state.vault_path.lock().unwrap().clone()) {
+        (Some(ref path), Some(ref vp)) => export::sync_calendar(
+            vp,
+            &state.device_hash,
+            path,
+            config.calendar_sync_days.unwrap_or(90),
+        )
+        .map(Some),
+        _ => Ok(None),
+    }
+}
+
+#[tauri::command]
+pub fn import_sessions(
+    app: AppHandle,
+    state: State<AppState>,
+    options: ImportOptions,
+) -> Result<ImportSummary, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+
+    let summary = import::run_import(&vault_path, &state.device_hash, &options)?;
+    if summary.written {
+        // Imported history stays out of the notes, as with the daily note times
+        on_records_changed(&state, &[]);
+        refresh_goal_progress(&app, &state);
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn generate_review(
+    state: State<AppState>,
+    options: ReviewOptions,
+) -> Result<ReviewSummary, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+    let records = all_records(&state)?;
+    let config = state.app_config();
+    let template = load_review_template(&vault_path, &config)?;
+
+    stats::generate_review(
+        &vault_path,
+        &records,
+        &options,
+        &template,
+        config.week_start.unwrap_or(Weekday::Mon),
+        config.time_format,
+        Local::now().date_naive(),
+    )
+}
+
+/// The configured review template, or the built-in one
+fn load_review_template(vault_path: &str, config: &AppConfig) -> Result<String, String> {
+    match config.review_template_path {
+        Some(ref p) => {
+            let path = std::path::Path::new(vault_path).join(p);
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read review template: {}", e))
+        }
+        None => Ok(stats::DEFAULT_REVIEW_TEMPLATE.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_review_template(state: State<AppState>) -> Result<String, String> {
+    let config = state.app_config();
+    match state.vault_path.lock().unwrap().clone() {
+        Some(vp) => load_review_template(&vp, &config),
+        None => Ok(stats::DEFAULT_REVIEW_TEMPLATE.to_string()),
+    }
+}
+
+/// Change how note totals are written. Existing daily notes are left alone;
+/// the result previews how many `migrate_time_format` would convert.
+#[tauri::command]
+pub fn set_time_format(
+    state: State<AppState>,
+    format: TimeFormat,
+) -> Result<TimeFormatMigration, String> {
+    let mut config = state.app_config();
+    config.time_format = format;
+    state.save_app_config(&config)?;
+
+    match state.vault_path.lock().unwrap().clone() {
+        Some(ref vp) => vault::migrate_time_format(vp, format, false),
+        None => Ok(TimeFormatMigration::default()),
+    }
+}
+
+/// Convert existing daily notes to the configured time format, once the
+/// user has confirmed the preview from `set_time_format`
+#[tauri::command]
+pub fn migrate_time_format(state: State<AppState>) -> Result<TimeFormatMigration, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+    let format = state.app_config().time_format;
+    vault::migrate_time_format(&vault_path, format, true)
+}
+
+#[tauri::command]
+pub fn set_note_frontmatter(
+    state: State<AppState>,
+    daily: bool,
+    readme: bool,
+) -> Result<(), String> {
+    let mut config = state.app_config();
+    config.note_frontmatter = app_config::NoteFrontmatter { daily, readme };
+    state.save_app_config(&config)
+}
+
+#[tauri::command]
+pub fn set_review_template(state: State<AppState>, path: Option<String>) -> Result<(), String> {
+    let mut config = state.app_config();
+    config.review_template_path = path.filter(|p| !p.trim().is_empty());
+    state.save_app_config(&config)
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpApiInfo {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: Option<String>,
+    /// Whether the server is listening right now
+    pub running: bool,
+}
+
+#[tauri::command]
+pub fn get_http_api(state: State<AppState>) -> HttpApiInfo {
+    let settings = state.app_config().http_api;
+    HttpApiInfo {
+        enabled: settings.enabled,
+        port: settings.port,
+        token: settings.token,
+        running: state.http_server.lock().unwrap().is_some(),
+    }
+}
+
+#[tauri::command]
+pub fn set_http_api(
+    app: AppHandle,
+    state: State<AppState>,
+    enabled: bool,
+    port: Option<u16>,
+    regenerate_token: bool,
+) -> Result<HttpApiInfo, String> {
+    let mut config = state.app_config();
+    config.http_api.enabled = enabled;
+    if let Some(port) = port {
+        config.http_api.port = port;
+    }
+    if regenerate_token {
+        config.http_api.token = None;
+    }
+    state.save_app_config(&config)?;
+
+    restart_http_api(&app, &state)?;
+    Ok(get_http_api(state))
+}
+
+/// Start the HTTP API from the saved settings, stopping any running server
+/// first. The token is generated and saved the first time.
+pub fn restart_http_api(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    let mut server = state.http_server.lock().unwrap();
+    if let Some(mut running) = server.take() {
+        running.shutdown();
+    }
+
+    let mut config = state.app_config();
+    if !config.http_api.enabled {
+        return Ok(());
+    }
+    let token = match config.http_api.token.clone() {
+        Some(token) => token,
+        None => {
+            let token = Uuid::new_v4().simple().to_string();
+            config.http_api.token = Some(token.clone());
+            state.save_app_config(&config)?;
+            token
+        }
+    };
+
+    *server = Some(HttpServer::start(
+        config.http_api.port,
+        token,
+        Arc::new(AppControl::new(app.clone())),
+        state.events.clone(),
+    )?);
+    Ok(())
+}
+
+/// Path of the panel status file, or None when it is turned off
+#[tauri::command]
+pub fn get_status_file(state: State<AppState>) -> Option<String> {
+    state
+        .status_file
+        .load(Ordering::Relaxed)
+        .then(|| control::status_file_path().to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn set_status_file(state: State<AppState>, enabled: bool) -> Result<Option<String>, String> {
+    let mut config = state.app_config();
+    config.status_file = enabled;
+    state.save_app_config(&config)?;
+
+    state.status_file.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        // Don't leave a frozen status behind for widgets
+        let _ = std::fs::remove_file(control::status_file_path());
+    }
+    Ok(get_status_file(state))
+}
+
+/// Path of the command inbox, or None when it is turned off or there is no vault
+#[tauri::command]
+pub fn get_command_inbox(state: State<AppState>) -> Option<String> {
+    let vault_path = state.vault_path.lock().unwrap().clone()?;
+    state.inbox_watcher.lock().unwrap().is_some().then(|| {
+        control::inbox_dir(&vault_path)
+            .to_string_lossy()
+            .to_string()
+    })
+}
+
+#[tauri::command]
+pub fn set_command_inbox(
+    app: AppHandle,
+    state: State<AppState>,
+    enabled: bool,
+) -> Result<Option<String>, String> {
+    let mut config = state.app_config();
+    config.command_inbox = enabled;
+    state.save_app_config(&config)?;
+
+    restart_command_inbox(&app, &state);
+    Ok(get_command_inbox(state))
+}
+
+/// Watch the current vault's command inbox if it is enabled, replacing any
+/// watcher on a previous vault
+pub fn restart_command_inbox(app: &AppHandle, state: &AppState) {
+    let mut watcher = state.inbox_watcher.lock().unwrap();
+    *watcher = None;
+
+    let config = state.app_config();
+    let vault_path = match state.vault_path.lock().unwrap().clone() {
+        Some(vp) if config.command_inbox => vp,
+        _ => return,
+    };
+    let inbox = control::Inbox::new(
+        control::inbox_dir(&vault_path),
+        state.device_hash.clone(),
+        app_config::device_name(&config),
+    );
+    match control::InboxWatcher::start(inbox, Arc::new(AppControl::new(app.clone()))) {
+        Ok(w) => *watcher = Some(w),
+        Err(e) => log::warn!("{}", e),
+    }
+}
+
+#[tauri::command]
+pub fn get_shell_hooks(state: State<AppState>) -> Vec<ShellHook> {
+    state.app_config().shell_hooks
 }
 
 #[tauri::command]
-pub fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn set_shell_hooks(state: State<AppState>, hooks: Vec<ShellHook>) -> Result<(), String> {
+    let mut config = state.app_config();
+    config.shell_hooks = hooks
+        .into_iter()
+        .filter(|h| !h.command.trim().is_empty())
+        .collect();
+    state.save_app_config(&config)
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookSettings {
+    pub webhooks: Vec<Webhook>,
+    /// Deliveries waiting for a retry, e.g. while offline
+    pub pending: usize,
+}
+
+#[tauri::command]
+pub fn get_webhooks(state: State<AppState>) -> WebhookSettings {
+    WebhookSettings {
+        webhooks: state.app_config().webhooks,
+        pending: state.webhooks.pending(),
+    }
+}
+
+#[tauri::command]
+pub fn set_webhooks(
+    state: State<AppState>,
+    webhooks: Vec<Webhook>,
+) -> Result<WebhookSettings, String> {
+    for hook in &webhooks {
+        let url = hook.url.trim();
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(format!("Invalid webhook URL: {}", url));
+        }
+    }
+    let mut config = state.app_config();
+    config.webhooks = webhooks;
+    state.save_app_config(&config)?;
+    Ok(get_webhooks(state))
+}
+
+/// Run shell hooks and queue webhooks for timer transitions
+pub fn on_timer_events(state: &AppState, events: &[EventPayload]) {
+    let config = state.app_config();
+    for payload in events {
+        hooks::dispatch_shell_hooks(&config.shell_hooks, payload);
+        state.webhooks.enqueue(
+            &config.webhooks,
+            &WebhookPayload::Timer(payload.clone()),
+            payload.timestamp,
+        );
+    }
+}
+
+/// Keep `current-session.{hash}.json` in the vault in step with the timer:
+/// rewritten on transitions and as a heartbeat, removed when idle. Returns
+/// whether the session was updated.
+pub fn sync_live_session(
+    state: &AppState,
+    status: &TimerStatus,
+    events: &[EventPayload],
+    now_ms: u64,
+) -> bool {
+    let mut current = state.live_session.lock().unwrap();
+    let due = match current.as_ref() {
+        Some(session) => !events.is_empty() || session.needs_heartbeat(now_ms),
+        None => !events.is_empty() || status.phase != TimerPhase::Idle,
+    };
+    if !due {
+        return false;
+    }
+    let vault_path = match state.vault_path.lock().unwrap().clone() {
+        Some(vp) => vp,
+        None => return false,
+    };
+
+    let started = events.iter().any(|e| {
+        matches!(
+            e.event,
+            TimerEvent::PomodoroStarted | TimerEvent::StopwatchStarted | TimerEvent::BreakStarted
+        )
+    });
+    let device_name = app_config::device_name(&state.app_config());
+    let next = vault::LiveSession::from_status(
+        status,
+        current.as_ref(),
+        started,
+        &state.device_hash,
+        &device_name,
+        now_ms,
+    );
+    let result = match next {
+        Some(ref session) => vault::write_live_session(&vault_path, session),
+        None => vault::clear_live_session(&vault_path, &state.device_hash),
+    };
+    if let Err(e) = result {
+        log::warn!("{}", e);
+    }
+    *current = next;
+    true
+}
+
+/// Warn once about each timer on another device or in the plugin that is
+/// counting focus time alongside ours: the devices' `current-session.{hash}.json`
+/// files and the plugin's `pomodoroState` in its `data.json`. `timer-conflict`
+/// is emitted on every check, with an empty list once nothing overlaps, so the
+/// popover clears.
+pub fn check_timer_conflicts(app: &AppHandle, state: &AppState, now_ms: u64) {
+    let own = state
+        .live_session
+        .lock()
+        .unwrap()
+        .clone()
+        .filter(|s| s.is_focus());
+    if own.is_none() {
+        state.warned_conflicts.lock().unwrap().clear();
+    }
+    let vault_path = state.vault_path.lock().unwrap().clone();
+    let conflicts = match (own, vault_path) {
+        (Some(own), Some(vp)) => {
+            let others = vault::read_live_sessions(&vp)
+                .into_iter()
+                .chain(vault::read_plugin_session(&vp, now_ms))
+                .collect();
+            vault::live_conflicts(&own, others, now_ms)
+        }
+        _ => Vec::new(),
+    };
+
+    let mut warned = state.warned_conflicts.lock().unwrap();
+    for session in &conflicts {
+        if warned.insert(format!("{}:{}", session.device_hash, session.started_at)) {
+            let _ = platform::send_timer_conflict(app, &session.device_name);
+        }
+    }
+    let _ = app.emit("timer-conflict", &conflicts);
+}
+
+/// A conflict with the device names to show next to each record
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictInfo {
+    #[serde(flatten)]
+    pub conflict: vault::RecordConflict,
+    pub first_device_name: String,
+    pub second_device_name: String,
+}
+
+/// Unresolved overlapping records from the last `days` days (default 7)
+#[tauri::command]
+pub fn get_conflicts(
+    state: State<AppState>,
+    days: Option<u32>,
+) -> Result<Vec<ConflictInfo>, String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+
+    let since = (Local::now() - chrono::Duration::days(days.unwrap_or(7) as i64))
+        .format("%Y-%m-%d")
+        .to_string();
+    let records: Vec<DeviceRecord> = vault::read_all_records(&vault_path, &state.device_hash)?
+        .into_iter()
+        .filter(|r| r.record.date >= since)
+        .collect();
+    let resolutions = vault::read_conflicts(&vault_path)?;
+    let names = vault::read_devices(&vault_path).unwrap_or_else(|_| vault::DevicesFile::new());
+
+    Ok(vault::find_conflicts(&records, &resolutions)
+        .into_iter()
+        .map(|conflict| ConflictInfo {
+            first_device_name: names.name_for(&conflict.first.device_hash),
+            second_device_name: names.name_for(&conflict.second.device_hash),
+            conflict,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn resolve_conflict(
+    app: AppHandle,
+    state: State<AppState>,
+    choice: ConflictChoice,
+) -> Result<(), String> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("Vault not configured")?;
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let format = state.app_config().time_format;
+    let touched =
+        vault::resolve_conflict(&vault_path, &state.device_hash, &choice, format, now_ms)?;
+
+    on_records_changed(&state, &touched);
+    refresh_goal_progress(&app, &state);
+    Ok(())
+}
+
+/// Sessions running on other devices, for "focusing on X on laptop"
+#[tauri::command]
+pub fn get_live_sessions(state: State<AppState>) -> Vec<vault::LiveSession> {
+    let vault_path = match state.vault_path.lock().unwrap().clone() {
+        Some(vp) => vp,
+        None => return Vec::new(),
+    };
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    vault::read_live_sessions(&vault_path)
+        .into_iter()
+        .filter(|s| s.device_hash != state.device_hash && !s.is_stale(now_ms))
+        .collect()
+}
+
+/// Keep derived files in step after records are written or edited.
+/// `touched` are the records whose daily notes and READMEs need refreshing.
+fn on_records_changed(state: &AppState, touched: &[PomodoroRecord]) {
+    let vault_path = match state.vault_path.lock().unwrap().clone() {
+        Some(vp) => vp,
+        None => return,
+    };
+    let config = state.app_config();
+
+    send_record_webhooks(state, &config, touched);
+
+    if let Some(ref path) = config.calendar_sync_path {
+        let days = config.calendar_sync_days.unwrap_or(90);
+        if let Err(e) = export::sync_calendar(&vault_path, &state.device_hash, path, days) {
+            log::warn!("Failed to sync calendar file: {}", e);
+        }
+    }
+
+    sync_note_frontmatter(state, &vault_path, &config, touched);
+    state.background.send(background::Job::RecordsChanged);
+}
+
+/// Queue each written record for webhooks. An edit touches the old version
+/// too, so only the last version of each record is sent.
+fn send_record_webhooks(state: &AppState, config: &AppConfig, touched: &[PomodoroRecord]) {
+    if config.webhooks.is_empty() {
+        return;
+    }
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    for (i, record) in touched.iter().enumerate() {
+        if touched[i + 1..].iter().any(|r| r.id == record.id) {
+            continue;
+        }
+        let payload = WebhookPayload::Record {
+            timestamp: now_ms,
+            record: record.clone(),
+        };
+        state.webhooks.enqueue(&config.webhooks, &payload, now_ms);
+    }
+}
+
+/// Refresh the Dataview frontmatter of the notes the touched records belong to
+fn sync_note_frontmatter(
+    state: &AppState,
+    vault_path: &str,
+    config: &AppConfig,
+    touched: &[PomodoroRecord],
+) {
+    let settings = &config.note_frontmatter;
+    if touched.is_empty() || !(settings.daily || settings.readme) {
+        return;
+    }
+    let records = match all_records(state) {
+        Ok(r) => r,
+        Err(e) => {
+            log::warn!("Failed to read records for frontmatter: {}", e);
+            return;
+        }
+    };
+
+    if settings.daily {
+        let project_names = export::project_name_map(vault_path);
+        let dates: BTreeSet<&str> = touched.iter().map(|r| r.date.as_str()).collect();
+        for date in dates {
+            if let Err(e) =
+                vault::sync_daily_frontmatter(vault_path, date, &records, &project_names)
+            {
+                log::warn!("Failed to update daily note frontmatter: {}", e);
+            }
+        }
+    }
+
+    if settings.readme {
+        let projects: BTreeSet<&str> = touched
+            .iter()
+            .filter_map(|r| r.project_path.as_deref())
+            .collect();
+        for project_path in projects {
+            if let Err(e) = vault::sync_readme_frontmatter(vault_path, project_path, &records) {
+                log::warn!("Failed to update README frontmatter: {}", e);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn set_device_name(
+    app: AppHandle,
+    state: State<AppState>,
+    name: Option<String>,
+) -> Result<(), String> {
+    let mut config = state.app_config();
+    config.device_name = name;
+    state.save_app_config(&config)?;
+
+    if let Some(ref vp) = *state.vault_path.lock().unwrap() {
+        vault::register_device(vp, &state.device_hash, &app_config::device_name(&config))?;
+    }
+    // Inbox commands can address this device by name
+    restart_command_inbox(&app, &state);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_autostart(
+    app: AppHandle,
+    state: State<AppState>,
+    enabled: bool,
+) -> Result<AutostartState, String> {
     if enabled {
         autostart::enable_autostart(&app)?;
     } else {
@@ -294,17 +1427,43 @@ pub fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), String> {
     }
 
     // Update config
-    let mut config = app_config::load_config();
+    let mut config = state.app_config();
     config.autostart = enabled;
-    app_config::save_config(&config)?;
+    state.save_app_config(&config)?;
 
-    Ok(())
+    autostart::autostart_state()
 }
 
+/// What the OS will actually do at login; the saved `autostart` flag can
+/// be out of date
 #[tauri::command]
-pub fn get_autostart() -> Result<bool, String> {
-    let config = app_config::load_config();
-    Ok(config.autostart)
+pub fn get_autostart() -> Result<AutostartState, String> {
+    autostart::autostart_state()
+}
+
+/// Repair stale login items at startup and bring the saved flag in line
+/// with the OS
+pub fn reconcile_autostart(app: &AppHandle) {
+    let os_state = match autostart::reconcile_autostart(app) {
+        Ok(os_state) => os_state,
+        Err(e) => {
+            log::warn!("Failed to reconcile autostart: {}", e);
+            return;
+        }
+    };
+    if os_state == AutostartState::Unsupported {
+        return;
+    }
+
+    let state = app.state::<AppState>();
+    let mut config = state.app_config();
+    let enabled = os_state == AutostartState::Enabled;
+    if config.autostart != enabled {
+        config.autostart = enabled;
+        if let Err(e) = state.save_app_config(&config) {
+            log::warn!("{}", e);
+        }
+    }
 }
 
 #[tauri::command]
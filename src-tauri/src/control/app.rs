@@ -0,0 +1,74 @@
+use super::{ControlRequest, StartOptions, TimerControl};
+use crate::app_state::AppState;
+use crate::commands;
+use crate::import::match_project;
+use crate::timer::TimerStatus;
+use crate::vault;
+use tauri::{AppHandle, Manager};
+
+/// Runs control requests through the same commands the webview invokes
+pub struct AppControl {
+    app: AppHandle,
+}
+
+impl AppControl {
+    pub fn new(app: AppHandle) -> Self {
+        Self { app }
+    }
+
+    /// Fill in the project path and display name for a project given by name
+    fn resolve_project(&self, options: StartOptions) -> StartOptions {
+        let (Some(name), None) = (&options.project, &options.project_path) else {
+            return options;
+        };
+        let state = self.app.state::<AppState>();
+        let vault_path = match state.vault_path.lock().unwrap().clone() {
+            Some(vp) => vp,
+            None => return options,
+        };
+        let projects = vault::scan_projects(&vault_path).unwrap_or_default();
+        match match_project(name, &projects) {
+            Some(p) => StartOptions {
+                task: options.task,
+                project: Some(p.display_name.clone()),
+                project_path: Some(p.readme_path.clone()),
+            },
+            None => options,
+        }
+    }
+}
+
+impl TimerControl for AppControl {
+    fn execute(&self, request: ControlRequest) -> Result<TimerStatus, String> {
+        let app = &self.app;
+        match request {
+            ControlRequest::Status => {}
+            ControlRequest::StartPomodoro(options) => {
+                let o = self.resolve_project(options);
+                commands::start_pomodoro(
+                    app.clone(),
+                    app.state(),
+                    o.task,
+                    o.project,
+                    o.project_path,
+                );
+            }
+            ControlRequest::StartStopwatch(options) => {
+                let o = self.resolve_project(options);
+                commands::start_stopwatch(
+                    app.clone(),
+                    app.state(),
+                    o.task,
+                    o.project,
+                    o.project_path,
+                );
+            }
+            ControlRequest::Pause => commands::pause_timer(app.clone(), app.state()),
+            ControlRequest::Resume => commands::resume_timer(app.clone(), app.state()),
+            ControlRequest::Stop => commands::stop_timer(app.clone(), app.state())?,
+            ControlRequest::Complete => commands::complete_pomodoro(app.clone(), app.state())?,
+            ControlRequest::SkipBreak => commands::skip_break(app.clone(), app.state()),
+        }
+        Ok(commands::get_timer_status(app.state()))
+    }
+}
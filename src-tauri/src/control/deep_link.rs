@@ -0,0 +1,176 @@
+use super::{ControlRequest, StartOptions, TimerControl};
+
+/// `bartomato://start?task=...&project=...`
+pub const URL_SCHEME: &str = "bartomato";
+
+/// Whether a command-line argument is one of our URLs rather than a flag
+pub fn is_deep_link(arg: &str) -> bool {
+    strip_scheme(arg).is_some()
+}
+
+fn strip_scheme(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once(':')?;
+    scheme.eq_ignore_ascii_case(URL_SCHEME).then_some(rest)
+}
+
+/// Decode `%XX` escapes and `+` for spaces, as browsers encode query values
+fn percent_decode(value: &str) -> Result<String, String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let byte = value
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| format!("Invalid escape in \"{}\"", value))?;
+                out.push(byte);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).map_err(|_| format!("Invalid UTF-8 in \"{}\"", value))
+}
+
+/// Any web page can open our URLs, so a project path must stay relative to
+/// the vault: no absolute paths, drive letters or `..` segments
+fn vault_relative(path: String) -> Result<String, String> {
+    let escapes = path.starts_with(['/', '\\'])
+        || path.contains(':')
+        || path.split(['/', '\\']).any(|segment| segment == "..");
+    if escapes {
+        return Err(format!("Project path must be inside the vault: {}", path));
+    }
+    Ok(path)
+}
+
+/// Turn a `bartomato://` URL into the control request it stands for.
+/// Actions: `start` (a pomodoro), `stopwatch`, `pause`, `resume`, `stop`,
+/// `complete` and `skip-break`. Starts take `task`, `project` and
+/// `projectPath` query parameters; the path is relative to the vault.
+pub fn parse_deep_link(url: &str) -> Result<ControlRequest, String> {
+    let rest = strip_scheme(url.trim()).ok_or_else(|| format!("Not a {} URL", URL_SCHEME))?;
+    let rest = rest.trim_start_matches('/');
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let action = action.trim_end_matches('/').to_lowercase();
+
+    let mut options = StartOptions::default();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = Some(percent_decode(value)?).filter(|v| !v.trim().is_empty());
+        match key {
+            "task" => options.task = value,
+            "project" => options.project = value,
+            "projectPath" | "project_path" => {
+                options.project_path = value.map(vault_relative).transpose()?
+            }
+            _ => {}
+        }
+    }
+
+    match action.as_str() {
+        "start" | "pomodoro" => Ok(ControlRequest::StartPomodoro(options)),
+        "stopwatch" => Ok(ControlRequest::StartStopwatch(options)),
+        "pause" => Ok(ControlRequest::Pause),
+        "resume" => Ok(ControlRequest::Resume),
+        "stop" => Ok(ControlRequest::Stop),
+        "complete" => Ok(ControlRequest::Complete),
+        "skip-break" | "skip_break" => Ok(ControlRequest::SkipBreak),
+        "status" => Ok(ControlRequest::Status),
+        "" => Err("Missing action".to_string()),
+        other => Err(format!("Unknown action: {}", other)),
+    }
+}
+
+/// Carry out a URL opened by the OS or forwarded by a second launch
+pub fn open_deep_link(control: &dyn TimerControl, url: &str) -> Result<(), String> {
+    let request = parse_deep_link(url)?;
+    control.execute(request).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::testing::CoreControl;
+    use crate::timer::TimerPhase;
+
+    #[test]
+    fn test_parse_start() {
+        let request =
+            parse_deep_link("bartomato://start?task=%E5%86%99%E8%AE%BA%E6%96%87&project=Thesis+2")
+                .unwrap();
+        assert_eq!(
+            request,
+            ControlRequest::StartPomodoro(StartOptions {
+                task: Some("写论文".to_string()),
+                project: Some("Thesis 2".to_string()),
+                project_path: None,
+            })
+        );
+        assert_eq!(
+            parse_deep_link("BarTomato:///stopwatch/?projectPath=a%2Fb.md&task=").unwrap(),
+            ControlRequest::StartStopwatch(StartOptions {
+                project_path: Some("a/b.md".to_string()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_actions_and_errors() {
+        assert_eq!(
+            parse_deep_link("bartomato://stop").unwrap(),
+            ControlRequest::Stop
+        );
+        assert_eq!(
+            parse_deep_link("bartomato://skip-break#x").unwrap(),
+            ControlRequest::SkipBreak
+        );
+        assert!(parse_deep_link("bartomato://").is_err());
+        assert!(parse_deep_link("bartomato://explode").is_err());
+        assert!(parse_deep_link("https://start").is_err());
+        assert!(parse_deep_link("bartomato://start?task=%E5").is_err());
+        assert!(parse_deep_link("bartomato://start?task=%zz").is_err());
+
+        // Project paths can't leave the vault
+        for path in [
+            "..%2Fsecret.md",
+            "1.%20%E9%A1%B9%E7%9B%AE%2F..%2F..%2Fx.md",
+            "%2Fetc%2Fx.md",
+            "C%3A%5CUsers%5Cx.md",
+            "%5C%5Cserver%5Cshare%5Cx.md",
+        ] {
+            let url = format!("bartomato://start?projectPath={}", path);
+            assert!(parse_deep_link(&url)
+                .unwrap_err()
+                .starts_with("Project path must be inside the vault"));
+        }
+
+        assert!(is_deep_link("bartomato://stop"));
+        assert!(!is_deep_link("--minimized"));
+    }
+
+    #[test]
+    fn test_open_runs_request() {
+        let control = CoreControl::default();
+        open_deep_link(&control, "bartomato://start?task=a").unwrap();
+        let status = control.execute(ControlRequest::Status).unwrap();
+        assert_eq!(status.phase, TimerPhase::Running);
+        assert_eq!(status.current_task.as_deref(), Some("a"));
+        assert!(open_deep_link(&control, "bartomato://complete?").is_ok());
+        assert_eq!(
+            open_deep_link(&control, "bartomato://complete").unwrap_err(),
+            "No pomodoro running"
+        );
+    }
+}
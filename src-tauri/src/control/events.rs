@@ -0,0 +1,89 @@
+use crate::timer::{TimerPhase, TimerStatus};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+
+/// One Server-Sent Event: `event: {name}` with a JSON `data` line
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerEvent {
+    pub name: &'static str,
+    pub data: String,
+}
+
+/// Fans timer updates out to every connected event stream
+#[derive(Default)]
+pub struct EventHub {
+    subscribers: Mutex<Vec<Sender<ServerEvent>>>,
+    last_phase: Mutex<Option<TimerPhase>>,
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self) -> Receiver<ServerEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Send a `tick` with the status, preceded by a `phase` event when the
+    /// phase differs from the last published one
+    pub fn publish(&self, status: &TimerStatus) {
+        let data = match serde_json::to_string(status) {
+            Ok(d) => d,
+            Err(e) => {
+                log::warn!("Failed to serialize timer status: {}", e);
+                return;
+            }
+        };
+
+        let phase_changed = {
+            let mut last = self.last_phase.lock().unwrap();
+            let changed = *last != Some(status.phase);
+            *last = Some(status.phase);
+            changed
+        };
+        if phase_changed {
+            self.send(ServerEvent {
+                name: "phase",
+                data: data.clone(),
+            });
+        }
+        self.send(ServerEvent { name: "tick", data });
+    }
+
+    /// Deliver to every subscriber, dropping the ones that disconnected
+    fn send(&self, event: ServerEvent) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if subscribers.is_empty() {
+            return;
+        }
+        subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+
+    #[test]
+    fn test_phase_events_only_on_change() {
+        let hub = EventHub::new();
+        let rx = hub.subscribe();
+        let mut timer = TimerState::new();
+
+        hub.publish(&timer.status());
+        hub.publish(&timer.status());
+        timer.start_pomodoro();
+        hub.publish(&timer.status());
+
+        let names: Vec<&str> = rx.try_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["phase", "tick", "tick", "phase", "tick"]);
+
+        drop(rx);
+        hub.publish(&timer.status());
+        assert!(hub.subscribers.lock().unwrap().is_empty());
+    }
+}
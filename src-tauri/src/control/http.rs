@@ -0,0 +1,558 @@
+use super::{ControlRequest, EventHub, StartOptions, TimerControl};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+pub const DEFAULT_HTTP_PORT: u16 = 27315;
+
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const KEEPALIVE_SECS: u32 = 15;
+/// Open connections, event streams included, before new ones get a 503
+const MAX_CONNECTIONS: usize = 16;
+
+/// Local HTTP control API settings. Off until the user enables it; the
+/// token is generated on first start.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: Option<String>,
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_HTTP_PORT,
+            token: None,
+        }
+    }
+}
+
+/// Shared by every connection thread
+struct Context {
+    token: String,
+    control: Arc<dyn TimerControl>,
+    events: Arc<EventHub>,
+    stop: Arc<AtomicBool>,
+    connections: AtomicUsize,
+}
+
+/// Releases a connection slot when its thread ends
+struct ConnectionSlot(Arc<Context>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Control API listening on 127.0.0.1. Stops when shut down or dropped.
+pub struct HttpServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+impl HttpServer {
+    /// Bind to `127.0.0.1:{port}` (0 picks a free port) and serve requests
+    /// on a background thread
+    pub fn start(
+        port: u16,
+        token: String,
+        control: Arc<dyn TimerControl>,
+        events: Arc<EventHub>,
+    ) -> Result<Self, String> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .map_err(|e| format!("Failed to bind HTTP API on port {}: {}", port, e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read HTTP API address: {}", e))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let ctx = Arc::new(Context {
+            token,
+            control,
+            events,
+            stop: stop.clone(),
+            connections: AtomicUsize::new(0),
+        });
+
+        let accept_thread = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if ctx.stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(mut stream) => {
+                        if ctx.connections.load(Ordering::SeqCst) >= MAX_CONNECTIONS {
+                            let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+                            let _ = write_error(
+                                &mut stream,
+                                &HttpError::new(503, "Too many connections"),
+                            );
+                            continue;
+                        }
+                        ctx.connections.fetch_add(1, Ordering::SeqCst);
+                        let slot = ConnectionSlot(ctx.clone());
+                        thread::spawn(move || handle_connection(stream, &slot.0));
+                    }
+                    Err(e) => log::warn!("HTTP API connection failed: {}", e),
+                }
+            }
+        });
+
+        log::info!("HTTP API listening on {}", addr);
+        Ok(Self {
+            addr,
+            stop,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stop accepting connections and end open event streams. The port is
+    /// free again once this returns.
+    pub fn shutdown(&mut self) {
+        if self.stop.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect_timeout(&self.addr, Duration::from_secs(1));
+        if let Some(handle) = self.accept_thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    query_token: Option<String>,
+    bearer: Option<String>,
+    body: Vec<u8>,
+}
+
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, ctx: &Context) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+    let mut writer = match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let request = match read_request(&mut BufReader::new(stream)) {
+        Ok(r) => r,
+        Err(e) => {
+            let _ = write_error(&mut writer, &e);
+            return;
+        }
+    };
+
+    // Browsers' EventSource can't send headers, so `/events` also takes the
+    // token as `?token=`. Everything else needs the Authorization header, to
+    // keep the token out of URLs that end up in logs and history.
+    let query_token = match request.path.as_str() {
+        "/events" => request.query_token.as_deref(),
+        _ => None,
+    };
+    let authorized = request
+        .bearer
+        .as_deref()
+        .or(query_token)
+        .is_some_and(|t| constant_time_eq(t.as_bytes(), ctx.token.as_bytes()));
+    if !authorized {
+        let _ = write_error(
+            &mut writer,
+            &HttpError::new(401, "Missing or invalid token"),
+        );
+        return;
+    }
+
+    if request.path == "/events" {
+        if request.method != "GET" {
+            let _ = write_error(&mut writer, &HttpError::new(405, "Method not allowed"));
+            return;
+        }
+        let _ = stream_events(&mut writer, ctx);
+        return;
+    }
+
+    let result = route(&request).and_then(|control| {
+        ctx.control
+            .execute(control)
+            .map_err(|e| HttpError::new(409, e))
+    });
+    let _ = match result {
+        Ok(status) => match serde_json::to_string(&status) {
+            Ok(body) => write_response(&mut writer, 200, "application/json", &body),
+            Err(e) => write_error(&mut writer, &HttpError::new(500, e.to_string())),
+        },
+        Err(e) => write_error(&mut writer, &e),
+    };
+}
+
+/// Read the request line, headers and body of a single HTTP/1.1 request
+fn read_request(reader: &mut impl BufRead) -> Result<HttpRequest, HttpError> {
+    let bad_request = |msg: &str| HttpError::new(400, msg);
+    let mut header_bytes = 0;
+    let mut read_line = |reader: &mut dyn BufRead| -> Result<String, HttpError> {
+        let mut line = String::new();
+        let n = reader
+            .take((MAX_HEADER_BYTES - header_bytes + 1) as u64)
+            .read_line(&mut line)
+            .map_err(|_| bad_request("Malformed request"))?;
+        header_bytes += n;
+        if header_bytes > MAX_HEADER_BYTES {
+            return Err(HttpError::new(431, "Headers too large"));
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    };
+
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(m), Some(t)) => (m.to_string(), t.to_string()),
+        _ => return Err(bad_request("Malformed request line")),
+    };
+
+    let mut content_length = 0;
+    let mut bearer = None;
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(bad_request("Malformed header"));
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                content_length = value
+                    .parse::<usize>()
+                    .map_err(|_| bad_request("Invalid Content-Length"))?;
+            }
+            "authorization" => {
+                bearer = value.strip_prefix("Bearer ").map(|t| t.trim().to_string());
+            }
+            _ => {}
+        }
+    }
+
+    if content_length > MAX_BODY_BYTES {
+        return Err(HttpError::new(413, "Body too large"));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| bad_request("Incomplete body"))?;
+
+    let (path, query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+    let query_token = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+        .map(String::from);
+
+    Ok(HttpRequest {
+        method,
+        path: path.to_string(),
+        query_token,
+        bearer,
+        body,
+    })
+}
+
+/// Paths handled by `route`, for telling 405 from 404
+const ROUTES: [&str; 8] = [
+    "/status",
+    "/pomodoro/start",
+    "/stopwatch/start",
+    "/pause",
+    "/resume",
+    "/stop",
+    "/complete",
+    "/break/skip",
+];
+
+fn route(request: &HttpRequest) -> Result<ControlRequest, HttpError> {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => Ok(ControlRequest::Status),
+        ("POST", "/pomodoro/start") => {
+            Ok(ControlRequest::StartPomodoro(start_options(&request.body)?))
+        }
+        ("POST", "/stopwatch/start") => Ok(ControlRequest::StartStopwatch(start_options(
+            &request.body,
+        )?)),
+        ("POST", "/pause") => Ok(ControlRequest::Pause),
+        ("POST", "/resume") => Ok(ControlRequest::Resume),
+        ("POST", "/stop") => Ok(ControlRequest::Stop),
+        ("POST", "/complete") => Ok(ControlRequest::Complete),
+        ("POST", "/break/skip") => Ok(ControlRequest::SkipBreak),
+        (_, path) if ROUTES.contains(&path) => Err(HttpError::new(405, "Method not allowed")),
+        _ => Err(HttpError::new(404, "Not found")),
+    }
+}
+
+/// Optional JSON body of the start routes; empty means no task or project
+fn start_options(body: &[u8]) -> Result<StartOptions, HttpError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(StartOptions::default());
+    }
+    serde_json::from_slice(body).map_err(|e| HttpError::new(400, format!("Invalid body: {}", e)))
+}
+
+/// Serve `/events` until the client goes away or the server stops
+fn stream_events(stream: &mut TcpStream, ctx: &Context) -> std::io::Result<()> {
+    let events = ctx.events.subscribe();
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+    )?;
+    if let Ok(status) = ctx.control.execute(ControlRequest::Status) {
+        if let Ok(data) = serde_json::to_string(&status) {
+            write_event(stream, "status", &data)?;
+        }
+    }
+
+    let mut idle_secs = 0;
+    while !ctx.stop.load(Ordering::SeqCst) {
+        match events.recv_timeout(Duration::from_secs(1)) {
+            Ok(event) => {
+                write_event(stream, event.name, &event.data)?;
+                idle_secs = 0;
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                idle_secs += 1;
+                if idle_secs >= KEEPALIVE_SECS {
+                    stream.write_all(b": keepalive\n\n")?;
+                    stream.flush()?;
+                    idle_secs = 0;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Ok(())
+}
+
+fn write_event(stream: &mut TcpStream, name: &str, data: &str) -> std::io::Result<()> {
+    write!(stream, "event: {}\ndata: {}\n\n", name, data)?;
+    stream.flush()
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &str,
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn write_error(stream: &mut TcpStream, error: &HttpError) -> std::io::Result<()> {
+    let body = serde_json::json!({ "error": error.message }).to_string();
+    write_response(stream, error.status, "application/json", &body)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Compare tokens without leaking the matching prefix length through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::testing::CoreControl;
+    use crate::timer::TimerState;
+
+    const TOKEN: &str = "secret";
+
+    fn server() -> (HttpServer, Arc<EventHub>) {
+        let events = Arc::new(EventHub::new());
+        let control = Arc::new(CoreControl::default());
+        let server = HttpServer::start(0, TOKEN.to_string(), control, events.clone()).unwrap();
+        (server, events)
+    }
+
+    /// Send a request and return the status code and body
+    fn request(server: &HttpServer, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            TOKEN,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let code = response[9..12].parse().unwrap();
+        let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+        (code, body)
+    }
+
+    fn phase(body: &str) -> String {
+        let status: serde_json::Value = serde_json::from_str(body).unwrap();
+        status["phase"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_requires_token() {
+        let (server, _) = server();
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        stream
+            .write_all(b"GET /status HTTP/1.1\r\nAuthorization: Bearer wrong\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 401"));
+
+        // The query token is only for event streams
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        write!(stream, "GET /status?token={} HTTP/1.1\r\n\r\n", TOKEN).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 401"));
+    }
+
+    #[test]
+    fn test_caps_connections() {
+        let (server, _) = server();
+        let streams: Vec<TcpStream> = (0..MAX_CONNECTIONS)
+            .map(|_| {
+                let mut stream = TcpStream::connect(server.addr()).unwrap();
+                write!(stream, "GET /events?token={} HTTP/1.1\r\n\r\n", TOKEN).unwrap();
+                stream
+            })
+            .collect();
+
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 503"));
+        drop(streams);
+    }
+
+    #[test]
+    fn test_timer_lifecycle() {
+        let (server, _) = server();
+
+        let (code, body) = request(&server, "POST", "/pomodoro/start", r#"{"task":"写论文"}"#);
+        assert_eq!(code, 200);
+        assert_eq!(phase(&body), "running");
+        assert!(body.contains("\"currentTask\":\"写论文\""));
+
+        assert_eq!(phase(&request(&server, "POST", "/pause", "").1), "paused");
+        assert_eq!(phase(&request(&server, "POST", "/resume", "").1), "running");
+        assert_eq!(
+            phase(&request(&server, "POST", "/complete", "").1),
+            "shortBreak"
+        );
+        assert_eq!(
+            phase(&request(&server, "POST", "/break/skip", "").1),
+            "idle"
+        );
+
+        assert_eq!(request(&server, "POST", "/complete", "").0, 409);
+        assert_eq!(request(&server, "GET", "/pause", "").0, 405);
+        assert_eq!(request(&server, "GET", "/nope", "").0, 404);
+        assert_eq!(request(&server, "POST", "/pomodoro/start", "{").0, 400);
+    }
+
+    #[test]
+    fn test_shutdown_frees_port() {
+        let (mut server, events) = server();
+        let port = server.addr().port();
+        server.shutdown();
+        let control = Arc::new(CoreControl::default());
+        assert!(HttpServer::start(port, TOKEN.to_string(), control, events).is_ok());
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let (server, events) = server();
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        write!(stream, "GET /events?token={} HTTP/1.1\r\n\r\n", TOKEN).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+
+        let mut next_event = || loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(name) = line.strip_prefix("event: ") {
+                return name.trim().to_string();
+            }
+        };
+        assert_eq!(next_event(), "status");
+
+        request(&server, "POST", "/stopwatch/start", "");
+        let mut timer = TimerState::new();
+        timer.start_pomodoro();
+        events.publish(&timer.status());
+        assert_eq!(next_event(), "phase");
+        assert_eq!(next_event(), "tick");
+    }
+}
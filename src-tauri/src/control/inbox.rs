@@ -0,0 +1,435 @@
+use super::{ControlRequest, TimerControl};
+use crate::timer::TimerStatus;
+use crate::vault;
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Folder in the LifeOS storage directory that Obsidian drops commands into
+pub const INBOX_DIR: &str = "bar-tomato-inbox";
+/// Commands that took longer than this to arrive, e.g. through a sync
+/// service, are refused rather than run late
+pub const COMMAND_TTL_MS: u64 = 10 * 60 * 1000;
+/// How long replies are kept for the sender to read. Far longer than
+/// `COMMAND_TTL_MS`, since a reply is what stops a command synced back from
+/// another device running twice, and it must outlive every runnable command.
+const REPLY_TTL_MS: u64 = 24 * 60 * 60 * 1000;
+/// Unparseable files younger than this may still be being written
+const SETTLE_MS: u64 = 2_000;
+
+/// One command file, e.g.
+/// `{"id": "…", "createdAt": 1700000000000, "command": "startPomodoro", "task": "写论文"}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxCommand {
+    /// Commands are run at most once per id
+    pub id: String,
+    #[serde(flatten)]
+    pub request: ControlRequest,
+    /// Device hash or name that should run the command; any device when unset
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Unix milliseconds when the command was written. Required, so that no
+    /// command can run longer after it was written than its reply is kept.
+    pub created_at: u64,
+}
+
+/// Written to `replies/{id}.json` once a command has been handled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxReply {
+    pub id: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Timer status after the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<TimerStatus>,
+    /// Hash of the device that handled the command
+    pub device: String,
+    pub handled_at: u64,
+}
+
+pub fn inbox_dir(vault_path: &str) -> PathBuf {
+    vault::get_storage_dir(vault_path).join(INBOX_DIR)
+}
+
+/// Keep ids usable as file names
+fn reply_file_name(id: &str) -> String {
+    let safe: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.json", safe)
+}
+
+fn modified_ms(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// The command inbox of one vault, as seen from this device
+pub struct Inbox {
+    dir: PathBuf,
+    device_hash: String,
+    device_name: String,
+}
+
+impl Inbox {
+    pub fn new(dir: PathBuf, device_hash: String, device_name: String) -> Self {
+        Self {
+            dir,
+            device_hash,
+            device_name,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn replies_dir(&self) -> PathBuf {
+        self.dir.join("replies")
+    }
+
+    fn is_this_device(&self, device: &str) -> bool {
+        device == self.device_hash || device.trim().eq_ignore_ascii_case(&self.device_name)
+    }
+
+    /// Run every waiting command in file name order and reply to each.
+    /// Commands for other devices are left alone.
+    pub fn process(&self, control: &dyn TimerControl, now_ms: u64) -> Vec<InboxReply> {
+        let mut files: Vec<PathBuf> = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "json"))
+                .collect(),
+            Err(_) => return Vec::new(),
+        };
+        files.sort();
+
+        let replies = files
+            .iter()
+            .filter_map(|path| self.handle_file(path, control, now_ms))
+            .collect();
+        self.prune_replies(now_ms);
+        replies
+    }
+
+    fn handle_file(
+        &self,
+        path: &Path,
+        control: &dyn TimerControl,
+        now_ms: u64,
+    ) -> Option<InboxReply> {
+        let content = fs::read_to_string(path).ok()?;
+        let file_id = path.file_stem()?.to_string_lossy().to_string();
+
+        let command = match serde_json::from_str::<InboxCommand>(&content) {
+            Ok(c) if c.id.trim().is_empty() => {
+                return Some(self.finish(path, file_id, Err("Missing id".to_string()), now_ms));
+            }
+            Ok(c) => c,
+            Err(_) if now_ms.saturating_sub(modified_ms(path)) < SETTLE_MS => return None,
+            Err(e) => {
+                // Answer under the command's id if it got that far
+                let id = serde_json::from_str::<serde_json::Value>(&content)
+                    .ok()
+                    .and_then(|v| v.get("id")?.as_str().map(str::to_string))
+                    .filter(|id| !id.trim().is_empty())
+                    .unwrap_or(file_id);
+                let error = format!("Invalid command: {}", e);
+                return Some(self.finish(path, id, Err(error), now_ms));
+            }
+        };
+
+        if let Some(ref device) = command.device {
+            if !self.is_this_device(device) {
+                return None;
+            }
+        }
+        if self
+            .replies_dir()
+            .join(reply_file_name(&command.id))
+            .exists()
+        {
+            // Handled before, here or on another device, and synced back
+            let _ = fs::remove_file(path);
+            return None;
+        }
+
+        let result = if now_ms.saturating_sub(command.created_at) > COMMAND_TTL_MS {
+            Err("Command expired".to_string())
+        } else if command.created_at.saturating_sub(now_ms) > COMMAND_TTL_MS {
+            // Would otherwise stay runnable after its reply is pruned
+            Err("Command is dated in the future".to_string())
+        } else {
+            control.execute(command.request)
+        };
+        Some(self.finish(path, command.id, result, now_ms))
+    }
+
+    /// Reply, then remove the command. Should the removal fail, the reply
+    /// keeps the command from running twice.
+    fn finish(
+        &self,
+        path: &Path,
+        id: String,
+        result: Result<TimerStatus, String>,
+        now_ms: u64,
+    ) -> InboxReply {
+        let (status, error) = match result {
+            Ok(status) => (Some(status), None),
+            Err(e) => (None, Some(e)),
+        };
+        let reply = InboxReply {
+            id,
+            ok: error.is_none(),
+            error,
+            status,
+            device: self.device_hash.clone(),
+            handled_at: now_ms,
+        };
+        if let Err(e) = self.write_reply(&reply) {
+            log::warn!("{}", e);
+        }
+        if let Err(e) = fs::remove_file(path) {
+            log::warn!("Failed to remove inbox command: {}", e);
+        }
+        reply
+    }
+
+    fn write_reply(&self, reply: &InboxReply) -> Result<(), String> {
+        let dir = self.replies_dir();
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create replies folder: {}", e))?;
+        let content = serde_json::to_string_pretty(reply)
+            .map_err(|e| format!("Failed to serialize inbox reply: {}", e))?;
+        fs::write(dir.join(reply_file_name(&reply.id)), content)
+            .map_err(|e| format!("Failed to write inbox reply: {}", e))
+    }
+
+    fn prune_replies(&self, now_ms: u64) {
+        let Ok(entries) = fs::read_dir(self.replies_dir()) else {
+            return;
+        };
+        for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+            if now_ms.saturating_sub(modified_ms(&path)) > REPLY_TTL_MS {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Runs inbox commands as they appear. Stops when dropped.
+pub struct InboxWatcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl InboxWatcher {
+    pub fn start(inbox: Inbox, control: Arc<dyn TimerControl>) -> Result<Self, String> {
+        fs::create_dir_all(inbox.dir())
+            .map_err(|e| format!("Failed to create command inbox: {}", e))?;
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)
+            .map_err(|e| format!("Failed to watch command inbox: {}", e))?;
+        watcher
+            .watch(inbox.dir(), RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch command inbox: {}", e))?;
+
+        thread::spawn(move || {
+            // Commands written while the app was not running
+            inbox.process(control.as_ref(), now_ms());
+            // Ends when the watcher, and with it the sender, is dropped
+            while rx.recv().is_ok() {
+                // Let a burst of events, and the writer, settle first
+                thread::sleep(Duration::from_millis(200));
+                while rx.try_recv().is_ok() {}
+                inbox.process(control.as_ref(), now_ms());
+            }
+        });
+
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::testing::CoreControl;
+    use crate::timer::TimerPhase;
+
+    fn temp_inbox() -> Inbox {
+        let dir = std::env::temp_dir().join(format!("bar-tomato-inbox-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        Inbox::new(dir, "hash1".to_string(), "Laptop".to_string())
+    }
+
+    fn read_reply(inbox: &Inbox, id: &str) -> InboxReply {
+        let content = fs::read_to_string(inbox.replies_dir().join(reply_file_name(id))).unwrap();
+        serde_json::from_str(&content).unwrap()
+    }
+
+    #[test]
+    fn test_runs_commands_once() {
+        let inbox = temp_inbox();
+        let control = CoreControl::default();
+        let start = format!(
+            r#"{{"id": "a1", "createdAt": {}, "command": "startPomodoro", "task": "写论文"}}"#,
+            now_ms()
+        );
+        fs::write(inbox.dir().join("1.json"), &start).unwrap();
+        fs::write(
+            inbox.dir().join("2.json"),
+            format!(
+                r#"{{"id": "a2", "createdAt": {}, "command": "pause"}}"#,
+                now_ms()
+            ),
+        )
+        .unwrap();
+        fs::write(inbox.dir().join("notes.txt"), "ignored").unwrap();
+
+        let replies = inbox.process(&control, now_ms() + SETTLE_MS);
+        assert_eq!(replies.len(), 2);
+        assert!(replies.iter().all(|r| r.ok));
+        assert_eq!(
+            replies[1].status.as_ref().unwrap().phase,
+            TimerPhase::Paused
+        );
+        assert!(!inbox.dir().join("1.json").exists());
+        assert!(inbox.dir().join("notes.txt").exists());
+        assert_eq!(
+            read_reply(&inbox, "a1")
+                .status
+                .unwrap()
+                .current_task
+                .as_deref(),
+            Some("写论文")
+        );
+
+        // Synced back from another device: not run again
+        fs::write(inbox.dir().join("1.json"), &start).unwrap();
+        assert!(inbox.process(&control, now_ms() + SETTLE_MS).is_empty());
+        assert!(!inbox.dir().join("1.json").exists());
+        let status = control.execute(ControlRequest::Status).unwrap();
+        assert_eq!(status.phase, TimerPhase::Paused);
+
+        // Nor once its reply has been pruned, as it has expired by then
+        let later = now_ms() + REPLY_TTL_MS + 1;
+        fs::write(inbox.dir().join("1.json"), &start).unwrap();
+        inbox.prune_replies(later);
+        let replies = inbox.process(&control, later);
+        assert_eq!(replies[0].error.as_deref(), Some("Command expired"));
+        let status = control.execute(ControlRequest::Status).unwrap();
+        assert_eq!(status.phase, TimerPhase::Paused);
+
+        fs::remove_dir_all(inbox.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_validation_and_targeting() {
+        let inbox = temp_inbox();
+        let control = CoreControl::default();
+        let now = now_ms() + SETTLE_MS;
+        let command = |id: &str, created: u64, extra: &str| {
+            format!(
+                r#"{{"id": "{}", "createdAt": {}, "command": "stop"{}}}"#,
+                id, created, extra
+            )
+        };
+        let files = [
+            (
+                "bad.json",
+                format!(
+                    r#"{{"id": "b1", "createdAt": {}, "command": "explode"}}"#,
+                    now
+                ),
+            ),
+            ("broken.json", "{not json".to_string()),
+            ("noid.json", command(" ", now, "")),
+            ("old.json", command("b2", now - COMMAND_TTL_MS - 1, "")),
+            ("other.json", command("b3", now, r#", "device": "Desktop""#)),
+            ("mine.json", command("b4", now, r#", "device": "laptop""#)),
+            (
+                "undated.json",
+                r#"{"id": "b5", "command": "stop"}"#.to_string(),
+            ),
+            ("future.json", command("b6", now + COMMAND_TTL_MS + 1, "")),
+        ];
+        for (name, content) in &files {
+            fs::write(inbox.dir().join(name), content).unwrap();
+        }
+
+        let replies = inbox.process(&control, now);
+        let find = |id: &str| replies.iter().find(|r| r.id == id).unwrap();
+        assert!(find("b1")
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("Invalid command"));
+        assert!(!find("broken").ok);
+        assert_eq!(find("noid").error.as_deref(), Some("Missing id"));
+        assert_eq!(find("b2").error.as_deref(), Some("Command expired"));
+        assert!(find("b4").ok);
+        assert!(find("b5").error.as_deref().unwrap().contains("createdAt"));
+        assert_eq!(
+            find("b6").error.as_deref(),
+            Some("Command is dated in the future")
+        );
+        assert_eq!(replies.len(), 7);
+        assert!(inbox.dir().join("other.json").exists());
+
+        fs::remove_dir_all(inbox.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_watcher_picks_up_new_commands() {
+        let inbox = temp_inbox();
+        let dir = inbox.dir().to_path_buf();
+        let control = Arc::new(CoreControl::default());
+        let watcher = InboxWatcher::start(inbox, control.clone()).unwrap();
+
+        fs::write(
+            dir.join("c.json"),
+            format!(
+                r#"{{"id": "c1", "createdAt": {}, "command": "startStopwatch"}}"#,
+                now_ms()
+            ),
+        )
+        .unwrap();
+        let reply = dir.join("replies").join("c1.json");
+        for _ in 0..50 {
+            if reply.exists() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert!(reply.exists());
+        let status = control.execute(ControlRequest::Status).unwrap();
+        assert_eq!(status.phase, TimerPhase::Running);
+
+        drop(watcher);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,355 @@
+use super::{ControlRequest, TimerControl};
+use crate::timer::TimerStatus;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// Named pipe the app listens on for the command-line client
+#[cfg(windows)]
+pub const PIPE_NAME: &str = r"\\.\pipe\bar-tomato";
+
+/// A file in `$XDG_RUNTIME_DIR`, or in the temp dir where there is no
+/// runtime dir (macOS, Windows)
+pub fn runtime_file(name: &str) -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(name)
+}
+
+/// Unix socket the app listens on
+pub fn socket_path() -> PathBuf {
+    runtime_file("bar-tomato.sock")
+}
+
+/// Reply to one request line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "camelCase")]
+pub enum IpcResponse {
+    Ok { status: Box<TimerStatus> },
+    Error { message: String },
+}
+
+/// Answer newline-delimited JSON requests until the client hangs up
+fn serve_connection<S>(stream: S, control: &dyn TimerControl)
+where
+    for<'a> &'a S: Read + Write,
+{
+    let mut writer = &stream;
+    for line in BufReader::new(&stream).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => match control.execute(request) {
+                Ok(status) => IpcResponse::Ok {
+                    status: Box::new(status),
+                },
+                Err(message) => IpcResponse::Error { message },
+            },
+            Err(e) => IpcResponse::Error {
+                message: format!("Invalid request: {}", e),
+            },
+        };
+        let Ok(json) = serde_json::to_string(&response) else {
+            break;
+        };
+        if writeln!(writer, "{}", json)
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Local socket (named pipe on Windows) for the `bar-tomato-cli` client.
+/// Stops when dropped.
+pub struct IpcServer {
+    #[cfg(unix)]
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+#[cfg(unix)]
+impl IpcServer {
+    pub fn start(control: Arc<dyn TimerControl>) -> Result<Self, String> {
+        Self::start_at(socket_path(), control)
+    }
+
+    pub fn start_at(path: PathBuf, control: Arc<dyn TimerControl>) -> Result<Self, String> {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err("Another instance is already listening".to_string());
+            }
+            // Left behind by a crash
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove stale socket: {}", e))?;
+        }
+        let listener =
+            UnixListener::bind(&path).map_err(|e| format!("Failed to bind socket: {}", e))?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict socket permissions: {}", e))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let accept_thread = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop_flag.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(stream) => {
+                        let control = control.clone();
+                        thread::spawn(move || serve_connection(stream, control.as_ref()));
+                    }
+                    Err(e) => log::warn!("IPC connection failed: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            path,
+            stop,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    fn wake(&self) {
+        let _ = std::os::unix::net::UnixStream::connect(&self.path);
+    }
+}
+
+#[cfg(windows)]
+impl IpcServer {
+    pub fn start(control: Arc<dyn TimerControl>) -> Result<Self, String> {
+        // Fail early if another instance owns the pipe
+        let first = pipe::create(PIPE_NAME, true)
+            .map_err(|e| format!("Failed to create named pipe: {}", e))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let accept_thread = thread::spawn(move || {
+            let mut next = Some(first);
+            while !stop_flag.load(Ordering::SeqCst) {
+                let instance = match next.take() {
+                    Some(p) => Ok(p),
+                    None => pipe::create(PIPE_NAME, false),
+                };
+                match instance.and_then(pipe::connect) {
+                    Ok(file) => {
+                        if stop_flag.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        let control = control.clone();
+                        thread::spawn(move || serve_connection(file, control.as_ref()));
+                    }
+                    Err(e) => {
+                        log::warn!("IPC connection failed: {}", e);
+                        thread::sleep(std::time::Duration::from_millis(200));
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            stop,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    fn wake(&self) {
+        let _ = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(PIPE_NAME);
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        self.wake();
+        if let Some(handle) = self.accept_thread.take() {
+            let _ = handle.join();
+        }
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Connection to the running app
+pub struct IpcClient {
+    #[cfg(unix)]
+    stream: std::os::unix::net::UnixStream,
+    #[cfg(windows)]
+    stream: std::fs::File,
+}
+
+impl IpcClient {
+    /// None when no instance is listening
+    pub fn connect() -> Option<Self> {
+        #[cfg(unix)]
+        {
+            Self::connect_at(&socket_path())
+        }
+        #[cfg(windows)]
+        {
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(PIPE_NAME)
+                .ok()
+                .map(|stream| Self { stream })
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn connect_at(path: &std::path::Path) -> Option<Self> {
+        std::os::unix::net::UnixStream::connect(path)
+            .ok()
+            .map(|stream| Self { stream })
+    }
+
+    /// Send one request and wait for the app's answer
+    pub fn send(&mut self, request: &ControlRequest) -> Result<TimerStatus, String> {
+        let json = serde_json::to_string(request)
+            .map_err(|e| format!("Failed to serialize request: {}", e))?;
+        writeln!(self.stream, "{}", json)
+            .and_then(|_| self.stream.flush())
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+
+        let mut line = String::new();
+        BufReader::new(&self.stream)
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read response: {}", e))?;
+        match serde_json::from_str(&line).map_err(|e| format!("Failed to parse response: {}", e))? {
+            IpcResponse::Ok { status } => Ok(*status),
+            IpcResponse::Error { message } => Err(message),
+        }
+    }
+}
+
+/// Just enough of the Win32 named pipe API for a blocking server
+#[cfg(windows)]
+mod pipe {
+    use std::ffi::{c_void, OsStr};
+    use std::fs::File;
+    use std::io;
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle};
+
+    const PIPE_ACCESS_DUPLEX: u32 = 0x0000_0003;
+    const FILE_FLAG_FIRST_PIPE_INSTANCE: u32 = 0x0008_0000;
+    const PIPE_REJECT_REMOTE_CLIENTS: u32 = 0x0000_0008;
+    const PIPE_UNLIMITED_INSTANCES: u32 = 255;
+    const ERROR_PIPE_CONNECTED: i32 = 535;
+    const BUFFER_SIZE: u32 = 4096;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateNamedPipeW(
+            name: *const u16,
+            open_mode: u32,
+            pipe_mode: u32,
+            max_instances: u32,
+            out_buffer_size: u32,
+            in_buffer_size: u32,
+            default_timeout: u32,
+            security_attributes: *mut c_void,
+        ) -> RawHandle;
+        fn ConnectNamedPipe(pipe: RawHandle, overlapped: *mut c_void) -> i32;
+    }
+
+    /// New pipe instance in blocking byte mode
+    pub fn create(name: &str, first: bool) -> io::Result<File> {
+        let wide: Vec<u16> = OsStr::new(name).encode_wide().chain(Some(0)).collect();
+        let open_mode = if first {
+            PIPE_ACCESS_DUPLEX | FILE_FLAG_FIRST_PIPE_INSTANCE
+        } else {
+            PIPE_ACCESS_DUPLEX
+        };
+        // SAFETY: `wide` is NUL-terminated and outlives the call
+        let handle = unsafe {
+            CreateNamedPipeW(
+                wide.as_ptr(),
+                open_mode,
+                PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                std::ptr::null_mut(),
+            )
+        };
+        if handle as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the handle is valid and owned by nobody else
+        Ok(unsafe { File::from_raw_handle(handle) })
+    }
+
+    /// Block until a client opens the pipe
+    pub fn connect(pipe: File) -> io::Result<File> {
+        // SAFETY: the handle belongs to `pipe`, which is alive for the call
+        let ok = unsafe { ConnectNamedPipe(pipe.as_raw_handle(), std::ptr::null_mut()) };
+        if ok == 0 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(ERROR_PIPE_CONNECTED) {
+                return Err(err);
+            }
+        }
+        Ok(pipe)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::control::testing::CoreControl;
+    use crate::control::StartOptions;
+    use crate::timer::TimerPhase;
+
+    #[test]
+    fn test_round_trip_over_socket() {
+        let path = std::env::temp_dir().join(format!("bar-tomato-{}.sock", uuid::Uuid::new_v4()));
+        let server = IpcServer::start_at(path.clone(), Arc::new(CoreControl::default())).unwrap();
+        assert!(IpcServer::start_at(path.clone(), Arc::new(CoreControl::default())).is_err());
+
+        let mut client = IpcClient::connect_at(&path).unwrap();
+        let status = client
+            .send(&ControlRequest::StartPomodoro(StartOptions {
+                task: Some("写论文".to_string()),
+                ..Default::default()
+            }))
+            .unwrap();
+        assert_eq!(status.phase, TimerPhase::Running);
+        assert_eq!(status.current_task.as_deref(), Some("写论文"));
+
+        // Several requests share one connection
+        assert_eq!(
+            client.send(&ControlRequest::Pause).unwrap().phase,
+            TimerPhase::Paused
+        );
+        assert_eq!(
+            client.send(&ControlRequest::SkipBreak).unwrap().phase,
+            TimerPhase::Idle
+        );
+        assert_eq!(
+            client.send(&ControlRequest::Complete).unwrap_err(),
+            "No pomodoro running"
+        );
+
+        drop(server);
+        assert!(!path.exists());
+        assert!(IpcClient::connect_at(&path).is_none());
+    }
+}
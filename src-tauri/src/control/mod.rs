@@ -0,0 +1,19 @@
+pub mod app;
+pub mod deep_link;
+pub mod events;
+pub mod http;
+pub mod inbox;
+pub mod ipc;
+pub mod request;
+pub mod status_file;
+#[cfg(test)]
+pub(crate) mod testing;
+
+pub use app::*;
+pub use deep_link::*;
+pub use events::*;
+pub use http::*;
+pub use inbox::*;
+pub use ipc::*;
+pub use request::*;
+pub use status_file::*;
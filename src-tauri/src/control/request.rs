@@ -0,0 +1,35 @@
+use crate::timer::TimerStatus;
+use serde::{Deserialize, Serialize};
+
+/// Task and project for a new session. A `project` name without a path is
+/// matched against the vault's projects.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartOptions {
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub project_path: Option<String>,
+}
+
+/// A timer operation requested from outside the webview
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "camelCase")]
+pub enum ControlRequest {
+    Status,
+    StartPomodoro(StartOptions),
+    StartStopwatch(StartOptions),
+    Pause,
+    Resume,
+    Stop,
+    Complete,
+    SkipBreak,
+}
+
+/// Carries out control requests and returns the timer status afterwards.
+/// The app implements it on top of the Tauri commands.
+pub trait TimerControl: Send + Sync {
+    fn execute(&self, request: ControlRequest) -> Result<TimerStatus, String>;
+}
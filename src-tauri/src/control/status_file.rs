@@ -0,0 +1,140 @@
+use super::runtime_file;
+use crate::timer::{TimerMode, TimerPhase, TimerStatus};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Snapshots older than this are from an app that is no longer running
+pub const STALE_AFTER_MS: u64 = 5_000;
+
+/// What the tick loop writes for panels and bar widgets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusSnapshot {
+    pub status: TimerStatus,
+    /// Same text as the tray title, without the leading space
+    pub title: String,
+    /// Share of the current pomodoro or break that has passed, 0-100
+    pub progress: Option<u8>,
+    /// Unix milliseconds of the write
+    pub updated_at: u64,
+}
+
+impl StatusSnapshot {
+    pub fn new(status: TimerStatus, title: &str, updated_at: u64) -> Self {
+        Self {
+            progress: progress_percent(&status),
+            title: title.trim().to_string(),
+            status,
+            updated_at,
+        }
+    }
+
+    pub fn is_stale(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.updated_at) > STALE_AFTER_MS
+    }
+
+    /// CSS class for bar widgets: `idle`, `running`, `paused`, `overtime`
+    /// or `break`
+    pub fn css_class(&self) -> &'static str {
+        match self.status.phase {
+            TimerPhase::Idle => "idle",
+            TimerPhase::Running if self.status.overtime_secs > 0 => "overtime",
+            TimerPhase::Running => "running",
+            TimerPhase::Paused => "paused",
+            TimerPhase::ShortBreak | TimerPhase::LongBreak => "break",
+        }
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/bar-tomato-status.json`
+pub fn status_file_path() -> PathBuf {
+    runtime_file("bar-tomato-status.json")
+}
+
+/// None for stopwatches and idle timers, which have no fixed length
+pub fn progress_percent(status: &TimerStatus) -> Option<u8> {
+    if status.phase == TimerPhase::Idle
+        || (status.mode == TimerMode::Stopwatch && status.phase == TimerPhase::Running)
+    {
+        return None;
+    }
+    if status.overtime_secs > 0 {
+        return Some(100);
+    }
+    let total = status.elapsed_secs + status.remaining_secs?;
+    if total == 0 {
+        return None;
+    }
+    Some((status.elapsed_secs * 100 / total).min(100) as u8)
+}
+
+/// Replace the status file atomically, so readers never see half a write
+pub fn write_status_file(path: &Path, snapshot: &StatusSnapshot) -> Result<(), String> {
+    let content = serde_json::to_string(snapshot)
+        .map_err(|e| format!("Failed to serialize status: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("Failed to write status file: {}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to replace status file: {}", e))
+}
+
+/// None if the file is missing or unreadable
+pub fn read_status_file(path: &Path) -> Option<StatusSnapshot> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+
+    #[test]
+    fn test_progress_and_class() {
+        let mut timer = TimerState::new();
+        let idle = StatusSnapshot::new(timer.status(), "", 0);
+        assert_eq!(idle.progress, None);
+        assert_eq!(idle.css_class(), "idle");
+
+        timer.start_pomodoro();
+        timer.pause_elapsed = std::time::Duration::from_secs(15 * 60);
+        let running = StatusSnapshot::new(timer.status(), " 10:00", 0);
+        assert_eq!(running.progress, Some(60));
+        assert_eq!(running.css_class(), "running");
+        assert_eq!(running.title, "10:00");
+
+        timer.pause_elapsed = std::time::Duration::from_secs(30 * 60);
+        let over = StatusSnapshot::new(timer.status(), "", 0);
+        assert_eq!(over.progress, Some(100));
+        assert_eq!(over.css_class(), "overtime");
+
+        timer.start_stopwatch();
+        assert_eq!(progress_percent(&timer.status()), None);
+        timer.start_break();
+        assert_eq!(
+            StatusSnapshot::new(timer.status(), "", 0).css_class(),
+            "break"
+        );
+    }
+
+    #[test]
+    fn test_status_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("bar-tomato-status-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("status.json");
+        assert!(read_status_file(&path).is_none());
+
+        let mut timer = TimerState::new();
+        timer.start_pomodoro();
+        let snapshot = StatusSnapshot::new(timer.status(), " 25:00", 1_000);
+        write_status_file(&path, &snapshot).unwrap();
+
+        let read = read_status_file(&path).unwrap();
+        assert_eq!(read.title, "25:00");
+        assert_eq!(read.status.phase, TimerPhase::Running);
+        assert!(!read.is_stale(5_000));
+        assert!(read.is_stale(7_000));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+use super::{ControlRequest, TimerControl};
+use crate::timer::{TimerMode, TimerPhase, TimerState, TimerStatus};
+use std::sync::Mutex;
+
+/// Drives a bare TimerState the way the commands do, minus the vault
+pub struct CoreControl(Mutex<TimerState>);
+
+impl Default for CoreControl {
+    fn default() -> Self {
+        Self(Mutex::new(TimerState::new()))
+    }
+}
+
+impl TimerControl for CoreControl {
+    fn execute(&self, request: ControlRequest) -> Result<TimerStatus, String> {
+        let mut timer = self.0.lock().unwrap();
+        match request {
+            ControlRequest::Status => {}
+            ControlRequest::StartPomodoro(o) => {
+                timer.current_task = o.task;
+                timer.current_project = o.project;
+                timer.current_project_path = o.project_path;
+                timer.start_pomodoro();
+            }
+            ControlRequest::StartStopwatch(o) => {
+                timer.current_task = o.task;
+                timer.current_project = o.project;
+                timer.current_project_path = o.project_path;
+                timer.start_stopwatch();
+            }
+            ControlRequest::Pause => timer.pause(),
+            ControlRequest::Resume => timer.resume(),
+            ControlRequest::Stop => timer.stop(),
+            ControlRequest::Complete => {
+                if timer.phase != TimerPhase::Running || timer.mode != TimerMode::Pomodoro {
+                    return Err("No pomodoro running".to_string());
+                }
+                timer.pomodoro_count += 1;
+                timer.start_break();
+            }
+            ControlRequest::SkipBreak => timer.skip_break(),
+        }
+        Ok(timer.status())
+    }
+}
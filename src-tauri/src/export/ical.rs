@@ -0,0 +1,210 @@
+use super::{device_name_map, project_name_map, records_for_range, ExportSummary};
+use crate::vault::{project_folder, DeviceRecord};
+use chrono::{Duration, Local, NaiveDate, TimeZone, Utc};
+use std::collections::HashMap;
+
+/// Escape TEXT values per RFC 5545 §3.3.11
+pub fn ical_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line to at most 75 octets, never splitting a UTF-8 character
+pub fn fold_line(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out
+}
+
+fn utc_stamp(ms: u64) -> Option<String> {
+    Utc.timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+fn event_lines(
+    entry: &DeviceRecord,
+    project_names: &HashMap<String, String>,
+    device_names: &HashMap<String, String>,
+    dtstamp: &str,
+) -> Option<Vec<String>> {
+    let r = &entry.record;
+    if r.start_time == 0 || r.end_time <= r.start_time {
+        return None;
+    }
+
+    let project = r.project_path.as_ref().map(|pp| {
+        project_names
+            .get(pp)
+            .cloned()
+            .unwrap_or_else(|| project_folder(pp).to_string())
+    });
+    let summary = match (&r.task_text, &project) {
+        (Some(task), _) => task.clone(),
+        (None, Some(p)) => p.clone(),
+        (None, None) => "专注".to_string(),
+    };
+
+    let mut description = Vec::new();
+    if let Some(ref p) = project {
+        description.push(format!("项目: {}", p));
+    }
+    if let Some(index) = r.pomodoro_index {
+        description.push(format!("🍅 #{}", index));
+    }
+    description.push(format!("{} 分钟 ({})", r.duration, r.mode));
+    if let Some(device) = device_names.get(&entry.device_hash) {
+        description.push(format!("设备: {}", device));
+    }
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@bar-tomato", r.id),
+        format!("DTSTAMP:{}", dtstamp),
+        format!("DTSTART:{}", utc_stamp(r.start_time)?),
+        format!("DTEND:{}", utc_stamp(r.end_time)?),
+        format!("SUMMARY:{}", ical_escape(&summary)),
+        format!("DESCRIPTION:{}", ical_escape(&description.join("\n"))),
+    ];
+    if let Some(ref p) = project {
+        lines.push(format!("CATEGORIES:{}", ical_escape(p)));
+    }
+    lines.push("TRANSP:OPAQUE".to_string());
+    lines.push("END:VEVENT".to_string());
+    Some(lines)
+}
+
+/// Render records as an iCalendar document with one VEVENT per record
+pub fn render_calendar(
+    records: &[DeviceRecord],
+    project_names: &HashMap<String, String>,
+    device_names: &HashMap<String, String>,
+    dtstamp_ms: u64,
+) -> String {
+    let dtstamp = utc_stamp(dtstamp_ms).unwrap_or_default();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//mariozzj//Bar Tomato//CN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Bar Tomato".to_string(),
+    ];
+    for entry in records {
+        if let Some(event) = event_lines(entry, project_names, device_names, &dtstamp) {
+            lines.extend(event);
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out: String = lines
+        .iter()
+        .map(|l| fold_line(l))
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    out.push_str("\r\n");
+    out
+}
+
+/// Write the records in the inclusive range to an `.ics` file
+pub fn export_calendar(
+    vault_path: &str,
+    device_hash: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    path: &str,
+) -> Result<ExportSummary, String> {
+    let records = records_for_range(vault_path, device_hash, from, to)?;
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let content = render_calendar(
+        &records,
+        &project_name_map(vault_path),
+        &device_name_map(vault_path),
+        now_ms,
+    );
+
+    // Write atomically so a subscribed calendar never reads a partial file
+    let tmp_path = format!("{}.tmp", path);
+    std::fs::write(&tmp_path, content).map_err(|e| format!("Failed to write calendar: {}", e))?;
+    std::fs::rename(&tmp_path, path).map_err(|e| format!("Failed to rename calendar: {}", e))?;
+
+    Ok(ExportSummary {
+        path: path.to_string(),
+        count: records.len(),
+    })
+}
+
+/// Refresh the subscribed calendar file with the last `days` days of sessions
+pub fn sync_calendar(
+    vault_path: &str,
+    device_hash: &str,
+    path: &str,
+    days: u32,
+) -> Result<ExportSummary, String> {
+    let today = Local::now().date_naive();
+    let from = today - Duration::days(days.saturating_sub(1) as i64);
+    export_calendar(vault_path, device_hash, from, today, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::PomodoroRecord;
+
+    #[test]
+    fn test_fold_line() {
+        let long = "SUMMARY:".to_string() + &"番".repeat(30);
+        let folded = fold_line(&long);
+        for line in folded.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        assert_eq!(folded.replace("\r\n ", ""), long);
+    }
+
+    #[test]
+    fn test_render_calendar() {
+        let entry = DeviceRecord {
+            device_hash: "abc".to_string(),
+            record: PomodoroRecord {
+                id: "r1".to_string(),
+                date: "2026-01-05".to_string(),
+                start_time: 1_767_574_800_000,
+                end_time: 1_767_576_300_000,
+                duration: 25,
+                mode: "pomodoro".to_string(),
+                status: "completed".to_string(),
+                project_path: Some("1. 项目/科研-A/A.README.md".to_string()),
+                task_text: Some("Draft, review".to_string()),
+                pomodoro_index: Some(3),
+                source: None,
+            },
+        };
+        let ics = render_calendar(
+            &[entry],
+            &HashMap::new(),
+            &HashMap::new(),
+            1_767_574_800_000,
+        );
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:r1@bar-tomato\r\n"));
+        assert!(ics.contains("DTSTART:20260105T010000Z\r\n"));
+        assert!(ics.contains("DTEND:20260105T012500Z\r\n"));
+        assert!(ics.contains("SUMMARY:Draft\\, review\r\n"));
+        assert!(ics.contains("🍅 #3"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
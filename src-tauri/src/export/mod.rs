@@ -0,0 +1,5 @@
+pub mod ical;
+pub mod records;
+
+pub use ical::*;
+pub use records::*;
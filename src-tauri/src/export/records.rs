@@ -0,0 +1,378 @@
+use crate::stats::{parse_range, project_area};
+use crate::vault::{self, project_folder, DeviceRecord};
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportColumn {
+    Id,
+    Date,
+    Start,
+    End,
+    Duration,
+    Mode,
+    Status,
+    Project,
+    ProjectPath,
+    Area,
+    Task,
+    PomodoroIndex,
+    Device,
+    Source,
+}
+
+impl ExportColumn {
+    pub const DEFAULT: [ExportColumn; 11] = [
+        ExportColumn::Id,
+        ExportColumn::Date,
+        ExportColumn::Start,
+        ExportColumn::End,
+        ExportColumn::Duration,
+        ExportColumn::Mode,
+        ExportColumn::Status,
+        ExportColumn::Project,
+        ExportColumn::Area,
+        ExportColumn::Task,
+        ExportColumn::Device,
+    ];
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            ExportColumn::Id => "id",
+            ExportColumn::Date => "date",
+            ExportColumn::Start => "start",
+            ExportColumn::End => "end",
+            ExportColumn::Duration => "duration_minutes",
+            ExportColumn::Mode => "mode",
+            ExportColumn::Status => "status",
+            ExportColumn::Project => "project",
+            ExportColumn::ProjectPath => "project_path",
+            ExportColumn::Area => "area",
+            ExportColumn::Task => "task",
+            ExportColumn::PomodoroIndex => "pomodoro_index",
+            ExportColumn::Device => "device",
+            ExportColumn::Source => "source",
+        }
+    }
+}
+
+/// Lookups used to turn raw record fields into readable values
+pub struct ExportContext<'a, Tz: TimeZone> {
+    pub tz: &'a Tz,
+    /// Project README path → display name
+    pub project_names: &'a HashMap<String, String>,
+    /// Device hash → device name
+    pub device_names: &'a HashMap<String, String>,
+}
+
+/// ISO 8601 timestamp with the zone offset, or empty for legacy records
+fn iso_timestamp<Tz: TimeZone>(tz: &Tz, ms: u64) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    if ms == 0 {
+        return String::new();
+    }
+    tz.timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, false))
+        .unwrap_or_default()
+}
+
+fn column_value<Tz: TimeZone>(
+    column: ExportColumn,
+    entry: &DeviceRecord,
+    ctx: &ExportContext<Tz>,
+) -> Value
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let r = &entry.record;
+    let opt = |v: &Option<String>| v.clone().map(Value::String).unwrap_or(Value::Null);
+    match column {
+        ExportColumn::Id => Value::String(r.id.clone()),
+        ExportColumn::Date => Value::String(r.date.clone()),
+        ExportColumn::Start => Value::String(iso_timestamp(ctx.tz, r.start_time)),
+        ExportColumn::End => Value::String(iso_timestamp(ctx.tz, r.end_time)),
+        ExportColumn::Duration => Value::from(r.duration),
+        ExportColumn::Mode => Value::String(r.mode.clone()),
+        ExportColumn::Status => Value::String(r.status.clone()),
+        ExportColumn::Project => opt(&r.project_path.as_ref().map(|pp| {
+            ctx.project_names
+                .get(pp)
+                .cloned()
+                .unwrap_or_else(|| project_folder(pp).to_string())
+        })),
+        ExportColumn::ProjectPath => opt(&r.project_path),
+        ExportColumn::Area => opt(&r
+            .project_path
+            .as_deref()
+            .map(|pp| project_area(pp).to_string())),
+        ExportColumn::Task => opt(&r.task_text),
+        ExportColumn::PomodoroIndex => r.pomodoro_index.map(Value::from).unwrap_or(Value::Null),
+        ExportColumn::Device => Value::String(
+            ctx.device_names
+                .get(&entry.device_hash)
+                .cloned()
+                .unwrap_or_else(|| entry.device_hash.clone()),
+        ),
+        ExportColumn::Source => opt(&r.source),
+    }
+}
+
+/// One flat JSON object per record with the selected columns
+pub fn flatten_record<Tz: TimeZone>(
+    entry: &DeviceRecord,
+    columns: &[ExportColumn],
+    ctx: &ExportContext<Tz>,
+) -> Map<String, Value>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    columns
+        .iter()
+        .map(|c| (c.header().to_string(), column_value(*c, entry, ctx)))
+        .collect()
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+pub fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Render records in the given format
+pub fn render_records<Tz: TimeZone>(
+    records: &[DeviceRecord],
+    format: ExportFormat,
+    columns: &[ExportColumn],
+    ctx: &ExportContext<Tz>,
+) -> Result<String, String>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match format {
+        ExportFormat::Csv => {
+            let mut out = columns
+                .iter()
+                .map(|c| c.header())
+                .collect::<Vec<_>>()
+                .join(",");
+            out.push('\n');
+            for entry in records {
+                let row: Vec<String> = columns
+                    .iter()
+                    .map(|c| match column_value(*c, entry, ctx) {
+                        Value::String(s) => csv_escape(&s),
+                        Value::Null => String::new(),
+                        other => other.to_string(),
+                    })
+                    .collect();
+                out.push_str(&row.join(","));
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        ExportFormat::JsonLines => {
+            let mut out = String::new();
+            for entry in records {
+                let line = serde_json::to_string(&flatten_record(entry, columns, ctx))
+                    .map_err(|e| format!("Failed to serialize record: {}", e))?;
+                out.push_str(&line);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        ExportFormat::Json => {
+            let rows: Vec<Map<String, Value>> = records
+                .iter()
+                .map(|entry| flatten_record(entry, columns, ctx))
+                .collect();
+            serde_json::to_string_pretty(&rows)
+                .map_err(|e| format!("Failed to serialize records: {}", e))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportOptions {
+    /// First day of the range, `YYYY-MM-DD` (inclusive)
+    pub from: String,
+    /// Last day of the range, `YYYY-MM-DD` (inclusive)
+    pub to: String,
+    pub format: ExportFormat,
+    /// Columns to write; defaults to the saved export columns
+    #[serde(default)]
+    pub columns: Option<Vec<ExportColumn>>,
+    /// Destination file
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub path: String,
+    pub count: usize,
+}
+
+/// Records from every device in the inclusive date range, oldest first
+pub fn records_for_range(
+    vault_path: &str,
+    device_hash: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<DeviceRecord>, String> {
+    let mut records: Vec<DeviceRecord> = vault::read_all_records(vault_path, device_hash)?
+        .into_iter()
+        .filter(|r| {
+            NaiveDate::parse_from_str(&r.record.date, "%Y-%m-%d")
+                .map(|d| d >= from && d <= to)
+                .unwrap_or(false)
+        })
+        .collect();
+    records.sort_by_key(|r| (r.record.date.clone(), r.record.start_time));
+    Ok(records)
+}
+
+/// Project README path → display name, for every project in the vault
+pub fn project_name_map(vault_path: &str) -> HashMap<String, String> {
+    vault::scan_projects(vault_path)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| (p.readme_path, p.display_name))
+        .collect()
+}
+
+/// Device hash → device name from the shared devices file
+pub fn device_name_map(vault_path: &str) -> HashMap<String, String> {
+    vault::read_devices(vault_path)
+        .map(|f| {
+            f.devices
+                .into_iter()
+                .map(|(hash, d)| (hash, d.name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Write the records in the range to `options.path`
+pub fn export_records(
+    vault_path: &str,
+    device_hash: &str,
+    options: &ExportOptions,
+    default_columns: Option<&[ExportColumn]>,
+) -> Result<ExportSummary, String> {
+    let (from, to) = parse_range(&options.from, &options.to)?;
+    let records = records_for_range(vault_path, device_hash, from, to)?;
+
+    let columns: &[ExportColumn] = options
+        .columns
+        .as_deref()
+        .or(default_columns)
+        .filter(|c| !c.is_empty())
+        .unwrap_or(&ExportColumn::DEFAULT);
+
+    let project_names = project_name_map(vault_path);
+    let device_names = device_name_map(vault_path);
+    let ctx = ExportContext {
+        tz: &Local,
+        project_names: &project_names,
+        device_names: &device_names,
+    };
+    let content = render_records(&records, options.format, columns, &ctx)?;
+
+    std::fs::write(&options.path, content).map_err(|e| format!("Failed to write export: {}", e))?;
+
+    Ok(ExportSummary {
+        path: options.path.clone(),
+        count: records.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::PomodoroRecord;
+    use chrono::FixedOffset;
+
+    fn entry() -> DeviceRecord {
+        DeviceRecord {
+            device_hash: "abc".to_string(),
+            record: PomodoroRecord {
+                id: "r1".to_string(),
+                date: "2026-01-05".to_string(),
+                start_time: 1_767_574_800_000, // 2026-01-05T01:00:00Z
+                end_time: 1_767_576_300_000,
+                duration: 25,
+                mode: "pomodoro".to_string(),
+                status: "completed".to_string(),
+                project_path: Some("1. 项目/科研-A/A.README.md".to_string()),
+                task_text: Some("Write \"intro\", draft".to_string()),
+                pomodoro_index: Some(1),
+                source: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let tz = FixedOffset::east_opt(8 * 3600).unwrap();
+        let names = HashMap::new();
+        let devices = HashMap::from([("abc".to_string(), "laptop".to_string())]);
+        let ctx = ExportContext {
+            tz: &tz,
+            project_names: &names,
+            device_names: &devices,
+        };
+        let columns = [
+            ExportColumn::Start,
+            ExportColumn::Duration,
+            ExportColumn::Project,
+            ExportColumn::Task,
+            ExportColumn::Device,
+        ];
+        let csv = render_records(&[entry()], ExportFormat::Csv, &columns, &ctx).unwrap();
+        assert_eq!(
+            csv,
+            "start,duration_minutes,project,task,device\n\
+             2026-01-05T09:00:00+08:00,25,科研-A,\"Write \"\"intro\"\", draft\",laptop\n"
+        );
+    }
+
+    #[test]
+    fn test_json_lines_export() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let names = HashMap::new();
+        let ctx = ExportContext {
+            tz: &tz,
+            project_names: &names,
+            device_names: &names,
+        };
+        let out = render_records(
+            &[entry(), entry()],
+            ExportFormat::JsonLines,
+            &[ExportColumn::Id, ExportColumn::Area, ExportColumn::Source],
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(out.lines().count(), 2);
+        let row: Value = serde_json::from_str(out.lines().next().unwrap()).unwrap();
+        assert_eq!(row["area"], "科研");
+        assert_eq!(row["source"], Value::Null);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::timer::{TimerMode, TimerPhase, TimerStatus};
+use serde::{Deserialize, Serialize};
+
+/// Something that happened to the timer, as seen by hooks and webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerEvent {
+    PomodoroStarted,
+    StopwatchStarted,
+    PomodoroCompleted,
+    BreakStarted,
+    BreakCompleted,
+    SessionStopped,
+    Paused,
+    Resumed,
+}
+
+impl TimerEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimerEvent::PomodoroStarted => "pomodoro_started",
+            TimerEvent::StopwatchStarted => "stopwatch_started",
+            TimerEvent::PomodoroCompleted => "pomodoro_completed",
+            TimerEvent::BreakStarted => "break_started",
+            TimerEvent::BreakCompleted => "break_completed",
+            TimerEvent::SessionStopped => "session_stopped",
+            TimerEvent::Paused => "paused",
+            TimerEvent::Resumed => "resumed",
+        }
+    }
+
+    /// Events about a session that just ended, described by the status from
+    /// before the transition
+    fn describes_previous(&self) -> bool {
+        matches!(
+            self,
+            TimerEvent::PomodoroCompleted | TimerEvent::BreakCompleted | TimerEvent::SessionStopped
+        )
+    }
+}
+
+/// What hooks and webhooks receive for one event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventPayload {
+    pub event: TimerEvent,
+    /// Unix milliseconds
+    pub timestamp: u64,
+    /// The session the event is about: the new one for starts, the one that
+    /// ended for completions and stops
+    pub status: TimerStatus,
+}
+
+fn is_break(phase: TimerPhase) -> bool {
+    matches!(phase, TimerPhase::ShortBreak | TimerPhase::LongBreak)
+}
+
+fn started(mode: TimerMode) -> TimerEvent {
+    match mode {
+        TimerMode::Pomodoro => TimerEvent::PomodoroStarted,
+        TimerMode::Stopwatch => TimerEvent::StopwatchStarted,
+    }
+}
+
+/// Events implied by going from `prev` to `next`
+pub fn transition_events(prev: &TimerStatus, next: &TimerStatus) -> Vec<TimerEvent> {
+    let (from, to) = (prev.phase, next.phase);
+
+    match (from, to) {
+        (TimerPhase::Idle, TimerPhase::Running) => vec![started(next.mode)],
+        (TimerPhase::Running, TimerPhase::Running) if prev.mode != next.mode => {
+            vec![TimerEvent::SessionStopped, started(next.mode)]
+        }
+        (TimerPhase::Running, TimerPhase::Paused) => vec![TimerEvent::Paused],
+        (TimerPhase::Paused, TimerPhase::Running) if prev.mode == next.mode => {
+            vec![TimerEvent::Resumed]
+        }
+        (TimerPhase::Paused, TimerPhase::Running) => {
+            vec![TimerEvent::SessionStopped, started(next.mode)]
+        }
+        (TimerPhase::Running | TimerPhase::Paused, TimerPhase::Idle) => {
+            vec![TimerEvent::SessionStopped]
+        }
+        (TimerPhase::Running | TimerPhase::Paused, b) if is_break(b) => {
+            vec![TimerEvent::PomodoroCompleted, TimerEvent::BreakStarted]
+        }
+        (a, TimerPhase::Running) if is_break(a) => {
+            vec![TimerEvent::BreakCompleted, started(next.mode)]
+        }
+        (a, TimerPhase::Idle) if is_break(a) => vec![TimerEvent::BreakCompleted],
+        (a, b) if is_break(a) && is_break(b) && a != b => {
+            vec![TimerEvent::BreakCompleted, TimerEvent::BreakStarted]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Payloads for one timer change, given the statuses taken just before and
+/// just after it under the same lock
+pub fn transition_payloads(
+    prev: &TimerStatus,
+    next: &TimerStatus,
+    timestamp: u64,
+) -> Vec<EventPayload> {
+    transition_events(prev, next)
+        .into_iter()
+        .map(|event| EventPayload {
+            event,
+            timestamp,
+            status: if event.describes_previous() {
+                prev.clone()
+            } else {
+                next.clone()
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+
+    /// Apply `change` and return the events it caused
+    fn events(timer: &mut TimerState, change: impl FnOnce(&mut TimerState)) -> Vec<TimerEvent> {
+        let before = timer.status();
+        change(timer);
+        transition_payloads(&before, &timer.status(), 0)
+            .into_iter()
+            .map(|p| p.event)
+            .collect()
+    }
+
+    #[test]
+    fn test_pomodoro_cycle() {
+        let mut timer = TimerState::new();
+        assert_eq!(
+            events(&mut timer, TimerState::start_pomodoro),
+            vec![TimerEvent::PomodoroStarted]
+        );
+        assert_eq!(
+            events(&mut timer, TimerState::pause),
+            vec![TimerEvent::Paused]
+        );
+        assert_eq!(
+            events(&mut timer, TimerState::resume),
+            vec![TimerEvent::Resumed]
+        );
+        assert_eq!(
+            events(&mut timer, TimerState::start_break),
+            vec![TimerEvent::PomodoroCompleted, TimerEvent::BreakStarted]
+        );
+        assert_eq!(
+            events(&mut timer, TimerState::start_pomodoro),
+            vec![TimerEvent::BreakCompleted, TimerEvent::PomodoroStarted]
+        );
+    }
+
+    #[test]
+    fn test_stop_reports_the_ended_session() {
+        let mut timer = TimerState::new();
+        timer.current_task = Some("写论文".to_string());
+        assert_eq!(
+            events(&mut timer, TimerState::start_stopwatch),
+            vec![TimerEvent::StopwatchStarted]
+        );
+
+        let before = timer.status();
+        timer.stop();
+        let payloads = transition_payloads(&before, &timer.status(), 42);
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0].event, TimerEvent::SessionStopped);
+        assert_eq!(payloads[0].timestamp, 42);
+        assert_eq!(payloads[0].status.mode, TimerMode::Stopwatch);
+        assert_eq!(payloads[0].status.phase, TimerPhase::Running);
+    }
+
+    #[test]
+    fn test_event_names() {
+        assert_eq!(
+            serde_json::to_string(&TimerEvent::PomodoroCompleted).unwrap(),
+            "\"pomodoro_completed\""
+        );
+        assert_eq!(TimerEvent::SessionStopped.as_str(), "session_stopped");
+    }
+}
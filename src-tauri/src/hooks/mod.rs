@@ -0,0 +1,7 @@
+pub mod events;
+pub mod shell;
+pub mod webhook;
+
+pub use events::*;
+pub use shell::*;
+pub use webhook::*;
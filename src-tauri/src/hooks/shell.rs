@@ -0,0 +1,275 @@
+use super::{EventPayload, TimerEvent};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 10;
+
+/// A user command run when the timer changes, e.g. to toggle do-not-disturb
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellHook {
+    /// Run through `sh -c` (`cmd /C` on Windows)
+    pub command: String,
+    /// Events that trigger the hook; empty means every event
+    #[serde(default)]
+    pub events: Vec<TimerEvent>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_HOOK_TIMEOUT_SECS
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ShellHook {
+    pub fn matches(&self, event: TimerEvent) -> bool {
+        self.enabled
+            && !self.command.trim().is_empty()
+            && (self.events.is_empty() || self.events.contains(&event))
+    }
+}
+
+/// How a hook run ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookOutput {
+    /// None when the process was killed, by the timeout or a signal
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Name a value has in JSON, e.g. `shortBreak` for a phase
+fn json_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+/// `BAR_TOMATO_*` variables describing the event and its session
+pub fn hook_env(payload: &EventPayload) -> Vec<(&'static str, String)> {
+    let status = &payload.status;
+    let mut env = vec![
+        ("BAR_TOMATO_EVENT", payload.event.as_str().to_string()),
+        ("BAR_TOMATO_PHASE", json_name(&status.phase)),
+        ("BAR_TOMATO_MODE", json_name(&status.mode)),
+        ("BAR_TOMATO_ELAPSED_SECS", status.elapsed_secs.to_string()),
+        (
+            "BAR_TOMATO_POMODORO_COUNT",
+            status.pomodoro_count.to_string(),
+        ),
+    ];
+    let optional = [
+        ("BAR_TOMATO_TASK", status.current_task.clone()),
+        ("BAR_TOMATO_PROJECT", status.current_project.clone()),
+        (
+            "BAR_TOMATO_PROJECT_PATH",
+            status.current_project_path.clone(),
+        ),
+        (
+            "BAR_TOMATO_REMAINING_SECS",
+            status.remaining_secs.map(|s| s.to_string()),
+        ),
+    ];
+    env.extend(
+        optional
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|v| (key, v))),
+    );
+    env
+}
+
+fn shell_command(command: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+fn read_all<R: Read + Send + 'static>(reader: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut r) = reader {
+            let _ = r.read_to_end(&mut buf);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
+/// Run one hook to completion, killing it after its timeout. The payload
+/// is passed as JSON on stdin and as environment variables.
+pub fn run_shell_hook(hook: &ShellHook, payload: &EventPayload) -> Result<HookOutput, String> {
+    let json = serde_json::to_string(payload)
+        .map_err(|e| format!("Failed to serialize hook payload: {}", e))?;
+
+    let mut child = shell_command(&hook.command)
+        .envs(hook_env(payload))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run hook: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks that ignore stdin close it early; that is not an error
+        let _ = stdin.write_all(json.as_bytes());
+    }
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+
+    let deadline = Instant::now() + Duration::from_secs(hook.timeout_secs);
+    let exit = loop {
+        match child.try_wait() {
+            Ok(Some(exit)) => break Some(exit),
+            Ok(None) if Instant::now() >= deadline => break None,
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("Failed to wait for hook: {}", e)),
+        }
+    };
+
+    let Some(exit) = exit else {
+        let _ = child.kill();
+        let _ = child.wait();
+        // Grandchildren may still hold the pipes open, so don't wait for output
+        return Ok(HookOutput {
+            exit_code: None,
+            timed_out: true,
+            stdout: String::new(),
+            stderr: String::new(),
+        });
+    };
+
+    Ok(HookOutput {
+        exit_code: exit.code(),
+        timed_out: false,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Run every hook that wants this event, each on its own thread, and log
+/// how it went
+pub fn dispatch_shell_hooks(hooks: &[ShellHook], payload: &EventPayload) {
+    for hook in hooks.iter().filter(|h| h.matches(payload.event)) {
+        let hook = hook.clone();
+        let payload = payload.clone();
+        thread::spawn(move || {
+            let event = payload.event.as_str();
+            match run_shell_hook(&hook, &payload) {
+                Ok(output) => {
+                    if output.timed_out {
+                        log::warn!(
+                            "Hook `{}` for {} timed out after {}s",
+                            hook.command,
+                            event,
+                            hook.timeout_secs
+                        );
+                    } else if output.exit_code == Some(0) {
+                        log::info!("Hook `{}` for {} succeeded", hook.command, event);
+                    } else {
+                        log::warn!(
+                            "Hook `{}` for {} exited with {:?}",
+                            hook.command,
+                            event,
+                            output.exit_code
+                        );
+                    }
+                    if !output.stdout.trim().is_empty() {
+                        log::info!("Hook stdout: {}", output.stdout.trim());
+                    }
+                    if !output.stderr.trim().is_empty() {
+                        log::warn!("Hook stderr: {}", output.stderr.trim());
+                    }
+                }
+                Err(e) => log::warn!("{}", e),
+            }
+        });
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+
+    fn payload(event: TimerEvent) -> EventPayload {
+        let mut timer = TimerState::new();
+        timer.current_task = Some("写论文".to_string());
+        timer.current_project = Some("Thesis".to_string());
+        timer.start_pomodoro();
+        EventPayload {
+            event,
+            timestamp: 1_000,
+            status: timer.status(),
+        }
+    }
+
+    fn hook(command: &str) -> ShellHook {
+        ShellHook {
+            command: command.to_string(),
+            events: Vec::new(),
+            timeout_secs: 5,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_env_and_stdin() {
+        let output = run_shell_hook(
+            &hook("echo \"$BAR_TOMATO_EVENT|$BAR_TOMATO_PHASE|$BAR_TOMATO_TASK|$BAR_TOMATO_REMAINING_SECS\"; cat"),
+            &payload(TimerEvent::PomodoroStarted),
+        )
+        .unwrap();
+        assert_eq!(output.exit_code, Some(0));
+
+        let mut lines = output.stdout.lines();
+        assert_eq!(lines.next(), Some("pomodoro_started|running|写论文|1500"));
+        let json: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(json["event"], "pomodoro_started");
+        assert_eq!(json["status"]["currentProject"], "Thesis");
+    }
+
+    #[test]
+    fn test_failure_and_timeout() {
+        let failed =
+            run_shell_hook(&hook("echo oops >&2; exit 3"), &payload(TimerEvent::Paused)).unwrap();
+        assert_eq!(failed.exit_code, Some(3));
+        assert_eq!(failed.stderr.trim(), "oops");
+
+        let mut slow = hook("sleep 5");
+        slow.timeout_secs = 0;
+        let started = Instant::now();
+        let output = run_shell_hook(&slow, &payload(TimerEvent::Paused)).unwrap();
+        assert!(output.timed_out);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let mut h = hook("true");
+        assert!(h.matches(TimerEvent::BreakStarted));
+        h.events = vec![TimerEvent::PomodoroStarted];
+        assert!(h.matches(TimerEvent::PomodoroStarted));
+        assert!(!h.matches(TimerEvent::BreakStarted));
+        h.enabled = false;
+        assert!(!h.matches(TimerEvent::PomodoroStarted));
+    }
+}
@@ -0,0 +1,526 @@
+use super::{EventPayload, TimerEvent};
+use crate::vault::PomodoroRecord;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// First retry delay; doubles with every failed attempt
+const INITIAL_BACKOFF_MS: u64 = 10_000;
+const MAX_BACKOFF_MS: u64 = 60 * 60 * 1000;
+/// Deliveries still failing after this long are dropped
+const MAX_AGE_MS: u64 = 3 * 24 * 60 * 60 * 1000;
+/// Oldest deliveries are dropped beyond this many
+const MAX_QUEUED: usize = 1000;
+const SEND_TIMEOUT_SECS: u64 = 10;
+/// How long the worker sleeps when nothing is due
+const IDLE_WAIT_MS: u64 = 60_000;
+
+pub const SIGNATURE_HEADER: &str = "X-Bar-Tomato-Signature";
+/// Unix seconds of the attempt, covered by the signature so receivers can
+/// refuse replayed requests
+pub const TIMESTAMP_HEADER: &str = "X-Bar-Tomato-Timestamp";
+
+/// An endpoint that receives timer events and written records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub url: String,
+    /// Signs each request with HMAC-SHA256 when set
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Timer events to send; empty means every event
+    #[serde(default)]
+    pub events: Vec<TimerEvent>,
+    /// Also send each record as it is written
+    #[serde(default = "default_true")]
+    pub records: bool,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Body of a webhook request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebhookPayload {
+    Timer(EventPayload),
+    Record {
+        timestamp: u64,
+        record: PomodoroRecord,
+    },
+}
+
+impl WebhookPayload {
+    /// Sent as `X-Bar-Tomato-Event`
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            WebhookPayload::Timer(p) => p.event.as_str(),
+            WebhookPayload::Record { .. } => "record_written",
+        }
+    }
+
+    fn wanted_by(&self, hook: &Webhook) -> bool {
+        if !hook.enabled || hook.url.trim().is_empty() {
+            return false;
+        }
+        match self {
+            WebhookPayload::Timer(p) => hook.events.is_empty() || hook.events.contains(&p.event),
+            WebhookPayload::Record { .. } => hook.records,
+        }
+    }
+}
+
+/// `sha256=<hex>` over `{timestamp}.{body}`, as in the signature header
+pub fn signature(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body.as_bytes());
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+fn backoff_ms(attempts: u32) -> u64 {
+    let doublings = attempts.saturating_sub(1).min(16);
+    (INITIAL_BACKOFF_MS << doublings).min(MAX_BACKOFF_MS)
+}
+
+/// One request waiting to be sent, kept on disk until it succeeds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Delivery {
+    /// Sent as `X-Bar-Tomato-Delivery` so receivers can ignore retried duplicates
+    id: String,
+    /// Also names the webhook in the config, whose secret is looked up when
+    /// sending so it never sits in the queue file
+    url: String,
+    event: String,
+    body: String,
+    attempts: u32,
+    created_at: u64,
+    next_attempt_at: u64,
+    #[serde(default)]
+    last_error: Option<String>,
+}
+
+enum Outcome {
+    Delivered,
+    /// Worth trying again later: network errors, 5xx, 408 and 429
+    Retry(String),
+    /// The endpoint refused the request; retrying won't help
+    Rejected(String),
+}
+
+fn send(delivery: &Delivery, secret: Option<&str>, now_ms: u64) -> Outcome {
+    let timestamp = now_ms / 1000;
+    let mut request = ureq::post(&delivery.url)
+        .timeout(Duration::from_secs(SEND_TIMEOUT_SECS))
+        .set("Content-Type", "application/json")
+        .set(
+            "User-Agent",
+            concat!("bar-tomato/", env!("CARGO_PKG_VERSION")),
+        )
+        .set("X-Bar-Tomato-Event", &delivery.event)
+        .set("X-Bar-Tomato-Delivery", &delivery.id)
+        .set(TIMESTAMP_HEADER, &timestamp.to_string());
+    if let Some(secret) = secret.filter(|s| !s.is_empty()) {
+        request = request.set(
+            SIGNATURE_HEADER,
+            &signature(secret, timestamp, &delivery.body),
+        );
+    }
+
+    match request.send_string(&delivery.body) {
+        Ok(_) => Outcome::Delivered,
+        Err(ureq::Error::Status(code, _))
+            if (400..500).contains(&code) && code != 408 && code != 429 =>
+        {
+            Outcome::Rejected(format!("HTTP {}", code))
+        }
+        Err(ureq::Error::Status(code, _)) => Outcome::Retry(format!("HTTP {}", code)),
+        Err(e) => Outcome::Retry(e.to_string()),
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    deliveries: Vec<Delivery>,
+    /// Set by `enqueue` so the worker doesn't miss new work
+    woken: bool,
+}
+
+/// Outgoing webhook deliveries, persisted so they survive restarts and
+/// offline periods
+pub struct WebhookQueue {
+    path: PathBuf,
+    state: Mutex<QueueState>,
+    wake: Condvar,
+}
+
+impl WebhookQueue {
+    /// Resume the queue saved at `path`, if any
+    pub fn load(path: PathBuf) -> Self {
+        let deliveries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            state: Mutex::new(QueueState {
+                deliveries,
+                woken: false,
+            }),
+            wake: Condvar::new(),
+        }
+    }
+
+    /// Deliveries not yet sent
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().deliveries.len()
+    }
+
+    /// Queue `payload` for every webhook that wants it; returns how many
+    pub fn enqueue(&self, hooks: &[Webhook], payload: &WebhookPayload, now_ms: u64) -> usize {
+        let targets: Vec<&Webhook> = hooks.iter().filter(|h| payload.wanted_by(h)).collect();
+        if targets.is_empty() {
+            return 0;
+        }
+        let body = match serde_json::to_string(payload) {
+            Ok(b) => b,
+            Err(e) => {
+                log::warn!("Failed to serialize webhook payload: {}", e);
+                return 0;
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        for hook in &targets {
+            state.deliveries.push(Delivery {
+                id: uuid::Uuid::new_v4().to_string(),
+                url: hook.url.trim().to_string(),
+                event: payload.event_name().to_string(),
+                body: body.clone(),
+                attempts: 0,
+                created_at: now_ms,
+                next_attempt_at: now_ms,
+                last_error: None,
+            });
+        }
+        let overflow = state.deliveries.len().saturating_sub(MAX_QUEUED);
+        if overflow > 0 {
+            log::warn!(
+                "Webhook queue full, dropping {} oldest deliveries",
+                overflow
+            );
+            state.deliveries.drain(..overflow);
+        }
+        self.save(&state.deliveries);
+        state.woken = true;
+        self.wake.notify_one();
+        targets.len()
+    }
+
+    /// Send every delivery that is due, rescheduling failures and signing
+    /// with the secrets in `hooks`. Deliveries whose webhook has since been
+    /// removed are dropped. Returns when the next delivery is due, if any
+    /// are left.
+    pub fn process_due(&self, hooks: &[Webhook], now_ms: u64) -> Option<u64> {
+        let due: Vec<Delivery> = {
+            let state = self.state.lock().unwrap();
+            state
+                .deliveries
+                .iter()
+                .filter(|d| d.next_attempt_at <= now_ms)
+                .cloned()
+                .collect()
+        };
+
+        if !due.is_empty() {
+            // Send without the lock so enqueueing never waits on the network
+            let outcomes: Vec<(String, Outcome)> = due
+                .iter()
+                .map(|d| {
+                    let outcome = match hooks.iter().find(|h| h.url.trim() == d.url) {
+                        Some(hook) => send(d, hook.secret.as_deref(), now_ms),
+                        None => Outcome::Rejected("webhook removed".to_string()),
+                    };
+                    (d.id.clone(), outcome)
+                })
+                .collect();
+
+            let mut state = self.state.lock().unwrap();
+            for (id, outcome) in outcomes {
+                let Some(index) = state.deliveries.iter().position(|d| d.id == id) else {
+                    continue;
+                };
+                let delivery = &mut state.deliveries[index];
+                match outcome {
+                    Outcome::Delivered => {
+                        state.deliveries.remove(index);
+                    }
+                    Outcome::Rejected(e) => {
+                        log::warn!(
+                            "Webhook {} rejected {}: {}",
+                            delivery.url,
+                            delivery.event,
+                            e
+                        );
+                        state.deliveries.remove(index);
+                    }
+                    Outcome::Retry(e)
+                        if now_ms.saturating_sub(delivery.created_at) > MAX_AGE_MS =>
+                    {
+                        log::warn!(
+                            "Giving up on webhook {} for {}: {}",
+                            delivery.url,
+                            delivery.event,
+                            e
+                        );
+                        state.deliveries.remove(index);
+                    }
+                    Outcome::Retry(e) => {
+                        delivery.attempts += 1;
+                        delivery.next_attempt_at = now_ms + backoff_ms(delivery.attempts);
+                        delivery.last_error = Some(e);
+                    }
+                }
+            }
+            self.save(&state.deliveries);
+        }
+
+        let state = self.state.lock().unwrap();
+        state.deliveries.iter().map(|d| d.next_attempt_at).min()
+    }
+
+    /// Deliver in the background for the life of the app, reading the
+    /// configured webhooks from `hooks` before each round
+    pub fn start_worker(queue: Arc<Self>, hooks: impl Fn() -> Vec<Webhook> + Send + 'static) {
+        thread::spawn(move || loop {
+            let next = queue.process_due(&hooks(), now_ms());
+            let wait = next
+                .map(|t| t.saturating_sub(now_ms()))
+                .unwrap_or(IDLE_WAIT_MS)
+                .clamp(100, IDLE_WAIT_MS);
+
+            let state = queue.state.lock().unwrap();
+            let (mut state, _) = queue
+                .wake
+                .wait_timeout_while(state, Duration::from_millis(wait), |s| !s.woken)
+                .unwrap();
+            state.woken = false;
+        });
+    }
+
+    fn save(&self, deliveries: &[Delivery]) {
+        if let Err(e) = write_queue(&self.path, deliveries) {
+            log::warn!("{}", e);
+        }
+    }
+}
+
+fn write_queue(path: &Path, deliveries: &[Delivery]) -> Result<(), String> {
+    let content = serde_json::to_string(deliveries)
+        .map_err(|e| format!("Failed to serialize webhook queue: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("Failed to write webhook queue: {}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to replace webhook queue: {}", e))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    struct Received {
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl Received {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        }
+    }
+
+    /// Answers one request per status in `statuses`, passing each on
+    fn stand_in(statuses: Vec<u16>) -> (String, mpsc::Receiver<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut headers = Vec::new();
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).unwrap();
+                    let Some((k, v)) = line.trim_end().split_once(':') else {
+                        break;
+                    };
+                    headers.push((k.trim().to_string(), v.trim().to_string()));
+                }
+                let length: usize = headers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                    .map_or(0, |(_, v)| v.parse().unwrap());
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                write!(
+                    &stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+                let _ = tx.send(Received {
+                    headers,
+                    body: String::from_utf8(body).unwrap(),
+                });
+            }
+        });
+        (url, rx)
+    }
+
+    fn temp_queue() -> (WebhookQueue, PathBuf) {
+        let path =
+            std::env::temp_dir().join(format!("bar-tomato-webhooks-{}.json", uuid::Uuid::new_v4()));
+        (WebhookQueue::load(path.clone()), path)
+    }
+
+    fn hook(url: &str) -> Webhook {
+        Webhook {
+            url: url.to_string(),
+            secret: Some("s3cret".to_string()),
+            events: Vec::new(),
+            records: true,
+            enabled: true,
+        }
+    }
+
+    fn started() -> WebhookPayload {
+        let mut timer = TimerState::new();
+        timer.start_pomodoro();
+        WebhookPayload::Timer(EventPayload {
+            event: TimerEvent::PomodoroStarted,
+            timestamp: 1_000,
+            status: timer.status(),
+        })
+    }
+
+    #[test]
+    fn test_signature_and_backoff() {
+        let body = r#"{"type":"timer"}"#;
+        let signed = signature("s3cret", 1_700_000_000, body);
+        assert!(signed.starts_with("sha256="));
+        assert_eq!(signed.len(), "sha256=".len() + 64);
+        // A replayed body under another timestamp doesn't verify
+        assert_ne!(signed, signature("s3cret", 1_700_000_001, body));
+        assert_ne!(signed, signature("other", 1_700_000_000, body));
+        assert_eq!(backoff_ms(1), INITIAL_BACKOFF_MS);
+        assert_eq!(backoff_ms(3), INITIAL_BACKOFF_MS * 4);
+        assert_eq!(backoff_ms(40), MAX_BACKOFF_MS);
+    }
+
+    #[test]
+    fn test_signed_delivery_with_retry() {
+        let (url, rx) = stand_in(vec![503, 200]);
+        let (queue, path) = temp_queue();
+        let hooks = [hook(&url)];
+        assert_eq!(queue.enqueue(&hooks, &started(), 0), 1);
+        // The secret stays in the config
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("s3cret"));
+
+        // Server error: kept and rescheduled
+        assert_eq!(queue.process_due(&hooks, 0), Some(INITIAL_BACKOFF_MS));
+        let first = rx.recv().unwrap();
+        assert_eq!(queue.pending(), 1);
+
+        // Not due yet
+        assert_eq!(queue.process_due(&hooks, 1_000), Some(INITIAL_BACKOFF_MS));
+        assert!(rx.try_recv().is_err());
+
+        assert_eq!(queue.process_due(&hooks, INITIAL_BACKOFF_MS), None);
+        let second = rx.recv().unwrap();
+        assert_eq!(queue.pending(), 0);
+
+        assert_eq!(
+            second.header("X-Bar-Tomato-Event"),
+            Some("pomodoro_started")
+        );
+        assert_eq!(
+            first.header("X-Bar-Tomato-Delivery"),
+            second.header("X-Bar-Tomato-Delivery")
+        );
+        let timestamp: u64 = second.header(TIMESTAMP_HEADER).unwrap().parse().unwrap();
+        assert_eq!(timestamp, INITIAL_BACKOFF_MS / 1000);
+        assert_eq!(
+            second.header(SIGNATURE_HEADER),
+            Some(signature("s3cret", timestamp, &second.body).as_str())
+        );
+        let json: serde_json::Value = serde_json::from_str(&second.body).unwrap();
+        assert_eq!(json["type"], "timer");
+        assert_eq!(json["event"], "pomodoro_started");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_queue_survives_restart_and_drops_rejected() {
+        let (queue, path) = temp_queue();
+        // Nothing listens here, as when offline
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let offline = format!("http://{}/hook", closed.local_addr().unwrap());
+        drop(closed);
+
+        let mut picky = hook(&offline);
+        picky.events = vec![TimerEvent::BreakStarted];
+        assert_eq!(queue.enqueue(&[picky], &started(), 0), 0);
+
+        queue.enqueue(&[hook(&offline)], &started(), 0);
+        assert!(queue.process_due(&[hook(&offline)], 0).is_some());
+        drop(queue);
+
+        let queue = WebhookQueue::load(path.clone());
+        assert_eq!(queue.pending(), 1);
+        let (url, rx) = stand_in(vec![400]);
+        queue.state.lock().unwrap().deliveries[0].url = url.clone();
+        assert_eq!(queue.process_due(&[hook(&url)], MAX_BACKOFF_MS), None);
+        assert!(rx.recv().is_ok());
+        assert_eq!(queue.pending(), 0);
+
+        // A webhook removed from the config gets nothing more
+        queue.enqueue(&[hook(&url)], &started(), 0);
+        assert_eq!(queue.process_due(&[], 0), None);
+        assert_eq!(queue.pending(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
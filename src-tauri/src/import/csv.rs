@@ -0,0 +1,178 @@
+use super::{parse_duration_minutes, parse_timestamp, ImportedEntry, RowError};
+use chrono::TimeZone;
+use serde::Deserialize;
+
+/// Parse RFC 4180 CSV: quoted fields, doubled quotes, CRLF, optional BOM
+pub fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|r| r.iter().any(|f| !f.trim().is_empty()));
+    rows
+}
+
+/// Which CSV header holds each value. `start`/`end` may be full timestamps,
+/// or times of day combined with `date`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnMapping {
+    pub date: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub end_date: Option<String>,
+    pub duration: Option<String>,
+    pub project: Option<String>,
+    pub task: Option<String>,
+    pub mode: Option<String>,
+}
+
+impl ColumnMapping {
+    /// Columns of a Toggl Track detailed CSV export
+    pub fn toggl() -> Self {
+        Self {
+            date: Some("Start date".to_string()),
+            start: Some("Start time".to_string()),
+            end: Some("End time".to_string()),
+            end_date: Some("End date".to_string()),
+            duration: Some("Duration".to_string()),
+            project: Some("Project".to_string()),
+            task: Some("Description".to_string()),
+            mode: None,
+        }
+    }
+}
+
+fn lookup<'a>(headers: &[String], row: &'a [String], column: &Option<String>) -> Option<&'a str> {
+    let name = column.as_ref()?;
+    let idx = headers
+        .iter()
+        .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))?;
+    row.get(idx).map(|v| v.trim()).filter(|v| !v.is_empty())
+}
+
+fn join_date(date: Option<&str>, time: &str) -> String {
+    match date {
+        // A bare time of day such as `09:30` or `09:30:00`
+        Some(d) if time.len() <= 8 => format!("{} {}", d, time),
+        _ => time.to_string(),
+    }
+}
+
+/// Turn CSV rows into entries using `mapping`. Row numbers in errors are
+/// 1-based and count the header row.
+pub fn entries_from_csv<Tz: TimeZone>(
+    content: &str,
+    mapping: &ColumnMapping,
+    tz: &Tz,
+) -> Result<(Vec<ImportedEntry>, Vec<RowError>), String> {
+    let mut rows = parse_csv(content).into_iter();
+    let headers = rows.next().ok_or("CSV file is empty")?;
+    if mapping.start.is_none() {
+        return Err("Column mapping needs a start column".to_string());
+    }
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (i, row) in rows.enumerate() {
+        let row_number = i + 2;
+        let fail = |reason: &str| RowError {
+            row: row_number,
+            reason: reason.to_string(),
+        };
+
+        let date = lookup(&headers, &row, &mapping.date);
+        let start = match lookup(&headers, &row, &mapping.start)
+            .and_then(|s| parse_timestamp(&join_date(date, s), tz))
+        {
+            Some(s) => s,
+            None => {
+                errors.push(fail("Missing or invalid start"));
+                continue;
+            }
+        };
+
+        let end_date = lookup(&headers, &row, &mapping.end_date).or(date);
+        let end = lookup(&headers, &row, &mapping.end)
+            .and_then(|e| parse_timestamp(&join_date(end_date, e), tz));
+        let duration = lookup(&headers, &row, &mapping.duration).and_then(parse_duration_minutes);
+
+        let (end_ms, duration) = match (end, duration) {
+            (Some(e), Some(d)) => (e, d),
+            (Some(e), None) => (e, (e.saturating_sub(start) / 60_000) as u32),
+            (None, Some(d)) => (start + d as u64 * 60_000, d),
+            (None, None) => {
+                errors.push(fail("Missing end or duration"));
+                continue;
+            }
+        };
+
+        entries.push(ImportedEntry {
+            row: row_number,
+            start_ms: start,
+            end_ms,
+            duration,
+            mode: lookup(&headers, &row, &mapping.mode).map(|m| m.to_lowercase()),
+            project: lookup(&headers, &row, &mapping.project).map(String::from),
+            task: lookup(&headers, &row, &mapping.task).map(String::from),
+        });
+    }
+
+    Ok((entries, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_parse_csv_quotes() {
+        let rows = parse_csv("\u{feff}a,b\r\n\"x, y\",\"say \"\"hi\"\"\"\r\n\r\n");
+        assert_eq!(rows, vec![vec!["a", "b"], vec!["x, y", "say \"hi\""]]);
+    }
+
+    #[test]
+    fn test_toggl_rows() {
+        let csv = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+                   me,me@x,,DualBasic,,Write draft,No,2026-01-05,09:00:00,2026-01-05,10:30:00,01:30:00,\n\
+                   me,me@x,,,,Broken,No,,,,,,\n";
+        let (entries, errors) = entries_from_csv(csv, &ColumnMapping::toggl(), &Utc).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].duration, 90);
+        assert_eq!(entries[0].project.as_deref(), Some("DualBasic"));
+        assert_eq!(entries[0].task.as_deref(), Some("Write draft"));
+        assert_eq!(errors[0].row, 3);
+    }
+}
@@ -0,0 +1,254 @@
+use super::{parse_duration_minutes, parse_timestamp, DurationUnit, ImportedEntry, RowError};
+use chrono::TimeZone;
+use serde_json::Value;
+
+const START_KEYS: &[&str] = &[
+    "startTime",
+    "start_time",
+    "start",
+    "startedAt",
+    "started_at",
+    "begin",
+];
+const END_KEYS: &[&str] = &[
+    "endTime",
+    "end_time",
+    "end",
+    "endedAt",
+    "ended_at",
+    "finishedAt",
+    "stop",
+];
+/// Duration keys whose name gives the unit
+const DURATION_KEYS: &[(&str, DurationUnit)] = &[
+    ("durationSeconds", DurationUnit::Seconds),
+    ("duration_seconds", DurationUnit::Seconds),
+    ("duration_sec", DurationUnit::Seconds),
+    ("seconds", DurationUnit::Seconds),
+    ("durationMinutes", DurationUnit::Minutes),
+    ("duration_minutes", DurationUnit::Minutes),
+    ("duration_min", DurationUnit::Minutes),
+    ("minutes", DurationUnit::Minutes),
+];
+/// Duration keys that could hold seconds or minutes
+const BARE_DURATION_KEYS: &[&str] = &["duration", "length"];
+const TASK_KEYS: &[&str] = &[
+    "taskText",
+    "task_text",
+    "task",
+    "title",
+    "name",
+    "description",
+    "note",
+];
+const PROJECT_KEYS: &[&str] = &["project", "projectName", "project_name", "tag", "category"];
+const MODE_KEYS: &[&str] = &["mode", "type", "kind"];
+
+/// Find the list of sessions: a top-level array, or the first array under a
+/// common key such as `records` (bar-tomato / LifeOS) or `sessions`
+fn session_list(json: &Value) -> Option<&Vec<Value>> {
+    if let Some(list) = json.as_array() {
+        return Some(list);
+    }
+    [
+        "records",
+        "sessions",
+        "pomodoros",
+        "entries",
+        "data",
+        "items",
+    ]
+    .iter()
+    .find_map(|k| json.get(*k).and_then(|v| v.as_array()))
+}
+
+fn first<'a>(obj: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter()
+        .find_map(|k| obj.get(*k))
+        .filter(|v| !v.is_null())
+}
+
+fn as_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        // Project objects such as `{ "name": "..." }`
+        Value::Object(_) => value.get("name").and_then(as_text),
+        _ => None,
+    }
+}
+
+fn as_timestamp<Tz: TimeZone>(value: &Value, tz: &Tz) -> Option<u64> {
+    match value {
+        Value::Number(n) => parse_timestamp(&n.to_string(), tz),
+        Value::String(s) => parse_timestamp(s, tz),
+        _ => None,
+    }
+}
+
+/// A plain number of seconds or minutes, as a number or a numeric string
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Minutes from a duration value. `H:MM[:SS]` strings carry their own unit;
+/// a plain number takes the unit from its key, then from how well it fits
+/// the start/end span, then from `default_unit`. Without any of those the
+/// row is ambiguous.
+fn as_duration(
+    value: &Value,
+    key_unit: Option<DurationUnit>,
+    span_minutes: Option<u32>,
+    default_unit: Option<DurationUnit>,
+) -> Result<u32, String> {
+    if let Value::String(s) = value {
+        if s.contains(':') {
+            return parse_duration_minutes(s).ok_or_else(|| format!("Invalid duration: {}", s));
+        }
+    }
+    let n = as_number(value)
+        .filter(|n| *n >= 0.0)
+        .ok_or_else(|| format!("Invalid duration: {}", value))?;
+
+    let unit = key_unit
+        .or_else(|| {
+            span_minutes.map(|span| {
+                let span = span as f64;
+                if (n / 60.0 - span).abs() < (n - span).abs() {
+                    DurationUnit::Seconds
+                } else {
+                    DurationUnit::Minutes
+                }
+            })
+        })
+        .or(default_unit)
+        .ok_or("Duration has no unit; use a *_sec or *_min key or choose a unit")?;
+
+    Ok(match unit {
+        DurationUnit::Seconds => (n / 60.0).round(),
+        DurationUnit::Minutes => n.round(),
+    } as u32)
+}
+
+/// Turn another app's JSON export into entries. Row numbers are 1-based
+/// positions in the session list. `duration_unit` applies to plain
+/// `duration` numbers on rows without an end time.
+pub fn entries_from_json<Tz: TimeZone>(
+    content: &str,
+    tz: &Tz,
+    duration_unit: Option<DurationUnit>,
+) -> Result<(Vec<ImportedEntry>, Vec<RowError>), String> {
+    let json: Value =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    let list = session_list(&json).ok_or("No list of sessions found in JSON")?;
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (i, item) in list.iter().enumerate() {
+        let row = i + 1;
+        let start = match first(item, START_KEYS).and_then(|v| as_timestamp(v, tz)) {
+            Some(s) => s,
+            None => {
+                errors.push(RowError {
+                    row,
+                    reason: "Missing or invalid start".to_string(),
+                });
+                continue;
+            }
+        };
+        let end = first(item, END_KEYS).and_then(|v| as_timestamp(v, tz));
+        let span = end.map(|e| (e.saturating_sub(start) / 60_000) as u32);
+        let duration_value = DURATION_KEYS
+            .iter()
+            .find_map(|(k, unit)| item.get(*k).map(|v| (v, Some(*unit))))
+            .or_else(|| first(item, BARE_DURATION_KEYS).map(|v| (v, None)))
+            .filter(|(v, _)| !v.is_null());
+        let duration = match duration_value {
+            Some((v, unit)) => match as_duration(v, unit, span, duration_unit) {
+                Ok(d) => Some(d),
+                Err(reason) => {
+                    errors.push(RowError { row, reason });
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        let (end_ms, duration) = match (end, duration) {
+            (Some(e), Some(d)) => (e, d),
+            (Some(e), None) => (e, span.unwrap_or(0)),
+            (None, Some(d)) => (start + d as u64 * 60_000, d),
+            (None, None) => {
+                errors.push(RowError {
+                    row,
+                    reason: "Missing end or duration".to_string(),
+                });
+                continue;
+            }
+        };
+
+        entries.push(ImportedEntry {
+            row,
+            start_ms: start,
+            end_ms,
+            duration,
+            mode: first(item, MODE_KEYS)
+                .and_then(as_text)
+                .map(|m| m.to_lowercase()),
+            project: first(item, PROJECT_KEYS).and_then(as_text),
+            task: first(item, TASK_KEYS).and_then(as_text),
+        });
+    }
+
+    Ok((entries, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_generic_pomodoro_json() {
+        let json = r#"{
+            "sessions": [
+                { "startedAt": "2026-01-05T09:00:00Z", "durationSeconds": 1500, "title": "Read", "project": { "name": "DualBasic" } },
+                { "start": 1767607200000, "end": 1767608700000, "type": "Pomodoro" },
+                { "title": "no start" }
+            ]
+        }"#;
+        let (entries, errors) = entries_from_json(json, &Utc, None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].duration, 25);
+        assert_eq!(entries[0].project.as_deref(), Some("DualBasic"));
+        assert_eq!(entries[0].task.as_deref(), Some("Read"));
+        assert_eq!(entries[1].duration, 25);
+        assert_eq!(entries[1].mode.as_deref(), Some("pomodoro"));
+        assert_eq!(errors[0].row, 3);
+    }
+
+    #[test]
+    fn test_duration_units() {
+        let json = r#"[
+            { "start": "2026-01-05T09:00:00Z", "end": "2026-01-05T09:25:00Z", "duration": 1500 },
+            { "start": "2026-01-05T10:00:00Z", "end": "2026-01-05T10:25:00Z", "duration": 25 },
+            { "start": "2026-01-05T11:00:00Z", "duration_min": 25 },
+            { "start": "2026-01-05T12:00:00Z", "duration": 25 }
+        ]"#;
+        let (entries, errors) = entries_from_json(json, &Utc, None).unwrap();
+        assert_eq!(
+            entries.iter().map(|e| e.duration).collect::<Vec<_>>(),
+            vec![25, 25, 25]
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].row, 4);
+
+        let (entries, errors) = entries_from_json(json, &Utc, Some(DurationUnit::Seconds)).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(entries[3].duration, 0);
+    }
+}
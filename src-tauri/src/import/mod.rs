@@ -0,0 +1,449 @@
+pub mod csv;
+pub mod json;
+
+pub use self::csv::*;
+pub use self::json::*;
+
+use crate::vault::{self, PomodoroRecord, Project};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// One session read from another tool, before it becomes a record
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedEntry {
+    /// Row (CSV) or list position (JSON) the entry came from, 1-based
+    pub row: usize,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub duration: u32,
+    pub mode: Option<String>,
+    pub project: Option<String>,
+    pub task: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowError {
+    pub row: usize,
+    pub reason: String,
+}
+
+/// Parse an RFC 3339 timestamp, a local date-time, or epoch seconds/milliseconds
+pub fn parse_timestamp<Tz: TimeZone>(value: &str, tz: &Tz) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(n) = value.parse::<f64>() {
+        // Epoch seconds from 2001 on have ten digits; shorter numbers are
+        // more likely clock times or counters than timestamps
+        if n < 1e9 {
+            return None;
+        }
+        // Anything past ~2001 in milliseconds is larger than any plausible seconds value
+        return Some(if n > 1e12 {
+            n as u64
+        } else {
+            (n * 1000.0) as u64
+        });
+    }
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return u64::try_from(t.timestamp_millis()).ok();
+    }
+
+    const FORMATS: [&str; 6] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y/%m/%d %H:%M",
+    ];
+    FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(value, f).ok())
+        .and_then(|naive| tz.from_local_datetime(&naive).earliest())
+        .and_then(|t| u64::try_from(t.timestamp_millis()).ok())
+}
+
+/// Parse a duration as `H:MM:SS`, `H:MM` or a plain number of minutes
+pub fn parse_duration_minutes(value: &str) -> Option<u32> {
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    let minutes = match parts.as_slice() {
+        [m] => m.parse::<f64>().ok()?,
+        [h, m] => h.parse::<f64>().ok()? * 60.0 + m.parse::<f64>().ok()?,
+        [h, m, s] => {
+            h.parse::<f64>().ok()? * 60.0 + m.parse::<f64>().ok()? + s.parse::<f64>().ok()? / 60.0
+        }
+        _ => return None,
+    };
+    (minutes >= 0.0).then(|| minutes.round() as u32)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportFormat {
+    /// Toggl Track detailed CSV export
+    Toggl,
+    /// Any CSV with a column mapping
+    Csv,
+    /// JSON export from another pomodoro app
+    Json,
+}
+
+impl ImportFormat {
+    fn source(&self) -> &'static str {
+        match self {
+            ImportFormat::Toggl => "toggl",
+            ImportFormat::Csv => "csv",
+            ImportFormat::Json => "json",
+        }
+    }
+
+    /// Time trackers log open-ended sessions; pomodoro apps log pomodoros
+    fn default_mode(&self) -> &'static str {
+        match self {
+            ImportFormat::Toggl | ImportFormat::Csv => "stopwatch",
+            ImportFormat::Json => "pomodoro",
+        }
+    }
+}
+
+/// Unit of bare numeric durations in a JSON export
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DurationUnit {
+    Seconds,
+    Minutes,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOptions {
+    /// File to import
+    pub path: String,
+    pub format: ImportFormat,
+    /// Required for `csv`; `toggl` uses its own columns
+    #[serde(default)]
+    pub mapping: Option<ColumnMapping>,
+    /// For `json` rows with a plain `duration` number and no end time
+    #[serde(default)]
+    pub duration_unit: Option<DurationUnit>,
+    /// Only report what would be imported
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    /// Rows or list entries read from the file
+    pub total_rows: usize,
+    /// Records that were (or would be) written
+    pub imported: usize,
+    /// Entries skipped because they overlap a record already in the vault
+    pub duplicates: usize,
+    pub errors: Vec<RowError>,
+    /// Distinct source project names matched to vault projects
+    pub matched_projects: Vec<String>,
+    /// Distinct source project names with no vault project
+    pub unmatched_projects: Vec<String>,
+    pub total_minutes: u32,
+    pub first_date: Option<String>,
+    pub last_date: Option<String>,
+    /// False for a dry run or when there was nothing to import
+    pub written: bool,
+}
+
+/// Match a source project name to a vault project: exact display name first,
+/// then the short name after the area prefix, then a substring match
+pub fn match_project<'a>(name: &str, projects: &'a [Project]) -> Option<&'a Project> {
+    let needle = name.trim().to_lowercase();
+    if needle.is_empty() {
+        return None;
+    }
+    let short = |p: &Project| {
+        let lower = p.display_name.to_lowercase();
+        lower
+            .split_once('-')
+            .map(|(_, s)| s.to_string())
+            .unwrap_or(lower)
+    };
+
+    projects
+        .iter()
+        .find(|p| p.display_name.to_lowercase() == needle)
+        .or_else(|| projects.iter().find(|p| short(p) == needle))
+        .or_else(|| {
+            projects
+                .iter()
+                .find(|p| p.display_name.to_lowercase().contains(&needle))
+        })
+}
+
+/// Turn entries into records, skipping any that overlap `existing` or each other
+pub fn entries_to_records<Tz: TimeZone>(
+    entries: Vec<ImportedEntry>,
+    format: ImportFormat,
+    projects: &[Project],
+    existing: &[PomodoroRecord],
+    tz: &Tz,
+    summary: &mut ImportSummary,
+) -> Vec<PomodoroRecord> {
+    let mut records: Vec<PomodoroRecord> = Vec::new();
+    let mut matched = std::collections::BTreeSet::new();
+    let mut unmatched = std::collections::BTreeSet::new();
+
+    for entry in entries {
+        if entry.end_ms <= entry.start_ms {
+            summary.errors.push(RowError {
+                row: entry.row,
+                reason: "End is not after start".to_string(),
+            });
+            continue;
+        }
+        if entry.duration == 0 {
+            summary.errors.push(RowError {
+                row: entry.row,
+                reason: "Session is shorter than a minute".to_string(),
+            });
+            continue;
+        }
+        if vault::find_overlap(existing, entry.start_ms, entry.end_ms, None).is_some()
+            || vault::find_overlap(&records, entry.start_ms, entry.end_ms, None).is_some()
+        {
+            summary.duplicates += 1;
+            continue;
+        }
+
+        let project_path =
+            entry
+                .project
+                .as_deref()
+                .and_then(|name| match match_project(name, projects) {
+                    Some(p) => {
+                        matched.insert(name.to_string());
+                        Some(p.readme_path.clone())
+                    }
+                    None => {
+                        unmatched.insert(name.to_string());
+                        None
+                    }
+                });
+        let date = match tz.timestamp_millis_opt(entry.start_ms as i64).single() {
+            Some(t) => t.date_naive().format("%Y-%m-%d").to_string(),
+            None => continue,
+        };
+
+        records.push(PomodoroRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            date,
+            start_time: entry.start_ms,
+            end_time: entry.end_ms,
+            duration: entry.duration,
+            mode: match entry.mode.as_deref() {
+                Some(m @ ("pomodoro" | "stopwatch")) => m.to_string(),
+                _ => format.default_mode().to_string(),
+            },
+            status: "completed".to_string(),
+            project_path,
+            task_text: entry.task,
+            pomodoro_index: None,
+            source: Some(format.source().to_string()),
+        });
+    }
+
+    records.sort_by_key(|r| r.start_time);
+    summary.imported = records.len();
+    summary.total_minutes = records.iter().map(|r| r.duration).sum();
+    summary.first_date = records.first().map(|r| r.date.clone());
+    summary.last_date = records.last().map(|r| r.date.clone());
+    summary.matched_projects = matched.into_iter().collect();
+    summary.unmatched_projects = unmatched.into_iter().collect();
+    records
+}
+
+/// Import sessions from another tool into this device's records file.
+/// Daily notes are not touched: imported history predates them.
+pub fn run_import(
+    vault_path: &str,
+    device_hash: &str,
+    options: &ImportOptions,
+) -> Result<ImportSummary, String> {
+    let content = std::fs::read_to_string(&options.path)
+        .map_err(|e| format!("Failed to read import file: {}", e))?;
+
+    let (entries, errors) = match options.format {
+        ImportFormat::Toggl => entries_from_csv(&content, &ColumnMapping::toggl(), &Local)?,
+        ImportFormat::Csv => {
+            let mapping = options
+                .mapping
+                .as_ref()
+                .ok_or("CSV import needs a column mapping")?;
+            entries_from_csv(&content, mapping, &Local)?
+        }
+        ImportFormat::Json => entries_from_json(&content, &Local, options.duration_unit)?,
+    };
+
+    let mut summary = ImportSummary {
+        total_rows: entries.len() + errors.len(),
+        errors,
+        ..Default::default()
+    };
+
+    let projects = vault::scan_projects(vault_path).unwrap_or_default();
+    let existing: Vec<PomodoroRecord> = vault::read_all_records(vault_path, device_hash)?
+        .into_iter()
+        .map(|r| r.record)
+        .collect();
+    let records = entries_to_records(
+        entries,
+        options.format,
+        &projects,
+        &existing,
+        &Local,
+        &mut summary,
+    );
+
+    if options.dry_run || records.is_empty() {
+        return Ok(summary);
+    }
+
+    // One write for the whole batch
+    let file_path = vault::get_records_file_path(vault_path, device_hash);
+    let mut file = vault::read_records(&file_path)?;
+    file.records.extend(records);
+    vault::write_records(&file_path, &file)?;
+    summary.written = true;
+
+    log::info!(
+        "Imported {} sessions from {} ({} duplicates, {} errors)",
+        summary.imported,
+        options.path,
+        summary.duplicates,
+        summary.errors.len()
+    );
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn project(display_name: &str) -> Project {
+        Project {
+            name: display_name.to_string(),
+            display_name: display_name.to_string(),
+            path: format!("1. 项目/{}", display_name),
+            readme_path: format!("1. 项目/{0}/{0}.README.md", display_name),
+            budget: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(
+            parse_timestamp("2026-01-05T09:00:00+08:00", &Utc),
+            Some(1_767_574_800_000)
+        );
+        assert_eq!(
+            parse_timestamp("2026-01-05 01:00", &Utc),
+            Some(1_767_574_800_000)
+        );
+        assert_eq!(parse_timestamp("1767574800", &Utc), Some(1_767_574_800_000));
+        assert_eq!(
+            parse_timestamp("1767574800000", &Utc),
+            Some(1_767_574_800_000)
+        );
+        assert_eq!(parse_timestamp("yesterday", &Utc), None);
+        assert_eq!(parse_timestamp("0930", &Utc), None);
+        assert_eq!(parse_timestamp("1500", &Utc), None);
+    }
+
+    #[test]
+    fn test_parse_duration_minutes() {
+        assert_eq!(parse_duration_minutes("01:30:00"), Some(90));
+        assert_eq!(parse_duration_minutes("0:25"), Some(25));
+        assert_eq!(parse_duration_minutes("45"), Some(45));
+        assert_eq!(parse_duration_minutes("a:b"), None);
+    }
+
+    #[test]
+    fn test_match_project() {
+        let projects = vec![project("科研-DualBasic"), project("写作-Blog")];
+        assert_eq!(
+            match_project("科研-DualBasic", &projects)
+                .unwrap()
+                .display_name,
+            "科研-DualBasic"
+        );
+        assert_eq!(
+            match_project("dualbasic", &projects).unwrap().display_name,
+            "科研-DualBasic"
+        );
+        assert_eq!(
+            match_project("blo", &projects).unwrap().display_name,
+            "写作-Blog"
+        );
+        assert!(match_project("Gardening", &projects).is_none());
+    }
+
+    #[test]
+    fn test_entries_to_records() {
+        let entry = |row, start_ms: u64, project: &str| ImportedEntry {
+            row,
+            start_ms,
+            end_ms: start_ms + 25 * 60_000,
+            duration: 25,
+            mode: None,
+            project: Some(project.to_string()),
+            task: None,
+        };
+        let base = 1_767_574_800_000;
+        let existing = vec![PomodoroRecord {
+            id: "old".to_string(),
+            date: "2026-01-05".to_string(),
+            start_time: base,
+            end_time: base + 25 * 60_000,
+            duration: 25,
+            mode: "pomodoro".to_string(),
+            status: "completed".to_string(),
+            project_path: None,
+            task_text: None,
+            pomodoro_index: None,
+            source: None,
+        }];
+        let entries = vec![
+            entry(1, base + 10 * 60_000, "Blog"), // overlaps existing
+            entry(2, base + 60 * 60_000, "Blog"),
+            entry(3, base + 70 * 60_000, "Blog"), // overlaps row 2
+            entry(4, base + 120 * 60_000, "Gardening"),
+            ImportedEntry {
+                end_ms: base + 200 * 60_000 + 30_000,
+                duration: 0,
+                ..entry(5, base + 200 * 60_000, "Blog")
+            },
+        ];
+
+        let mut summary = ImportSummary::default();
+        let records = entries_to_records(
+            entries,
+            ImportFormat::Toggl,
+            &[project("写作-Blog")],
+            &existing,
+            &Utc,
+            &mut summary,
+        );
+        assert_eq!(records.len(), 2);
+        assert_eq!(summary.duplicates, 2);
+        assert_eq!(summary.errors.len(), 1);
+        assert_eq!(summary.total_minutes, 50);
+        assert_eq!(records[0].mode, "stopwatch");
+        assert_eq!(records[0].source.as_deref(), Some("toggl"));
+        assert_eq!(
+            records[0].project_path.as_deref(),
+            Some("1. 项目/写作-Blog/写作-Blog.README.md")
+        );
+        assert_eq!(records[1].project_path, None);
+        assert_eq!(summary.matched_projects, vec!["Blog"]);
+        assert_eq!(summary.unmatched_projects, vec!["Gardening"]);
+    }
+}
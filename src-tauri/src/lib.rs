@@ -1,21 +1,47 @@
 mod app_config;
 mod app_state;
 mod autostart;
+mod background;
+pub mod cli;
 mod commands;
+mod control;
+mod export;
+mod hooks;
+mod import;
 mod platform;
+mod stats;
 mod timer;
+mod tray;
 mod vault;
 
 use app_state::AppState;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::menu::{MenuBuilder, MenuItemBuilder};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{Emitter, Manager};
 #[cfg(not(target_os = "windows"))]
 use tauri::WindowEvent;
 
+/// Bring the popover up, e.g. when the app is launched again
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if window.is_minimized().unwrap_or(false) {
+            let _ = window.unminimize();
+        }
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn open_deep_links(control: &control::AppControl, urls: Vec<tauri::Url>) {
+    for url in urls {
+        if let Err(e) = control::open_deep_link(control, url.as_str()) {
+            log::warn!("Failed to open {}: {}", url, e);
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let device_hash = platform::get_device_hash();
@@ -24,6 +50,14 @@ pub fn run() {
     let saved_config = app_config::load_config();
 
     tauri::Builder::default()
+        // Registered first so a second launch hands over and exits early
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            // URLs in `argv` go to the deep-link handler set up below
+            if !argv.iter().skip(1).any(|arg| control::is_deep_link(arg)) {
+                show_main_window(app);
+            }
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_positioner::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_notification::init())
@@ -38,10 +72,17 @@ pub fn run() {
             }
 
             // Initialize app state with saved vault path
-            let app_state = AppState::new(device_hash.clone());
+            let app_state = AppState::new(device_hash.clone(), saved_config.clone());
             if let Some(ref vault_path) = saved_config.vault_path {
                 *app_state.vault_path.lock().unwrap() = Some(vault_path.clone());
 
+                // Keep this device's name next to its hash for cross-device stats
+                let _ = vault::register_device(
+                    vault_path,
+                    &device_hash,
+                    &app_config::device_name(&saved_config),
+                );
+
                 // Try to load config from vault
                 if let Ok(cfg) = vault::read_lifeos_config(vault_path) {
                     *app_state.config.lock().unwrap() = cfg.clone();
@@ -53,23 +94,84 @@ pub fn run() {
                 }
             }
 
+            app_state
+                .status_file
+                .store(saved_config.status_file, Ordering::Relaxed);
+            // Left behind if the app was killed; the timer always starts idle
+            if let Some(ref vault_path) = saved_config.vault_path {
+                let _ = vault::clear_live_session(vault_path, &device_hash);
+            }
+            app_state.background.start_worker(app.handle().clone());
+            let webhooks = app_state.webhooks.clone();
             app.manage(app_state);
+            // Started once the state is managed, since it reads the hooks from there
+            let handle = app.handle().clone();
+            hooks::WebhookQueue::start_worker(webhooks, move || {
+                handle.state::<AppState>().app_config().webhooks
+            });
+            commands::refresh_goal_progress(app.handle(), &app.state::<AppState>());
+            commands::reconcile_autostart(app.handle());
+
+            // Socket for bar-tomato-cli
+            let app_control = Arc::new(control::AppControl::new(app.handle().clone()));
+            match control::IpcServer::start(app_control) {
+                Ok(server) => {
+                    *app.state::<AppState>().ipc_server.lock().unwrap() = Some(server);
+                }
+                Err(e) => log::warn!("Failed to start command-line socket: {}", e),
+            }
+
+            // bartomato:// URLs, opened by the OS or forwarded by a second launch
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                // macOS picks the scheme up from Info.plist instead
+                #[cfg(any(target_os = "linux", target_os = "windows"))]
+                if let Err(e) = app.deep_link().register(control::URL_SCHEME) {
+                    log::warn!("Failed to register {} URLs: {}", control::URL_SCHEME, e);
+                }
 
-            // Create tray menu
-            let quit_item = MenuItemBuilder::with_id("quit", "退出 Bar Tomato").build(app)?;
-            let tray_menu = MenuBuilder::new(app).item(&quit_item).build()?;
+                let link_control = control::AppControl::new(app.handle().clone());
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    open_deep_links(&link_control, urls);
+                }
+                app.deep_link().on_open_url(move |event| {
+                    open_deep_links(&link_control, event.urls());
+                });
+            }
+
+            commands::restart_command_inbox(app.handle(), &app.state::<AppState>());
+
+            if saved_config.http_api.enabled {
+                if let Err(e) = commands::restart_http_api(app.handle(), &app.state::<AppState>()) {
+                    log::warn!("Failed to start HTTP API: {}", e);
+                }
+            }
+
+            // Create tray menu; the background worker rebuilds it as the phase changes
+            let tray_menu = tray::tray_menu(
+                app.handle(),
+                &commands::get_timer_status(app.state::<AppState>()),
+            )?;
 
             // Create tray icon
-            let _tray = TrayIconBuilder::with_id("main")
+            let _tray = TrayIconBuilder::with_id(tray::TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
                 .icon_as_template(true)
                 .tooltip("Bar Tomato")
                 .menu(&tray_menu)
                 .show_menu_on_left_click(false)
-                .on_menu_event(|app, event| {
-                    if event.id().as_ref() == "quit" {
+                .on_menu_event(|app, event| match tray::TrayAction::from_id(event.id().as_ref()) {
+                    Some(tray::TrayAction::Quit) => {
+                        let state = app.state::<AppState>();
+                        if let Some(ref vault_path) = *state.vault_path.lock().unwrap() {
+                            let _ = vault::clear_live_session(vault_path, &state.device_hash);
+                        }
                         app.exit(0);
                     }
+                    Some(tray::TrayAction::ShowWindow) => show_main_window(app),
+                    Some(action) => tray::run_tray_action(app, action),
+                    None => {}
                 })
                 .on_tray_icon_event(|tray, event| {
                     // Let positioner track tray position
@@ -215,6 +317,20 @@ pub fn run() {
                     std::thread::sleep(Duration::from_secs(1));
 
                     let state = app_handle.state::<AppState>();
+
+                    // Roll goal progress over to the new day
+                    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+                    let goal_stale = state
+                        .timer
+                        .lock()
+                        .unwrap()
+                        .goal_progress
+                        .as_ref()
+                        .is_some_and(|g| g.date != today);
+                    if goal_stale {
+                        state.background.send(background::Job::GoalRollover);
+                    }
+
                     let mut timer = state.timer.lock().unwrap();
 
                     // Check if timer completed (only notify once)
@@ -242,10 +358,35 @@ pub fn run() {
                     let mode = timer.mode;
                     let elapsed_secs = timer.elapsed().as_secs();
                     let duration_secs = timer.duration_secs;
+                    let status = timer.status();
 
                     drop(timer); // Release lock before updating tray/window
 
-                    if let Some(tray) = app_handle.tray_by_id("main") {
+                    state.events.publish(&status);
+
+                    let now_ms = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_millis() as u64;
+                    // Transitions are reported by the timer commands; the
+                    // worker keeps the live session's heartbeat and the tray
+                    // menu going without the vault holding up the tick
+                    state.background.send(background::Job::Tick {
+                        status: status.clone(),
+                        now_ms,
+                    });
+                    tray::refresh_tray_icon(&app_handle, &status);
+
+                    if state.status_file.load(Ordering::Relaxed) {
+                        let snapshot = control::StatusSnapshot::new(status, &title, now_ms);
+                        if let Err(e) =
+                            control::write_status_file(&control::status_file_path(), &snapshot)
+                        {
+                            log::warn!("{}", e);
+                        }
+                    }
+
+                    if let Some(tray) = app_handle.tray_by_id(tray::TRAY_ID) {
                         let _ = tray.set_title(Some(&title));
 
                         // Update tooltip (visible on hover in Windows)
@@ -348,6 +489,8 @@ pub fn run() {
             commands::resume_timer,
             commands::stop_timer,
             commands::complete_pomodoro,
+            commands::add_manual_entry,
+            commands::update_record,
             commands::skip_break,
             commands::complete_break,
             commands::set_vault_path,
@@ -357,6 +500,39 @@ pub fn run() {
             commands::scan_tasks,
             commands::get_tray_title,
             commands::get_today_stats,
+            commands::set_device_name,
+            commands::get_stats,
+            commands::get_streaks,
+            commands::get_heatmap,
+            commands::get_focus_distribution,
+            commands::get_session_length_trend,
+            commands::get_daily_goal,
+            commands::set_daily_goal,
+            commands::get_budget_summary,
+            commands::set_project_budget,
+            commands::export_records,
+            commands::export_calendar,
+            commands::set_calendar_sync,
+            commands::import_sessions,
+            commands::generate_review,
+            commands::get_review_template,
+            commands::set_review_template,
+            commands::set_note_frontmatter,
+            commands::set_time_format,
+            commands::migrate_time_format,
+            commands::get_http_api,
+            commands::set_http_api,
+            commands::get_status_file,
+            commands::set_status_file,
+            commands::get_command_inbox,
+            commands::set_command_inbox,
+            commands::get_live_sessions,
+            commands::get_conflicts,
+            commands::resolve_conflict,
+            commands::get_shell_hooks,
+            commands::set_shell_hooks,
+            commands::get_webhooks,
+            commands::set_webhooks,
             commands::set_autostart,
             commands::get_autostart,
             commands::set_dialog_open,
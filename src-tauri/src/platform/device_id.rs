@@ -28,6 +28,13 @@ pub fn get_device_hash() -> String {
     }
 }
 
+/// Human-readable name for this device, used to label its records
+pub fn get_device_name() -> String {
+    hostname::get()
+        .map(|h| h.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
 // Helper for hex encoding
 mod hex {
     pub fn encode(bytes: impl AsRef<[u8]>) -> String {
@@ -27,3 +27,37 @@ pub fn send_stopwatch_stopped<R: Runtime>(app: &AppHandle<R>, duration_mins: u32
         .show()
         .map_err(|e| format!("Failed to send notification: {}", e))
 }
+
+pub fn send_goal_reached<R: Runtime>(app: &AppHandle<R>, label: &str) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title("🎯 今日目标达成")
+        .body(&format!("今日专注 {}，继续保持！", label))
+        .show()
+        .map_err(|e| format!("Failed to send notification: {}", e))
+}
+
+pub fn send_budget_exceeded<R: Runtime>(
+    app: &AppHandle<R>,
+    project_name: &str,
+    over_minutes: u32,
+) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title("⚠️ 项目超出周预算")
+        .body(&format!(
+            "{} 本周已超出预算 {} 分钟",
+            project_name, over_minutes
+        ))
+        .show()
+        .map_err(|e| format!("Failed to send notification: {}", e))
+}
+
+pub fn send_timer_conflict<R: Runtime>(app: &AppHandle<R>, device_name: &str) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title("⚠️ 检测到重复计时")
+        .body(&format!("{} 也在计时，重叠的时间只会计算一次", device_name))
+        .show()
+        .map_err(|e| format!("Failed to send notification: {}", e))
+}
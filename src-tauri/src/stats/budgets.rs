@@ -0,0 +1,147 @@
+use super::{records_in_range, week_start_of};
+use crate::vault::{project_folder, BudgetStatus, PomodoroRecord};
+use chrono::{Duration, NaiveDate, Weekday};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetSummary {
+    pub week_start: String,
+    pub week_end: String,
+    /// Projects that used more than their budget, largest overrun first
+    pub over: Vec<BudgetStatus>,
+    /// Projects with budget left, most remaining first
+    pub under: Vec<BudgetStatus>,
+}
+
+/// Parse a budget such as `6h`, `90m`, `1.5h` or `6h30m`; a bare number is hours
+pub fn parse_budget(value: &str) -> Option<u32> {
+    let v = value.trim().to_lowercase().replace(' ', "");
+    if v.is_empty() {
+        return None;
+    }
+    if let Ok(hours) = v.parse::<f64>() {
+        return Some((hours * 60.0).round() as u32);
+    }
+
+    let (hours, rest) = match v.split_once('h') {
+        Some((h, rest)) => (h.parse::<f64>().ok()?, rest),
+        None => (0.0, v.as_str()),
+    };
+    let minutes = match rest.trim_end_matches("min").trim_end_matches('m') {
+        "" => 0.0,
+        m => m.parse::<f64>().ok()?,
+    };
+    Some((hours * 60.0 + minutes).round() as u32)
+}
+
+/// Inclusive first and last day of the week containing `today`
+pub fn current_week(today: NaiveDate, week_start: Weekday) -> (NaiveDate, NaiveDate) {
+    let start = week_start_of(today, week_start);
+    (start, start + Duration::days(6))
+}
+
+/// Budget usage for one project over the given week
+pub fn budget_status(
+    records: &[PomodoroRecord],
+    project_path: &str,
+    budget_minutes: u32,
+    week: (NaiveDate, NaiveDate),
+) -> BudgetStatus {
+    let used_minutes: u32 = records_in_range(records, week.0, week.1)
+        .into_iter()
+        .filter(|r| r.project_path.as_deref() == Some(project_path))
+        .map(|r| r.duration)
+        .sum();
+
+    BudgetStatus {
+        project_path: project_path.to_string(),
+        project_name: project_folder(project_path).to_string(),
+        budget_minutes,
+        used_minutes,
+        remaining_minutes: budget_minutes as i64 - used_minutes as i64,
+        percent: if budget_minutes > 0 {
+            used_minutes as f64 / budget_minutes as f64 * 100.0
+        } else {
+            0.0
+        },
+        over: used_minutes > budget_minutes,
+    }
+}
+
+/// Whether the last `added_minutes` pushed the project over its budget
+pub fn crossed_budget(status: &BudgetStatus, added_minutes: u32) -> bool {
+    status.over && status.used_minutes.saturating_sub(added_minutes) <= status.budget_minutes
+}
+
+/// Split budgeted projects into over- and under-served lists
+pub fn budget_summary(statuses: Vec<BudgetStatus>, week: (NaiveDate, NaiveDate)) -> BudgetSummary {
+    let (mut over, mut under): (Vec<_>, Vec<_>) = statuses.into_iter().partition(|s| s.over);
+    over.sort_by_key(|s| s.remaining_minutes);
+    under.sort_by_key(|s| -s.remaining_minutes);
+
+    BudgetSummary {
+        week_start: week.0.format("%Y-%m-%d").to_string(),
+        week_end: week.1.format("%Y-%m-%d").to_string(),
+        over,
+        under,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(date: &str, duration: u32, project: &str) -> PomodoroRecord {
+        PomodoroRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            date: date.to_string(),
+            start_time: 0,
+            end_time: 0,
+            duration,
+            mode: "pomodoro".to_string(),
+            status: "completed".to_string(),
+            project_path: Some(project.to_string()),
+            task_text: None,
+            pomodoro_index: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        assert_eq!(parse_budget("6h"), Some(360));
+        assert_eq!(parse_budget("1.5h"), Some(90));
+        assert_eq!(parse_budget("6h30m"), Some(390));
+        assert_eq!(parse_budget("45m"), Some(45));
+        assert_eq!(parse_budget("4"), Some(240));
+        assert_eq!(parse_budget("soon"), None);
+    }
+
+    #[test]
+    fn test_budget_status_and_summary() {
+        let a = "1. 项目/科研-A/A.README.md";
+        let b = "1. 项目/写作-B/B.README.md";
+        let records = vec![
+            record("2026-01-05", 50, a),
+            record("2026-01-06", 25, a),
+            record("2026-01-04", 100, a), // previous week
+            record("2026-01-07", 25, b),
+        ];
+        let week = current_week(NaiveDate::from_ymd_opt(2026, 1, 7).unwrap(), Weekday::Mon);
+        assert_eq!(week.0, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+
+        let sa = budget_status(&records, a, 60, week);
+        assert_eq!(
+            (sa.used_minutes, sa.remaining_minutes, sa.over),
+            (75, -15, true)
+        );
+        assert!(crossed_budget(&sa, 25));
+        assert!(!crossed_budget(&sa, 10));
+
+        let sb = budget_status(&records, b, 120, week);
+        let summary = budget_summary(vec![sa, sb], week);
+        assert_eq!(summary.over[0].project_name, "科研-A");
+        assert_eq!(summary.under[0].remaining_minutes, 95);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::vault::PomodoroRecord;
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GoalUnit {
+    Pomodoros,
+    Minutes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyGoal {
+    pub unit: GoalUnit,
+    pub target: u32,
+    /// Per-weekday overrides of `target`, e.g. a lighter goal on weekends
+    #[serde(default)]
+    pub weekday_targets: HashMap<Weekday, u32>,
+    /// Append goal progress to the tray title
+    #[serde(default)]
+    pub show_in_tray: bool,
+    /// Let a `focus_goal` property in the daily note override the target
+    #[serde(default)]
+    pub use_daily_note: bool,
+}
+
+impl DailyGoal {
+    pub fn target_for(&self, date: NaiveDate) -> u32 {
+        self.weekday_targets
+            .get(&date.weekday())
+            .copied()
+            .unwrap_or(self.target)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalProgress {
+    pub date: String,
+    pub unit: GoalUnit,
+    pub target: u32,
+    pub done: u32,
+    pub percent: f64,
+    pub reached: bool,
+}
+
+impl GoalProgress {
+    /// Compact form for the tray title, e.g. `3/8` or `80/180m`
+    pub fn short_label(&self) -> String {
+        match self.unit {
+            GoalUnit::Pomodoros => format!("{}/{}", self.done, self.target),
+            GoalUnit::Minutes => format!("{}/{}m", self.done, self.target),
+        }
+    }
+}
+
+/// Parse a `focus_goal` frontmatter value: a bare number is a pomodoro count,
+/// `90m`, `2h` or `1.5h` are minutes
+pub fn parse_goal_value(value: &str) -> Option<(GoalUnit, u32)> {
+    let v = value.trim().to_lowercase();
+    if let Some(h) = v.strip_suffix('h') {
+        let hours: f64 = h.trim().parse().ok()?;
+        return Some((GoalUnit::Minutes, (hours * 60.0).round() as u32));
+    }
+    if let Some(m) = v.strip_suffix("min").or_else(|| v.strip_suffix('m')) {
+        return Some((GoalUnit::Minutes, m.trim().parse().ok()?));
+    }
+    Some((GoalUnit::Pomodoros, v.parse().ok()?))
+}
+
+/// Progress toward the goal for `date`. `note_override` is the daily note's
+/// own goal, if any. Returns `None` when there is no target for the day.
+pub fn goal_progress(
+    goal: &DailyGoal,
+    note_override: Option<(GoalUnit, u32)>,
+    records: &[PomodoroRecord],
+    date: NaiveDate,
+) -> Option<GoalProgress> {
+    let (unit, target) = note_override.unwrap_or((goal.unit, goal.target_for(date)));
+    if target == 0 {
+        return None;
+    }
+
+    let date_str = date.format("%Y-%m-%d").to_string();
+    let today = records.iter().filter(|r| r.date == date_str);
+    let done = match unit {
+        GoalUnit::Pomodoros => today.filter(|r| r.mode == "pomodoro").count() as u32,
+        GoalUnit::Minutes => today.map(|r| r.duration).sum(),
+    };
+
+    Some(GoalProgress {
+        date: date_str,
+        unit,
+        target,
+        done,
+        percent: (done as f64 / target as f64 * 100.0).min(100.0),
+        reached: done >= target,
+    })
+}
+
+/// Whether moving from `previous` to `current` just reached the goal. Only
+/// fires within the same day, so restarts and day changes stay quiet, and
+/// not again on `notified_on`, when an edit took progress back below the goal.
+pub fn goal_just_reached(
+    previous: Option<&GoalProgress>,
+    current: Option<&GoalProgress>,
+    notified_on: Option<&str>,
+) -> bool {
+    match (previous, current) {
+        (Some(p), Some(c)) => {
+            p.date == c.date && !p.reached && c.reached && notified_on != Some(c.date.as_str())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(mode: &str, duration: u32) -> PomodoroRecord {
+        PomodoroRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            date: "2026-01-10".to_string(),
+            start_time: 0,
+            end_time: 0,
+            duration,
+            mode: mode.to_string(),
+            status: "completed".to_string(),
+            project_path: None,
+            task_text: None,
+            pomodoro_index: None,
+            source: None,
+        }
+    }
+
+    fn goal() -> DailyGoal {
+        DailyGoal {
+            unit: GoalUnit::Pomodoros,
+            target: 2,
+            weekday_targets: HashMap::from([(Weekday::Sat, 1)]),
+            show_in_tray: false,
+            use_daily_note: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_goal_value() {
+        assert_eq!(parse_goal_value("8"), Some((GoalUnit::Pomodoros, 8)));
+        assert_eq!(parse_goal_value("90m"), Some((GoalUnit::Minutes, 90)));
+        assert_eq!(parse_goal_value("1.5h"), Some((GoalUnit::Minutes, 90)));
+        assert_eq!(parse_goal_value("lots"), None);
+    }
+
+    #[test]
+    fn test_goal_progress_uses_weekday_target() {
+        let records = vec![record("pomodoro", 25), record("stopwatch", 40)];
+        // 2026-01-10 is a Saturday
+        let saturday = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let p = goal_progress(&goal(), None, &records, saturday).unwrap();
+        assert_eq!((p.done, p.target, p.reached), (1, 1, true));
+
+        let p = goal_progress(&goal(), Some((GoalUnit::Minutes, 120)), &records, saturday).unwrap();
+        assert_eq!((p.done, p.reached), (65, false));
+        assert_eq!(p.short_label(), "65/120m");
+    }
+
+    #[test]
+    fn test_goal_just_reached() {
+        let saturday = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let before = goal_progress(&goal(), None, &[], saturday);
+        let after = goal_progress(&goal(), None, &[record("pomodoro", 25)], saturday);
+        assert!(goal_just_reached(before.as_ref(), after.as_ref(), None));
+        assert!(!goal_just_reached(after.as_ref(), after.as_ref(), None));
+        assert!(!goal_just_reached(None, after.as_ref(), None));
+        // Dropped below the goal by an edit and reached again: already told
+        assert!(!goal_just_reached(
+            before.as_ref(),
+            after.as_ref(),
+            Some("2026-01-10")
+        ));
+        assert!(goal_just_reached(
+            before.as_ref(),
+            after.as_ref(),
+            Some("2026-01-09")
+        ));
+    }
+}
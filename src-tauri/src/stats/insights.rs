@@ -0,0 +1,320 @@
+use super::{query_stats, GroupBy, StatsQuery};
+use crate::vault::PomodoroRecord;
+use chrono::{Datelike, Duration, NaiveDate, TimeZone, Timelike, Weekday};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreakStats {
+    pub threshold_minutes: u32,
+    pub current: u32,
+    pub longest: u32,
+    pub longest_start: Option<String>,
+    pub longest_end: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapDay {
+    pub date: String,
+    pub minutes: u32,
+    pub count: u32,
+    /// Intensity bucket from 0 (no focus) to 4 (busiest days)
+    pub level: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusDistribution {
+    /// Focus minutes per hour of day, index 0 = 00:00–01:00
+    pub by_hour: Vec<f64>,
+    /// Focus minutes per weekday, index 0 = Monday
+    pub by_weekday: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendPoint {
+    pub key: String,
+    pub label: String,
+    pub average_minutes: f64,
+    pub session_count: u32,
+}
+
+/// Total minutes and session count per day
+pub fn daily_totals(records: &[PomodoroRecord]) -> BTreeMap<NaiveDate, (u32, u32)> {
+    let mut days = BTreeMap::new();
+    for r in records {
+        if let Ok(date) = NaiveDate::parse_from_str(&r.date, "%Y-%m-%d") {
+            let entry = days.entry(date).or_insert((0, 0));
+            entry.0 += r.duration;
+            entry.1 += 1;
+        }
+    }
+    days
+}
+
+/// Current and longest run of consecutive days with at least
+/// `threshold_minutes` of focus. The current streak still counts if today
+/// has not reached the threshold yet but yesterday did.
+pub fn streaks(
+    records: &[PomodoroRecord],
+    today: NaiveDate,
+    threshold_minutes: u32,
+) -> StreakStats {
+    let threshold = threshold_minutes.max(1);
+    let active: Vec<NaiveDate> = daily_totals(records)
+        .into_iter()
+        .filter(|(_, (minutes, _))| *minutes >= threshold)
+        .map(|(date, _)| date)
+        .collect();
+
+    let mut longest = 0;
+    let mut longest_range: Option<(NaiveDate, NaiveDate)> = None;
+    let mut run = 0;
+    let mut run_start = None;
+    let mut prev: Option<NaiveDate> = None;
+    for &date in &active {
+        if prev.is_some_and(|p| p + Duration::days(1) == date) {
+            run += 1;
+        } else {
+            run = 1;
+            run_start = Some(date);
+        }
+        if run > longest {
+            longest = run;
+            longest_range = run_start.map(|s| (s, date));
+        }
+        prev = Some(date);
+    }
+
+    let mut current = 0;
+    let mut day = if active.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    while active.binary_search(&day).is_ok() {
+        current += 1;
+        day -= Duration::days(1);
+    }
+
+    StreakStats {
+        threshold_minutes: threshold,
+        current,
+        longest,
+        longest_start: longest_range.map(|(s, _)| s.format("%Y-%m-%d").to_string()),
+        longest_end: longest_range.map(|(_, e)| e.format("%Y-%m-%d").to_string()),
+    }
+}
+
+/// One entry per day for the `days` days ending at `end`, oldest first
+pub fn heatmap(records: &[PomodoroRecord], end: NaiveDate, days: u32) -> Vec<HeatmapDay> {
+    let totals = daily_totals(records);
+    let start = end - Duration::days(days.saturating_sub(1) as i64);
+
+    let series: Vec<(NaiveDate, u32, u32)> = start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .map(|d| {
+            let (minutes, count) = totals.get(&d).copied().unwrap_or((0, 0));
+            (d, minutes, count)
+        })
+        .collect();
+
+    let max = series.iter().map(|(_, m, _)| *m).max().unwrap_or(0);
+    series
+        .into_iter()
+        .map(|(date, minutes, count)| HeatmapDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            minutes,
+            count,
+            level: if minutes == 0 || max == 0 {
+                0
+            } else {
+                ((minutes as f64 / max as f64) * 4.0).ceil().clamp(1.0, 4.0) as u8
+            },
+        })
+        .collect()
+}
+
+/// Spread each record's focus minutes over the hours its span covers (in
+/// `tz`) and over its weekday. Records without timestamps only count by day.
+pub fn focus_distribution<Tz: TimeZone>(records: &[PomodoroRecord], tz: &Tz) -> FocusDistribution {
+    let mut by_hour = vec![0.0; 24];
+    let mut by_weekday = vec![0.0; 7];
+
+    for r in records {
+        if let Ok(date) = NaiveDate::parse_from_str(&r.date, "%Y-%m-%d") {
+            by_weekday[date.weekday().num_days_from_monday() as usize] += r.duration as f64;
+        }
+
+        if r.start_time == 0 || r.end_time <= r.start_time {
+            continue;
+        }
+
+        // The span may include pauses, so scale each hour's share to the
+        // recorded focus duration
+        let span_ms = (r.end_time - r.start_time) as f64;
+        let mut cursor = r.start_time;
+        while cursor < r.end_time {
+            let local = match tz.timestamp_millis_opt(cursor as i64).single() {
+                Some(t) => t,
+                None => break,
+            };
+            let into_hour_ms = (local.minute() as u64 * 60 + local.second() as u64) * 1000
+                + local.timestamp_subsec_millis() as u64;
+            let hour_end = cursor - into_hour_ms + 3_600_000;
+            let chunk_end = hour_end.min(r.end_time);
+            by_hour[local.hour() as usize] +=
+                r.duration as f64 * (chunk_end - cursor) as f64 / span_ms;
+            cursor = chunk_end;
+        }
+    }
+
+    FocusDistribution {
+        by_hour,
+        by_weekday,
+    }
+}
+
+/// Average session length per day, week or month across the range
+pub fn session_length_trend(
+    records: &[PomodoroRecord],
+    from: &str,
+    to: &str,
+    group_by: GroupBy,
+    week_start: Option<Weekday>,
+) -> Result<Vec<TrendPoint>, String> {
+    if !matches!(group_by, GroupBy::Day | GroupBy::Week | GroupBy::Month) {
+        return Err("Trends can only be grouped by day, week or month".to_string());
+    }
+
+    let report = query_stats(
+        records,
+        &StatsQuery {
+            from: from.to_string(),
+            to: to.to_string(),
+            group_by,
+            statuses: vec![],
+            week_start,
+        },
+    )?;
+
+    Ok(report
+        .groups
+        .into_iter()
+        .map(|g| TrendPoint {
+            key: g.key,
+            label: g.label,
+            average_minutes: g.totals.average_minutes,
+            session_count: g.totals.session_count,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn record(date: &str, duration: u32, start: &str) -> PomodoroRecord {
+        let start_ms = if start.is_empty() {
+            0
+        } else {
+            chrono::NaiveDateTime::parse_from_str(&format!("{} {}", date, start), "%Y-%m-%d %H:%M")
+                .unwrap()
+                .and_utc()
+                .timestamp_millis() as u64
+        };
+        PomodoroRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            date: date.to_string(),
+            start_time: start_ms,
+            end_time: if start_ms == 0 {
+                0
+            } else {
+                start_ms + duration as u64 * 60_000
+            },
+            duration,
+            mode: "pomodoro".to_string(),
+            status: "completed".to_string(),
+            project_path: None,
+            task_text: None,
+            pomodoro_index: None,
+            source: None,
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn fixtures() -> Vec<PomodoroRecord> {
+        vec![
+            record("2026-01-01", 25, ""),
+            record("2026-01-02", 25, ""),
+            record("2026-01-03", 25, ""),
+            record("2026-01-05", 10, ""),
+            record("2026-01-06", 50, ""),
+            record("2026-01-07", 25, ""),
+        ]
+    }
+
+    #[test]
+    fn test_streaks() {
+        let s = streaks(&fixtures(), date("2026-01-07"), 1);
+        assert_eq!((s.current, s.longest), (3, 3));
+        assert_eq!(s.longest_start.as_deref(), Some("2026-01-01"));
+
+        // Today not reached yet: yesterday's run still counts
+        let s = streaks(&fixtures(), date("2026-01-08"), 1);
+        assert_eq!(s.current, 3);
+
+        // Threshold drops 01-05 and breaks the recent run
+        let s = streaks(&fixtures(), date("2026-01-07"), 20);
+        assert_eq!((s.current, s.longest), (2, 3));
+
+        let s = streaks(&fixtures(), date("2026-01-10"), 1);
+        assert_eq!(s.current, 0);
+    }
+
+    #[test]
+    fn test_heatmap_levels() {
+        let map = heatmap(&fixtures(), date("2026-01-07"), 7);
+        assert_eq!(map.len(), 7);
+        assert_eq!(map[0].date, "2026-01-01");
+        assert_eq!(map[3].level, 0);
+        assert_eq!(map[5].level, 4);
+        assert_eq!(map[4].level, 1);
+    }
+
+    #[test]
+    fn test_focus_distribution_splits_hours() {
+        let records = vec![
+            record("2026-01-05", 60, "09:30"),
+            record("2026-01-06", 25, "14:00"),
+        ];
+        let dist = focus_distribution(&records, &Utc);
+        assert!((dist.by_hour[9] - 30.0).abs() < 1e-9);
+        assert!((dist.by_hour[10] - 30.0).abs() < 1e-9);
+        assert!((dist.by_hour[14] - 25.0).abs() < 1e-9);
+        assert_eq!(dist.by_weekday[0], 60.0);
+        assert_eq!(dist.by_weekday[1], 25.0);
+    }
+
+    #[test]
+    fn test_session_length_trend() {
+        let trend =
+            session_length_trend(&fixtures(), "2026-01-01", "2026-01-07", GroupBy::Week, None)
+                .unwrap();
+        assert_eq!(trend.len(), 2);
+        assert!((trend[0].average_minutes - 25.0).abs() < 1e-9);
+        assert!((trend[1].average_minutes - 85.0 / 3.0).abs() < 1e-9);
+        assert!(
+            session_length_trend(&fixtures(), "2026-01-01", "2026-01-07", GroupBy::Task, None)
+                .is_err()
+        );
+    }
+}
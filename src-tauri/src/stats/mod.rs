@@ -0,0 +1,11 @@
+pub mod budgets;
+pub mod goals;
+pub mod insights;
+pub mod query;
+pub mod review;
+
+pub use budgets::*;
+pub use goals::*;
+pub use insights::*;
+pub use query::*;
+pub use review::*;
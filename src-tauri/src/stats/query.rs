@@ -0,0 +1,371 @@
+use crate::vault::{project_folder, PomodoroRecord};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GroupBy {
+    Day,
+    Week,
+    Month,
+    Project,
+    Area,
+    Task,
+    Mode,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsQuery {
+    /// First day of the range, `YYYY-MM-DD` (inclusive)
+    pub from: String,
+    /// Last day of the range, `YYYY-MM-DD` (inclusive)
+    pub to: String,
+    pub group_by: GroupBy,
+    /// Only include records with these statuses; empty means all
+    #[serde(default)]
+    pub statuses: Vec<String>,
+    /// First day of the week for `GroupBy::Week`, defaults to Monday
+    #[serde(default)]
+    pub week_start: Option<Weekday>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsTotals {
+    pub total_minutes: u32,
+    pub session_count: u32,
+    pub pomodoro_count: u32,
+    pub completed_count: u32,
+    pub average_minutes: f64,
+    pub completion_rate: f64,
+}
+
+impl StatsTotals {
+    fn add(&mut self, record: &PomodoroRecord) {
+        self.total_minutes += record.duration;
+        self.session_count += 1;
+        if record.mode == "pomodoro" {
+            self.pomodoro_count += 1;
+        }
+        if record.status == "completed" {
+            self.completed_count += 1;
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.session_count > 0 {
+            self.average_minutes = self.total_minutes as f64 / self.session_count as f64;
+            self.completion_rate = self.completed_count as f64 / self.session_count as f64;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsGroup {
+    /// Stable group key (date, week start, project path, ...); empty for "none"
+    pub key: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub totals: StatsTotals,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsReport {
+    pub from: String,
+    pub to: String,
+    pub group_by: GroupBy,
+    #[serde(flatten)]
+    pub totals: StatsTotals,
+    pub groups: Vec<StatsGroup>,
+}
+
+/// Parse an inclusive `YYYY-MM-DD` range
+pub fn parse_range(from: &str, to: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let from_date = NaiveDate::parse_from_str(from, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", from))?;
+    let to_date =
+        NaiveDate::parse_from_str(to, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", to))?;
+    if to_date < from_date {
+        return Err("Range end is before range start".to_string());
+    }
+    Ok((from_date, to_date))
+}
+
+/// Records whose date falls inside the inclusive range
+pub fn records_in_range<'a>(
+    records: &'a [PomodoroRecord],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<&'a PomodoroRecord> {
+    records
+        .iter()
+        .filter(|r| {
+            NaiveDate::parse_from_str(&r.date, "%Y-%m-%d")
+                .map(|d| d >= from && d <= to)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// First day of the week containing `date`
+pub fn week_start_of(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+    let offset =
+        (7 + date.weekday().num_days_from_monday() - week_start.num_days_from_monday()) % 7;
+    date - Duration::days(offset as i64)
+}
+
+/// Area part of a `领域-项目` folder name
+pub fn project_area(project_path: &str) -> &str {
+    let folder = project_folder(project_path);
+    folder
+        .split_once('-')
+        .map(|(area, _)| area)
+        .unwrap_or(folder)
+}
+
+fn group_key(record: &PomodoroRecord, group_by: GroupBy, week_start: Weekday) -> (String, String) {
+    let date = NaiveDate::parse_from_str(&record.date, "%Y-%m-%d").ok();
+    match group_by {
+        GroupBy::Day => (record.date.clone(), record.date.clone()),
+        GroupBy::Week => match date {
+            Some(d) => week_key(week_start_of(d, week_start), week_start),
+            None => (String::new(), String::new()),
+        },
+        GroupBy::Month => {
+            let key = record.date.get(..7).unwrap_or(&record.date).to_string();
+            (key.clone(), key)
+        }
+        GroupBy::Project => match record.project_path {
+            Some(ref pp) => (pp.clone(), project_folder(pp).to_string()),
+            None => (String::new(), String::new()),
+        },
+        GroupBy::Area => match record.project_path {
+            Some(ref pp) => {
+                let area = project_area(pp).to_string();
+                (area.clone(), area)
+            }
+            None => (String::new(), String::new()),
+        },
+        GroupBy::Task => match record.task_text {
+            Some(ref t) => (t.clone(), t.clone()),
+            None => (String::new(), String::new()),
+        },
+        GroupBy::Mode => (record.mode.clone(), record.mode.clone()),
+    }
+}
+
+/// Key is the week's first day; label is the ISO week for Monday-start weeks
+fn week_key(start: NaiveDate, week_start: Weekday) -> (String, String) {
+    let key = start.format("%Y-%m-%d").to_string();
+    let label = if week_start == Weekday::Mon {
+        start.format("%G-W%V").to_string()
+    } else {
+        key.clone()
+    };
+    (key, label)
+}
+
+/// Empty buckets for every day/week/month in the range, so charts have no gaps
+fn calendar_buckets(
+    from: NaiveDate,
+    to: NaiveDate,
+    group_by: GroupBy,
+    week_start: Weekday,
+) -> Vec<(String, String)> {
+    let mut buckets = Vec::new();
+    match group_by {
+        GroupBy::Day => {
+            let mut d = from;
+            while d <= to {
+                let key = d.format("%Y-%m-%d").to_string();
+                buckets.push((key.clone(), key));
+                d += Duration::days(1);
+            }
+        }
+        GroupBy::Week => {
+            let mut d = week_start_of(from, week_start);
+            while d <= to {
+                buckets.push(week_key(d, week_start));
+                d += Duration::days(7);
+            }
+        }
+        GroupBy::Month => {
+            let mut d = from.with_day(1).unwrap();
+            while d <= to {
+                let key = d.format("%Y-%m").to_string();
+                buckets.push((key.clone(), key));
+                d = if d.month() == 12 {
+                    NaiveDate::from_ymd_opt(d.year() + 1, 1, 1).unwrap()
+                } else {
+                    NaiveDate::from_ymd_opt(d.year(), d.month() + 1, 1).unwrap()
+                };
+            }
+        }
+        _ => {}
+    }
+    buckets
+}
+
+/// Aggregate records over the query's range, grouped and filtered
+pub fn query_stats(records: &[PomodoroRecord], query: &StatsQuery) -> Result<StatsReport, String> {
+    let (from, to) = parse_range(&query.from, &query.to)?;
+    let week_start = query.week_start.unwrap_or(Weekday::Mon);
+
+    let selected: Vec<&PomodoroRecord> = records_in_range(records, from, to)
+        .into_iter()
+        .filter(|r| query.statuses.is_empty() || query.statuses.contains(&r.status))
+        .collect();
+
+    let mut totals = StatsTotals::default();
+    let mut groups: BTreeMap<String, StatsGroup> = BTreeMap::new();
+
+    for (key, label) in calendar_buckets(from, to, query.group_by, week_start) {
+        groups.insert(
+            key.clone(),
+            StatsGroup {
+                key,
+                label,
+                totals: StatsTotals::default(),
+            },
+        );
+    }
+
+    for record in &selected {
+        totals.add(record);
+        let (key, label) = group_key(record, query.group_by, week_start);
+        groups
+            .entry(key.clone())
+            .or_insert_with(|| StatsGroup {
+                key,
+                label,
+                totals: StatsTotals::default(),
+            })
+            .totals
+            .add(record);
+    }
+
+    totals.finish();
+    let mut groups: Vec<StatsGroup> = groups.into_values().collect();
+    for g in &mut groups {
+        g.totals.finish();
+    }
+
+    // Calendar groupings stay chronological; the rest list the largest first
+    if !matches!(
+        query.group_by,
+        GroupBy::Day | GroupBy::Week | GroupBy::Month
+    ) {
+        groups.sort_by(|a, b| b.totals.total_minutes.cmp(&a.totals.total_minutes));
+    }
+
+    Ok(StatsReport {
+        from: query.from.clone(),
+        to: query.to.clone(),
+        group_by: query.group_by,
+        totals,
+        groups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(date: &str, duration: u32, project: Option<&str>, status: &str) -> PomodoroRecord {
+        PomodoroRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            date: date.to_string(),
+            start_time: 0,
+            end_time: 0,
+            duration,
+            mode: "pomodoro".to_string(),
+            status: status.to_string(),
+            project_path: project.map(String::from),
+            task_text: None,
+            pomodoro_index: None,
+            source: None,
+        }
+    }
+
+    fn fixtures() -> Vec<PomodoroRecord> {
+        vec![
+            record(
+                "2026-01-05",
+                25,
+                Some("1. 项目/科研-A/A.README.md"),
+                "completed",
+            ),
+            record(
+                "2026-01-05",
+                30,
+                Some("1. 项目/科研-B/B.README.md"),
+                "completed",
+            ),
+            record("2026-01-07", 10, None, "interrupted"),
+            record(
+                "2026-01-12",
+                25,
+                Some("1. 项目/写作-C/C.README.md"),
+                "completed",
+            ),
+            record("2025-12-31", 25, None, "completed"),
+        ]
+    }
+
+    fn query(group_by: GroupBy) -> StatsQuery {
+        StatsQuery {
+            from: "2026-01-01".to_string(),
+            to: "2026-01-14".to_string(),
+            group_by,
+            statuses: vec![],
+            week_start: None,
+        }
+    }
+
+    #[test]
+    fn test_totals_and_rates() {
+        let report = query_stats(&fixtures(), &query(GroupBy::Mode)).unwrap();
+        assert_eq!(report.totals.total_minutes, 90);
+        assert_eq!(report.totals.session_count, 4);
+        assert_eq!(report.totals.completed_count, 3);
+        assert!((report.totals.completion_rate - 0.75).abs() < 1e-9);
+        assert!((report.totals.average_minutes - 22.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_group_by_day_fills_gaps() {
+        let report = query_stats(&fixtures(), &query(GroupBy::Day)).unwrap();
+        assert_eq!(report.groups.len(), 14);
+        assert_eq!(report.groups[4].key, "2026-01-05");
+        assert_eq!(report.groups[4].totals.total_minutes, 55);
+        assert_eq!(report.groups[5].totals.session_count, 0);
+    }
+
+    #[test]
+    fn test_group_by_week_with_week_start() {
+        let report = query_stats(&fixtures(), &query(GroupBy::Week)).unwrap();
+        let keys: Vec<_> = report.groups.iter().map(|g| g.label.as_str()).collect();
+        assert_eq!(keys, vec!["2026-W01", "2026-W02", "2026-W03"]);
+        assert_eq!(report.groups[1].totals.total_minutes, 65);
+
+        let mut q = query(GroupBy::Week);
+        q.week_start = Some(Weekday::Sun);
+        let report = query_stats(&fixtures(), &q).unwrap();
+        assert_eq!(report.groups[0].key, "2025-12-28");
+    }
+
+    #[test]
+    fn test_group_by_area_and_status_filter() {
+        let mut q = query(GroupBy::Area);
+        q.statuses = vec!["completed".to_string()];
+        let report = query_stats(&fixtures(), &q).unwrap();
+        assert_eq!(report.groups[0].key, "科研");
+        assert_eq!(report.groups[0].totals.total_minutes, 55);
+        assert_eq!(report.groups[1].key, "写作");
+        assert_eq!(report.totals.session_count, 3);
+    }
+}
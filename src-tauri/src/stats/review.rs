@@ -0,0 +1,497 @@
+use super::{query_stats, week_start_of, GroupBy, StatsGroup, StatsQuery, StatsReport};
+use crate::vault::{self, project_folder, PomodoroRecord, TimeFormat};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Built-in review template. `{{name}}` placeholders are filled by `render_review`.
+pub const DEFAULT_REVIEW_TEMPLATE: &str = "## 🍅 专注回顾 {{title}}
+
+{{from}} ~ {{to}}
+
+- 总专注：{{total}}（环比 {{total_delta}}）
+- 专注次数：{{sessions}}（环比 {{sessions_delta}}），其中番茄 {{pomodoros}} 个
+- 完成率：{{completion_rate}}（环比 {{completion_delta}}）
+- 专注天数：{{active_days}}
+
+### 项目
+
+{{projects}}
+
+### 领域
+
+{{areas}}
+
+### 主要任务
+
+{{tasks}}
+
+### 每日
+
+{{daily}}
+";
+
+/// How many tasks the `{{tasks}}` list shows
+const TOP_TASKS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReviewPeriod {
+    Week,
+    Month,
+}
+
+/// Inclusive first and last day of the week or month containing `date`
+pub fn review_range(
+    period: ReviewPeriod,
+    date: NaiveDate,
+    week_start: Weekday,
+) -> (NaiveDate, NaiveDate) {
+    match period {
+        ReviewPeriod::Week => {
+            let start = week_start_of(date, week_start);
+            (start, start + Duration::days(6))
+        }
+        ReviewPeriod::Month => {
+            let start = date.with_day(1).unwrap();
+            let next = if date.month() == 12 {
+                NaiveDate::from_ymd_opt(date.year() + 1, 1, 1).unwrap()
+            } else {
+                NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1).unwrap()
+            };
+            (start, next - Duration::days(1))
+        }
+    }
+}
+
+/// The period right before `range`, used for the deltas
+pub fn previous_range(
+    period: ReviewPeriod,
+    range: (NaiveDate, NaiveDate),
+    week_start: Weekday,
+) -> (NaiveDate, NaiveDate) {
+    review_range(period, range.0 - Duration::days(1), week_start)
+}
+
+/// Title used in the report and for the periodic note, e.g. `2026-W02` or
+/// `2026-01`. Weeks are named after their middle, so Sunday-start weeks still
+/// get the ISO week most of their days belong to.
+pub fn review_title(period: ReviewPeriod, range: (NaiveDate, NaiveDate)) -> String {
+    let date = range.1 - Duration::days(3);
+    match period {
+        ReviewPeriod::Week => date.format("%G-W%V").to_string(),
+        ReviewPeriod::Month => date.format("%Y-%m").to_string(),
+    }
+}
+
+fn report(
+    records: &[PomodoroRecord],
+    range: (NaiveDate, NaiveDate),
+    group_by: GroupBy,
+    week_start: Weekday,
+) -> Result<StatsReport, String> {
+    let query = StatsQuery {
+        from: range.0.format("%Y-%m-%d").to_string(),
+        to: range.1.format("%Y-%m-%d").to_string(),
+        group_by,
+        statuses: vec![],
+        week_start: Some(week_start),
+    };
+    query_stats(records, &query)
+}
+
+fn signed_time(delta: i64, time: TimeFormat) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{}", sign, time.format(delta.unsigned_abs() as u32))
+}
+
+fn signed(delta: i64) -> String {
+    if delta < 0 {
+        delta.to_string()
+    } else {
+        format!("+{}", delta)
+    }
+}
+
+fn percent(rate: f64) -> String {
+    format!("{:.0}%", rate * 100.0)
+}
+
+/// Markdown table cell: pipes and line breaks would break the row
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Table of named groups with share of the total and the change since the previous period
+fn group_table(
+    header: &str,
+    groups: &[StatsGroup],
+    previous: &[StatsGroup],
+    total_minutes: u32,
+    time: TimeFormat,
+    name: impl Fn(&StatsGroup) -> String,
+) -> String {
+    let before: HashMap<&str, u32> = previous
+        .iter()
+        .map(|g| (g.key.as_str(), g.totals.total_minutes))
+        .collect();
+
+    let mut lines = vec![
+        format!("| {} | 时长 | 次数 | 占比 | 环比 |", header),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ];
+    for g in groups.iter().filter(|g| g.totals.session_count > 0) {
+        let share = if total_minutes > 0 {
+            g.totals.total_minutes as f64 / total_minutes as f64
+        } else {
+            0.0
+        };
+        let delta =
+            g.totals.total_minutes as i64 - *before.get(g.key.as_str()).unwrap_or(&0) as i64;
+        lines.push(format!(
+            "| {} | {} | {} | {} | {} |",
+            cell(&name(g)),
+            time.format(g.totals.total_minutes),
+            g.totals.session_count,
+            percent(share),
+            signed_time(delta, time)
+        ));
+    }
+    if lines.len() == 2 {
+        return "（无记录）".to_string();
+    }
+    lines.join("\n")
+}
+
+fn task_list(tasks: &[StatsGroup], time: TimeFormat) -> String {
+    let lines: Vec<String> = tasks
+        .iter()
+        .filter(|g| !g.key.is_empty())
+        .take(TOP_TASKS)
+        .enumerate()
+        .map(|(i, g)| {
+            format!(
+                "{}. {} — {}（{} 次）",
+                i + 1,
+                g.label,
+                time.format(g.totals.total_minutes),
+                g.totals.session_count
+            )
+        })
+        .collect();
+    if lines.is_empty() {
+        "（无记录）".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+fn weekday_name(date: NaiveDate) -> &'static str {
+    ["周一", "周二", "周三", "周四", "周五", "周六", "周日"]
+        [date.weekday().num_days_from_monday() as usize]
+}
+
+fn daily_table(days: &[StatsGroup], time: TimeFormat) -> String {
+    let mut lines = vec![
+        "| 日期 | 星期 | 时长 | 次数 | 番茄 |".to_string(),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ];
+    for g in days {
+        let weekday = NaiveDate::parse_from_str(&g.key, "%Y-%m-%d")
+            .map(weekday_name)
+            .unwrap_or("");
+        lines.push(format!(
+            "| {} | {} | {} | {} | {} |",
+            g.key,
+            weekday,
+            time.format(g.totals.total_minutes),
+            g.totals.session_count,
+            g.totals.pomodoro_count
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Placeholder values for the review of `range`, compared with `previous`.
+/// Durations are written in `time`, like the daily note totals.
+pub fn review_values(
+    records: &[PomodoroRecord],
+    period: ReviewPeriod,
+    range: (NaiveDate, NaiveDate),
+    previous: (NaiveDate, NaiveDate),
+    week_start: Weekday,
+    time: TimeFormat,
+    project_names: &HashMap<String, String>,
+) -> Result<HashMap<&'static str, String>, String> {
+    let projects = report(records, range, GroupBy::Project, week_start)?;
+    let prev_projects = report(records, previous, GroupBy::Project, week_start)?;
+    let areas = report(records, range, GroupBy::Area, week_start)?;
+    let prev_areas = report(records, previous, GroupBy::Area, week_start)?;
+    let tasks = report(records, range, GroupBy::Task, week_start)?;
+    let days = report(records, range, GroupBy::Day, week_start)?;
+
+    let now = &projects.totals;
+    let before = &prev_projects.totals;
+    let total = now.total_minutes;
+
+    let project_name = |g: &StatsGroup| {
+        if g.key.is_empty() {
+            return "（无项目）".to_string();
+        }
+        project_names
+            .get(&g.key)
+            .cloned()
+            .unwrap_or_else(|| project_folder(&g.key).to_string())
+    };
+    let area_name = |g: &StatsGroup| {
+        if g.key.is_empty() {
+            "（无领域）".to_string()
+        } else {
+            g.label.clone()
+        }
+    };
+
+    Ok(HashMap::from([
+        ("title", review_title(period, range)),
+        ("from", range.0.format("%Y-%m-%d").to_string()),
+        ("to", range.1.format("%Y-%m-%d").to_string()),
+        ("total", time.format(total)),
+        (
+            "total_delta",
+            signed_time(total as i64 - before.total_minutes as i64, time),
+        ),
+        ("sessions", now.session_count.to_string()),
+        (
+            "sessions_delta",
+            signed(now.session_count as i64 - before.session_count as i64),
+        ),
+        ("pomodoros", now.pomodoro_count.to_string()),
+        ("completion_rate", percent(now.completion_rate)),
+        (
+            "completion_delta",
+            format!(
+                "{}pp",
+                signed(((now.completion_rate - before.completion_rate) * 100.0).round() as i64)
+            ),
+        ),
+        (
+            "active_days",
+            days.groups
+                .iter()
+                .filter(|g| g.totals.session_count > 0)
+                .count()
+                .to_string(),
+        ),
+        (
+            "projects",
+            group_table(
+                "项目",
+                &projects.groups,
+                &prev_projects.groups,
+                total,
+                time,
+                project_name,
+            ),
+        ),
+        (
+            "areas",
+            group_table(
+                "领域",
+                &areas.groups,
+                &prev_areas.groups,
+                total,
+                time,
+                area_name,
+            ),
+        ),
+        ("tasks", task_list(&tasks.groups, time)),
+        ("daily", daily_table(&days.groups, time)),
+    ]))
+}
+
+/// Fill `{{name}}` placeholders; unknown placeholders are left as written
+pub fn render_review(template: &str, values: &HashMap<&'static str, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 2..];
+        match after.find("}}") {
+            Some(close) => {
+                let name = after[..close].trim();
+                match values.get(name) {
+                    Some(value) => out.push_str(value),
+                    None => out.push_str(&rest[open..open + 2 + close + 2]),
+                }
+                rest = &after[close + 2..];
+            }
+            None => {
+                out.push_str(&rest[open..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewOptions {
+    pub period: ReviewPeriod,
+    /// Any day in the period, `YYYY-MM-DD`; defaults to today
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Write to this file instead of the weekly or monthly periodic note.
+    /// Relative paths start at the vault root; it must stay inside the vault.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSummary {
+    pub path: String,
+    pub title: String,
+    pub from: String,
+    pub to: String,
+    /// True if the note did not exist before
+    pub created: bool,
+}
+
+/// Render the review for the options' period and write it into the note
+pub fn generate_review(
+    vault_path: &str,
+    records: &[PomodoroRecord],
+    options: &ReviewOptions,
+    template: &str,
+    week_start: Weekday,
+    time: TimeFormat,
+    today: NaiveDate,
+) -> Result<ReviewSummary, String> {
+    let date = match options.date {
+        Some(ref d) => {
+            NaiveDate::parse_from_str(d, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", d))?
+        }
+        None => today,
+    };
+    let range = review_range(options.period, date, week_start);
+    let previous = previous_range(options.period, range, week_start);
+    let title = review_title(options.period, range);
+
+    let project_names = vault::scan_projects(vault_path)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| (p.readme_path, p.display_name))
+        .collect();
+    let values = review_values(
+        records,
+        options.period,
+        range,
+        previous,
+        week_start,
+        time,
+        &project_names,
+    )?;
+    let block = render_review(template, &values);
+
+    let path = match options.path {
+        Some(ref p) => vault::review_note_path_in_vault(vault_path, p)?,
+        None => match options.period {
+            ReviewPeriod::Week => vault::weekly_note_path(vault_path, &title),
+            ReviewPeriod::Month => vault::monthly_note_path(vault_path, &title),
+        },
+    };
+    let created = vault::write_review_note(&path, &block)?;
+
+    Ok(ReviewSummary {
+        path,
+        title,
+        from: values["from"].clone(),
+        to: values["to"].clone(),
+        created,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        date: &str,
+        duration: u32,
+        project: Option<&str>,
+        task: Option<&str>,
+    ) -> PomodoroRecord {
+        PomodoroRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            date: date.to_string(),
+            start_time: 0,
+            end_time: 0,
+            duration,
+            mode: "pomodoro".to_string(),
+            status: "completed".to_string(),
+            project_path: project.map(String::from),
+            task_text: task.map(String::from),
+            pomodoro_index: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_review_ranges() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 7).unwrap();
+        let week = review_range(ReviewPeriod::Week, date, Weekday::Mon);
+        assert_eq!(week.0, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+        assert_eq!(week.1, NaiveDate::from_ymd_opt(2026, 1, 11).unwrap());
+        let prev = previous_range(ReviewPeriod::Week, week, Weekday::Mon);
+        assert_eq!(prev.0, NaiveDate::from_ymd_opt(2025, 12, 29).unwrap());
+
+        let month = review_range(ReviewPeriod::Month, date, Weekday::Mon);
+        assert_eq!(month.1, NaiveDate::from_ymd_opt(2026, 1, 31).unwrap());
+        let prev = previous_range(ReviewPeriod::Month, month, Weekday::Mon);
+        assert_eq!(
+            prev,
+            (
+                NaiveDate::from_ymd_opt(2025, 12, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 12, 31).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_review() {
+        let a = "1. 项目/科研-A/A.README.md";
+        let records = vec![
+            record("2026-01-05", 50, Some(a), Some("Draft")),
+            record("2026-01-06", 25, Some(a), Some("Draft")),
+            record("2026-01-07", 25, None, Some("Email")),
+            record("2025-12-30", 40, Some(a), None), // previous week
+        ];
+        let date = NaiveDate::from_ymd_opt(2026, 1, 7).unwrap();
+        let range = review_range(ReviewPeriod::Week, date, Weekday::Mon);
+        let previous = previous_range(ReviewPeriod::Week, range, Weekday::Mon);
+        let values = review_values(
+            &records,
+            ReviewPeriod::Week,
+            range,
+            previous,
+            Weekday::Mon,
+            TimeFormat::Hr,
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(values["title"], "2026-W02");
+        assert_eq!(values["total"], "1hr40");
+        assert_eq!(values["total_delta"], "+1hr00");
+        assert_eq!(values["sessions_delta"], "+2");
+        assert_eq!(values["active_days"], "3");
+        assert!(values["projects"].contains("| 科研-A | 1hr15 | 2 | 75% | +0hr35 |"));
+        assert!(values["tasks"].starts_with("1. Draft — 1hr15（2 次）"));
+        assert_eq!(values["daily"].lines().count(), 9);
+
+        let out = render_review("{{ total }} / {{unknown}} / {{sessions}}", &values);
+        assert_eq!(out, "1hr40 / {{unknown}} / 3");
+    }
+}
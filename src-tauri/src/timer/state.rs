@@ -1,3 +1,5 @@
+use crate::stats::GoalProgress;
+use crate::vault::BudgetStatus;
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 
@@ -30,6 +32,8 @@ pub struct TimerStatus {
     pub current_task: Option<String>,
     pub current_project: Option<String>,
     pub current_project_path: Option<String>,
+    pub goal: Option<GoalProgress>,
+    pub project_budget: Option<BudgetStatus>,
 }
 
 pub struct TimerState {
@@ -44,6 +48,11 @@ pub struct TimerState {
     pub current_project: Option<String>,
     pub current_project_path: Option<String>,
     pub completion_notified: bool,
+    pub goal_progress: Option<GoalProgress>,
+    /// Day the goal-reached notification was last sent
+    pub goal_notified_on: Option<String>,
+    pub goal_in_tray: bool,
+    pub project_budget: Option<BudgetStatus>,
     // Config
     pub pomodoro_duration: u32,
     pub short_break_duration: u32,
@@ -65,6 +74,10 @@ impl TimerState {
             current_project: None,
             current_project_path: None,
             completion_notified: false,
+            goal_progress: None,
+            goal_notified_on: None,
+            goal_in_tray: false,
+            project_budget: None,
             pomodoro_duration: 25,
             short_break_duration: 5,
             long_break_duration: 15,
@@ -196,10 +209,20 @@ impl TimerState {
             current_task: self.current_task.clone(),
             current_project: self.current_project.clone(),
             current_project_path: self.current_project_path.clone(),
+            goal: self.goal_progress.clone(),
+            project_budget: self.project_budget.clone(),
         }
     }
 
     pub fn tray_title(&self) -> String {
+        let title = self.timer_title();
+        match self.goal_progress {
+            Some(ref goal) if self.goal_in_tray => format!("{} 🎯{}", title, goal.short_label()),
+            _ => title,
+        }
+    }
+
+    fn timer_title(&self) -> String {
         match self.phase {
             TimerPhase::Idle => String::new(),
             TimerPhase::Running => {
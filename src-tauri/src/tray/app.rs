@@ -0,0 +1,153 @@
+use super::{
+    build_menu, recent_tasks, IconChange, IconKey, MenuShape, TrayAction, TrayMenuEntry,
+    RECENT_TASK_COUNT,
+};
+use crate::app_state::AppState;
+use crate::commands;
+use crate::control::{AppControl, ControlRequest, StartOptions, TimerControl};
+use crate::export;
+use crate::timer::{TimerMode, TimerStatus};
+use crate::vault;
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder};
+use tauri::{AppHandle, Manager, Wry};
+
+pub const TRAY_ID: &str = "main";
+
+/// Turn the menu model into a native menu
+fn native_menu(app: &AppHandle, entries: &[TrayMenuEntry]) -> tauri::Result<Menu<Wry>> {
+    let mut builder = MenuBuilder::new(app);
+    for entry in entries {
+        builder = match entry {
+            TrayMenuEntry::Label(text) => {
+                builder.item(&MenuItemBuilder::new(text).enabled(false).build(app)?)
+            }
+            TrayMenuEntry::Action {
+                action,
+                label,
+                enabled,
+            } => builder.item(
+                &MenuItemBuilder::with_id(action.id(), label)
+                    .enabled(*enabled)
+                    .build(app)?,
+            ),
+            TrayMenuEntry::Separator => builder.separator(),
+        };
+    }
+    builder.build()
+}
+
+/// The tray menu for `status`, with recent tasks and today's totals read
+/// from the vault. The recent tasks are kept for `run_tray_action`.
+pub fn tray_menu(app: &AppHandle, status: &TimerStatus) -> tauri::Result<Menu<Wry>> {
+    let state = app.state::<AppState>();
+    let vault_path = state.vault_path.lock().unwrap().clone();
+    let recent = match vault_path {
+        Some(ref vp) => {
+            let records: Vec<_> = vault::read_all_records(vp, &state.device_hash)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| r.record)
+                .collect();
+            recent_tasks(&records, &export::project_name_map(vp), RECENT_TASK_COUNT)
+        }
+        None => Vec::new(),
+    };
+    let (minutes, pomodoros) = commands::get_today_stats(app.state(), None)
+        .map(|s| (s.total_minutes, s.pomodoro_count))
+        .unwrap_or((0, 0));
+    let format = state.app_config().time_format;
+
+    let entries = build_menu(status, &recent, minutes, pomodoros, format);
+    *state.tray_recent.lock().unwrap() = recent;
+    native_menu(app, &entries)
+}
+
+/// Rebuild the tray menu when the phase or session it shows has changed
+pub fn refresh_tray_menu(app: &AppHandle, status: &TimerStatus) {
+    let state = app.state::<AppState>();
+    let shape = MenuShape::of(status);
+    {
+        let mut last = state.tray_shape.lock().unwrap();
+        if last.as_ref() == Some(&shape) {
+            return;
+        }
+        *last = Some(shape);
+    }
+
+    match tray_menu(app, status) {
+        Ok(menu) => {
+            if let Some(tray) = app.tray_by_id(TRAY_ID) {
+                let _ = tray.set_menu(Some(menu));
+            }
+        }
+        Err(e) => log::warn!("Failed to build tray menu: {}", e),
+    }
+}
+
+/// Draw the timer's progress into the tray icon. macOS shows the countdown
+/// as the tray title and keeps its monochrome template icon.
+pub fn refresh_tray_icon(app: &AppHandle, status: &TimerStatus) {
+    if cfg!(target_os = "macos") {
+        return;
+    }
+    let state = app.state::<AppState>();
+    let (change, size) = {
+        let mut cache = state.tray_icon.lock().unwrap();
+        (cache.update(IconKey::for_status(status)), cache.size())
+    };
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let result = match change {
+        IconChange::Unchanged => return,
+        IconChange::Default => tray.set_icon(app.default_window_icon().cloned()),
+        IconChange::Rendered(rgba) => {
+            tray.set_icon(Some(Image::new_owned(rgba.to_vec(), size, size)))
+        }
+    };
+    if let Err(e) = result {
+        log::warn!("Failed to update tray icon: {}", e);
+    }
+}
+
+/// Carry out a timer action picked from the tray menu. Starting from idle
+/// keeps the task and project last selected in the popover.
+pub fn run_tray_action(app: &AppHandle, action: TrayAction) {
+    let state = app.state::<AppState>();
+    let selected = || {
+        let timer = state.timer.lock().unwrap();
+        StartOptions {
+            task: timer.current_task.clone(),
+            project: timer.current_project.clone(),
+            project_path: timer.current_project_path.clone(),
+        }
+    };
+    let request = match action {
+        TrayAction::StartPomodoro => ControlRequest::StartPomodoro(selected()),
+        TrayAction::StartStopwatch => ControlRequest::StartStopwatch(selected()),
+        TrayAction::Pause => ControlRequest::Pause,
+        TrayAction::Resume => ControlRequest::Resume,
+        TrayAction::Stop => ControlRequest::Stop,
+        TrayAction::Complete => ControlRequest::Complete,
+        TrayAction::SkipBreak => ControlRequest::SkipBreak,
+        TrayAction::Restart(i) => {
+            let Some(recent) = state.tray_recent.lock().unwrap().get(i).cloned() else {
+                return;
+            };
+            let options = StartOptions {
+                task: recent.task,
+                project: recent.project,
+                project_path: recent.project_path,
+            };
+            match recent.mode {
+                TimerMode::Pomodoro => ControlRequest::StartPomodoro(options),
+                TimerMode::Stopwatch => ControlRequest::StartStopwatch(options),
+            }
+        }
+        TrayAction::ShowWindow | TrayAction::Quit => return,
+    };
+    if let Err(e) = AppControl::new(app.clone()).execute(request) {
+        log::warn!("Tray action {} failed: {}", action.id(), e);
+    }
+}
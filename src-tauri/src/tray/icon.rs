@@ -0,0 +1,284 @@
+use crate::timer::{TimerMode, TimerPhase, TimerStatus};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Width and height of rendered tray icons, in pixels
+pub const TRAY_ICON_SIZE: u32 = 32;
+/// Each side of a pixel is sampled this many times for smooth edges
+const SUPERSAMPLE: u32 = 4;
+
+/// Colour scheme of the icon, matching the popover's progress ring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconPhase {
+    Running,
+    Overtime,
+    Paused,
+    Break,
+}
+
+impl IconPhase {
+    fn color(&self) -> [u8; 3] {
+        match self {
+            IconPhase::Running => [0x22, 0xc5, 0x5e],
+            IconPhase::Overtime => [0xf9, 0x73, 0x16],
+            IconPhase::Paused => [0xea, 0xb3, 0x08],
+            IconPhase::Break => [0x3b, 0x82, 0xf6],
+        }
+    }
+}
+
+/// Everything that decides what the icon looks like. Progress is kept in
+/// whole percent so a new image is needed at most a hundred times a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IconKey {
+    pub phase: IconPhase,
+    /// 0 to 100
+    pub percent: u8,
+}
+
+impl IconKey {
+    /// The icon for a status, or None when idle and the app icon should show
+    pub fn for_status(status: &TimerStatus) -> Option<Self> {
+        let phase = match status.phase {
+            TimerPhase::Idle => return None,
+            TimerPhase::Running if status.overtime_secs > 0 => IconPhase::Overtime,
+            TimerPhase::Running => IconPhase::Running,
+            TimerPhase::Paused => IconPhase::Paused,
+            TimerPhase::ShortBreak | TimerPhase::LongBreak => IconPhase::Break,
+        };
+        // Stopwatches have no end, so their ring stays full
+        let percent = match (status.mode, status.remaining_secs) {
+            _ if status.overtime_secs > 0 => 100,
+            (TimerMode::Pomodoro, Some(remaining)) => (status.elapsed_secs * 100)
+                .checked_div(status.elapsed_secs + remaining)
+                .map_or(0, |p| p.min(100) as u8),
+            _ => 100,
+        };
+        Some(Self { phase, percent })
+    }
+}
+
+/// Where a sample point falls, for a `size`-pixel icon
+fn sample(key: &IconKey, size: f32, x: f32, y: f32) -> Option<[u8; 4]> {
+    let center = size / 2.0;
+    let (dx, dy) = (x - center, y - center);
+    let distance = (dx * dx + dy * dy).sqrt();
+    let [r, g, b] = key.phase.color();
+
+    let (inner, outer) = (size * 0.30, size * 0.47);
+    if distance >= inner && distance <= outer {
+        // Clockwise from twelve o'clock, 0.0 to 1.0
+        let turn = dx.atan2(-dy).rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+        let alpha = if turn * 100.0 < key.percent as f32 {
+            255
+        } else {
+            80
+        };
+        return Some([r, g, b, alpha]);
+    }
+
+    // Pause bars in the middle of the ring
+    if key.phase == IconPhase::Paused {
+        let (u, v) = (x / size, y / size);
+        let in_bar = (0.36..0.46).contains(&u) || (0.54..0.64).contains(&u);
+        if in_bar && (0.35..0.65).contains(&v) {
+            return Some([r, g, b, 255]);
+        }
+    }
+    None
+}
+
+/// Draw the icon as straight (not premultiplied) RGBA, row by row
+pub fn render_tray_icon(key: &IconKey, size: u32) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    let samples = (SUPERSAMPLE * SUPERSAMPLE) as f32;
+    let step = 1.0 / SUPERSAMPLE as f32;
+
+    for py in 0..size {
+        for px in 0..size {
+            let mut color = [0.0f32; 3];
+            let mut alpha = 0.0f32;
+            for sy in 0..SUPERSAMPLE {
+                for sx in 0..SUPERSAMPLE {
+                    let x = px as f32 + (sx as f32 + 0.5) * step;
+                    let y = py as f32 + (sy as f32 + 0.5) * step;
+                    if let Some([r, g, b, a]) = sample(key, size as f32, x, y) {
+                        let a = a as f32 / 255.0;
+                        color[0] += r as f32 * a;
+                        color[1] += g as f32 * a;
+                        color[2] += b as f32 * a;
+                        alpha += a;
+                    }
+                }
+            }
+            if alpha == 0.0 {
+                pixels.extend_from_slice(&[0, 0, 0, 0]);
+            } else {
+                pixels.extend_from_slice(&[
+                    (color[0] / alpha).round() as u8,
+                    (color[1] / alpha).round() as u8,
+                    (color[2] / alpha).round() as u8,
+                    (alpha / samples * 255.0).round() as u8,
+                ]);
+            }
+        }
+    }
+    pixels
+}
+
+/// What the tray should do after a tick
+#[derive(Debug, Clone, PartialEq)]
+pub enum IconChange {
+    Unchanged,
+    /// Back to the app icon
+    Default,
+    Rendered(Arc<Vec<u8>>),
+}
+
+/// Renders each icon once and remembers which one the tray shows
+pub struct TrayIconCache {
+    size: u32,
+    images: HashMap<IconKey, Arc<Vec<u8>>>,
+    shown: Option<IconKey>,
+}
+
+impl TrayIconCache {
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            images: HashMap::new(),
+            shown: None,
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn image(&mut self, key: IconKey) -> Arc<Vec<u8>> {
+        let size = self.size;
+        self.images
+            .entry(key)
+            .or_insert_with(|| Arc::new(render_tray_icon(&key, size)))
+            .clone()
+    }
+
+    /// Switch to the icon for `key`, reporting whether the tray must change
+    pub fn update(&mut self, key: Option<IconKey>) -> IconChange {
+        if key == self.shown {
+            return IconChange::Unchanged;
+        }
+        self.shown = key;
+        match key {
+            Some(key) => IconChange::Rendered(self.image(key)),
+            None => IconChange::Default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+    use std::time::Duration;
+
+    const SIZE: u32 = 32;
+
+    fn pixel(image: &[u8], x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * SIZE + x) * 4) as usize;
+        [image[i], image[i + 1], image[i + 2], image[i + 3]]
+    }
+
+    #[test]
+    fn test_key_follows_status() {
+        let mut timer = TimerState::new();
+        assert_eq!(IconKey::for_status(&timer.status()), None);
+
+        timer.start_pomodoro();
+        timer.pause_elapsed = Duration::from_secs(timer.duration_secs / 2);
+        let key = IconKey::for_status(&timer.status()).unwrap();
+        assert_eq!(key.phase, IconPhase::Running);
+        assert_eq!(key.percent, 50);
+
+        timer.pause();
+        assert_eq!(
+            IconKey::for_status(&timer.status()).unwrap().phase,
+            IconPhase::Paused
+        );
+
+        timer.start_stopwatch();
+        assert_eq!(
+            IconKey::for_status(&timer.status()),
+            Some(IconKey {
+                phase: IconPhase::Running,
+                percent: 100
+            })
+        );
+
+        timer.start_break();
+        assert_eq!(
+            IconKey::for_status(&timer.status()).unwrap().phase,
+            IconPhase::Break
+        );
+    }
+
+    #[test]
+    fn test_ring_shows_progress() {
+        let key = IconKey {
+            phase: IconPhase::Running,
+            percent: 50,
+        };
+        let image = render_tray_icon(&key, SIZE);
+        assert_eq!(image.len(), (SIZE * SIZE * 4) as usize);
+
+        // Corners and the middle stay transparent
+        assert_eq!(pixel(&image, 0, 0)[3], 0);
+        assert_eq!(pixel(&image, SIZE / 2, SIZE / 2)[3], 0);
+
+        // Three o'clock is done, nine o'clock is still to come
+        let done = pixel(&image, SIZE - 4, SIZE / 2);
+        let todo = pixel(&image, 3, SIZE / 2);
+        assert_eq!(done, [0x22, 0xc5, 0x5e, 255]);
+        assert_eq!(&todo[..3], &[0x22, 0xc5, 0x5e]);
+        assert_eq!(todo[3], 80);
+
+        let overtime = render_tray_icon(
+            &IconKey {
+                phase: IconPhase::Overtime,
+                percent: 100,
+            },
+            SIZE,
+        );
+        assert_eq!(pixel(&overtime, 3, SIZE / 2), [0xf9, 0x73, 0x16, 255]);
+
+        let paused = render_tray_icon(
+            &IconKey {
+                phase: IconPhase::Paused,
+                percent: 50,
+            },
+            SIZE,
+        );
+        assert_eq!(pixel(&paused, 13, SIZE / 2), [0xea, 0xb3, 0x08, 255]);
+        assert_eq!(pixel(&paused, SIZE / 2, SIZE / 2)[3], 0);
+    }
+
+    #[test]
+    fn test_cache_renders_once() {
+        let mut cache = TrayIconCache::new(SIZE);
+        let key = IconKey {
+            phase: IconPhase::Break,
+            percent: 10,
+        };
+        let IconChange::Rendered(first) = cache.update(Some(key)) else {
+            panic!("expected an image");
+        };
+        assert_eq!(cache.update(Some(key)), IconChange::Unchanged);
+        assert_eq!(cache.update(None), IconChange::Default);
+        assert_eq!(cache.update(None), IconChange::Unchanged);
+
+        let IconChange::Rendered(again) = cache.update(Some(key)) else {
+            panic!("expected an image");
+        };
+        assert!(Arc::ptr_eq(&first, &again));
+    }
+}
@@ -0,0 +1,393 @@
+use crate::timer::{TimerMode, TimerPhase, TimerStatus};
+use crate::vault::{PomodoroRecord, TimeFormat};
+use std::collections::HashMap;
+
+/// How many recent tasks the menu offers to restart
+pub const RECENT_TASK_COUNT: usize = 5;
+/// Longer task names are cut short so the menu stays narrow
+const MAX_LABEL_CHARS: usize = 30;
+
+/// What a tray menu item does when clicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayAction {
+    StartPomodoro,
+    StartStopwatch,
+    Pause,
+    Resume,
+    Stop,
+    Complete,
+    SkipBreak,
+    /// Start the recent task at this index again
+    Restart(usize),
+    ShowWindow,
+    Quit,
+}
+
+impl TrayAction {
+    pub fn id(&self) -> String {
+        match self {
+            TrayAction::StartPomodoro => "start".to_string(),
+            TrayAction::StartStopwatch => "stopwatch".to_string(),
+            TrayAction::Pause => "pause".to_string(),
+            TrayAction::Resume => "resume".to_string(),
+            TrayAction::Stop => "stop".to_string(),
+            TrayAction::Complete => "complete".to_string(),
+            TrayAction::SkipBreak => "skip-break".to_string(),
+            TrayAction::Restart(i) => format!("recent-{}", i),
+            TrayAction::ShowWindow => "show".to_string(),
+            TrayAction::Quit => "quit".to_string(),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        let action = match id {
+            "start" => TrayAction::StartPomodoro,
+            "stopwatch" => TrayAction::StartStopwatch,
+            "pause" => TrayAction::Pause,
+            "resume" => TrayAction::Resume,
+            "stop" => TrayAction::Stop,
+            "complete" => TrayAction::Complete,
+            "skip-break" => TrayAction::SkipBreak,
+            "show" => TrayAction::ShowWindow,
+            "quit" => TrayAction::Quit,
+            other => TrayAction::Restart(other.strip_prefix("recent-")?.parse().ok()?),
+        };
+        Some(action)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayMenuEntry {
+    /// Greyed-out information line
+    Label(String),
+    Action {
+        action: TrayAction,
+        label: String,
+        enabled: bool,
+    },
+    Separator,
+}
+
+fn action(action: TrayAction, label: &str) -> TrayMenuEntry {
+    TrayMenuEntry::Action {
+        action,
+        label: label.to_string(),
+        enabled: true,
+    }
+}
+
+/// A task timed recently, offered for one-click restart
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentTask {
+    pub task: Option<String>,
+    /// Display name of the project
+    pub project: Option<String>,
+    pub project_path: Option<String>,
+    pub mode: TimerMode,
+}
+
+impl RecentTask {
+    fn label(&self) -> String {
+        let icon = match self.mode {
+            TimerMode::Pomodoro => "🍅",
+            TimerMode::Stopwatch => "🕙",
+        };
+        let text = match (&self.task, &self.project) {
+            (Some(task), Some(project)) => format!("{} · {}", task, project),
+            (Some(task), None) => task.clone(),
+            (None, Some(project)) => project.clone(),
+            (None, None) => String::new(),
+        };
+        format!("{} {}", icon, truncate(&text, MAX_LABEL_CHARS))
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars - 1).collect();
+    format!("{}…", cut)
+}
+
+/// The latest distinct task/project pairs, newest first. Records with
+/// neither a task nor a project have nothing worth restarting.
+pub fn recent_tasks(
+    records: &[PomodoroRecord],
+    project_names: &HashMap<String, String>,
+    limit: usize,
+) -> Vec<RecentTask> {
+    let mut sorted: Vec<&PomodoroRecord> = records
+        .iter()
+        .filter(|r| r.task_text.is_some() || r.project_path.is_some())
+        .collect();
+    sorted.sort_by_key(|r| std::cmp::Reverse(r.end_time));
+
+    let mut recent: Vec<RecentTask> = Vec::new();
+    for r in sorted {
+        if recent
+            .iter()
+            .any(|t| t.task == r.task_text && t.project_path == r.project_path)
+        {
+            continue;
+        }
+        recent.push(RecentTask {
+            task: r.task_text.clone(),
+            project: r
+                .project_path
+                .as_ref()
+                .map(|pp| project_names.get(pp).cloned().unwrap_or_else(|| pp.clone())),
+            project_path: r.project_path.clone(),
+            mode: if r.mode == "stopwatch" {
+                TimerMode::Stopwatch
+            } else {
+                TimerMode::Pomodoro
+            },
+        });
+        if recent.len() == limit {
+            break;
+        }
+    }
+    recent
+}
+
+/// What the menu shows apart from recent tasks and totals; the menu is
+/// rebuilt whenever this changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuShape {
+    phase: TimerPhase,
+    mode: TimerMode,
+    overtime: bool,
+    task: Option<String>,
+}
+
+impl MenuShape {
+    pub fn of(status: &TimerStatus) -> Self {
+        Self {
+            phase: status.phase,
+            mode: status.mode,
+            overtime: status.overtime_secs > 0,
+            task: status.current_task.clone(),
+        }
+    }
+}
+
+/// One line describing what the timer is doing
+pub fn status_line(status: &TimerStatus) -> String {
+    let state = match (status.phase, status.mode) {
+        (TimerPhase::Idle, _) => "🍅 未在计时",
+        (TimerPhase::Running, TimerMode::Pomodoro) if status.overtime_secs > 0 => "✅ 番茄钟已完成",
+        (TimerPhase::Running, TimerMode::Pomodoro) => "🍅 专注中",
+        (TimerPhase::Running, TimerMode::Stopwatch) => "🕙 正计时中",
+        (TimerPhase::Paused, _) => "⏸ 已暂停",
+        (TimerPhase::ShortBreak, _) => "☕ 短休息中",
+        (TimerPhase::LongBreak, _) => "☕ 长休息中",
+    };
+    let subject = if status.phase == TimerPhase::Idle {
+        None
+    } else {
+        status
+            .current_task
+            .as_ref()
+            .or(status.current_project.as_ref())
+    };
+    match subject {
+        Some(s) => format!("{} · {}", state, truncate(s, MAX_LABEL_CHARS)),
+        None => state.to_string(),
+    }
+}
+
+/// Controls for the current phase, matching the popover's buttons
+fn controls(status: &TimerStatus) -> Vec<TrayMenuEntry> {
+    match status.phase {
+        TimerPhase::Idle => vec![
+            action(TrayAction::StartPomodoro, "▶ 开始番茄钟"),
+            action(TrayAction::StartStopwatch, "⏱ 开始正计时"),
+        ],
+        TimerPhase::Running if status.mode == TimerMode::Pomodoro && status.overtime_secs > 0 => {
+            vec![
+                action(TrayAction::Complete, "☕ 休息"),
+                action(TrayAction::Stop, "■ 停止"),
+            ]
+        }
+        TimerPhase::Running => vec![
+            action(TrayAction::Pause, "⏸ 暂停"),
+            action(TrayAction::Stop, "■ 停止"),
+        ],
+        TimerPhase::Paused => vec![
+            action(TrayAction::Resume, "▶ 继续"),
+            action(TrayAction::Stop, "■ 停止"),
+        ],
+        TimerPhase::ShortBreak | TimerPhase::LongBreak => {
+            vec![action(TrayAction::SkipBreak, "⏭ 跳过休息")]
+        }
+    }
+}
+
+/// The whole tray menu, top to bottom. Recent tasks can only be started
+/// when no session is running.
+pub fn build_menu(
+    status: &TimerStatus,
+    recent: &[RecentTask],
+    today_minutes: u32,
+    today_pomodoros: u32,
+    format: TimeFormat,
+) -> Vec<TrayMenuEntry> {
+    let mut entries = vec![TrayMenuEntry::Label(status_line(status))];
+    entries.extend(controls(status));
+
+    if !recent.is_empty() {
+        let can_start = !matches!(status.phase, TimerPhase::Running | TimerPhase::Paused);
+        entries.push(TrayMenuEntry::Separator);
+        entries.push(TrayMenuEntry::Label("最近任务".to_string()));
+        entries.extend(
+            recent
+                .iter()
+                .enumerate()
+                .map(|(i, task)| TrayMenuEntry::Action {
+                    action: TrayAction::Restart(i),
+                    label: task.label(),
+                    enabled: can_start,
+                }),
+        );
+    }
+
+    entries.push(TrayMenuEntry::Separator);
+    entries.push(TrayMenuEntry::Label(format!(
+        "今日: {} · 🍅×{}",
+        format.format(today_minutes),
+        today_pomodoros
+    )));
+    entries.push(TrayMenuEntry::Separator);
+    entries.push(action(TrayAction::ShowWindow, "打开 Bar Tomato"));
+    entries.push(action(TrayAction::Quit, "退出 Bar Tomato"));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+
+    fn record(task: Option<&str>, project_path: Option<&str>, end_time: u64) -> PomodoroRecord {
+        PomodoroRecord {
+            id: end_time.to_string(),
+            date: "2026-01-05".to_string(),
+            start_time: end_time - 60_000,
+            end_time,
+            duration: 1,
+            mode: "pomodoro".to_string(),
+            status: "completed".to_string(),
+            project_path: project_path.map(String::from),
+            task_text: task.map(String::from),
+            pomodoro_index: None,
+            source: None,
+        }
+    }
+
+    fn actions(entries: &[TrayMenuEntry]) -> Vec<(TrayAction, bool)> {
+        entries
+            .iter()
+            .filter_map(|e| match e {
+                TrayMenuEntry::Action {
+                    action, enabled, ..
+                } => Some((*action, *enabled)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_action_ids_round_trip() {
+        for action in [
+            TrayAction::StartPomodoro,
+            TrayAction::SkipBreak,
+            TrayAction::Restart(3),
+            TrayAction::Quit,
+        ] {
+            assert_eq!(TrayAction::from_id(&action.id()), Some(action));
+        }
+        assert_eq!(TrayAction::from_id("recent-x"), None);
+        assert_eq!(TrayAction::from_id("other"), None);
+    }
+
+    #[test]
+    fn test_recent_tasks_newest_distinct() {
+        let records = vec![
+            record(Some("写论文"), Some("Projects/Thesis/README.md"), 3_000_000),
+            record(None, None, 9_000_000),
+            record(Some("写论文"), Some("Projects/Thesis/README.md"), 5_000_000),
+            record(Some("读书"), None, 4_000_000),
+            record(Some("写论文"), None, 1_000_000),
+        ];
+        let names = HashMap::from([(
+            "Projects/Thesis/README.md".to_string(),
+            "Thesis".to_string(),
+        )]);
+        let recent = recent_tasks(&records, &names, 2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].project.as_deref(), Some("Thesis"));
+        assert_eq!(recent[0].label(), "🍅 写论文 · Thesis");
+        assert_eq!(recent[1].task.as_deref(), Some("读书"));
+        assert_eq!(recent_tasks(&records, &names, 5).len(), 3);
+    }
+
+    #[test]
+    fn test_menu_follows_phase() {
+        let recent = vec![RecentTask {
+            task: Some(
+                "一个非常非常非常非常非常非常非常非常非常非常非常非常长的任务名称".to_string(),
+            ),
+            project: None,
+            project_path: None,
+            mode: TimerMode::Stopwatch,
+        }];
+        let mut timer = TimerState::new();
+        let idle = build_menu(&timer.status(), &recent, 65, 2, TimeFormat::Hr);
+        assert_eq!(idle[0], TrayMenuEntry::Label("🍅 未在计时".to_string()));
+        assert_eq!(
+            actions(&idle),
+            vec![
+                (TrayAction::StartPomodoro, true),
+                (TrayAction::StartStopwatch, true),
+                (TrayAction::Restart(0), true),
+                (TrayAction::ShowWindow, true),
+                (TrayAction::Quit, true),
+            ]
+        );
+        assert!(idle.contains(&TrayMenuEntry::Label("今日: 1hr05 · 🍅×2".to_string())));
+        let TrayMenuEntry::Action { label, .. } = &idle[5] else {
+            panic!("expected the recent task");
+        };
+        assert_eq!(label.chars().count(), MAX_LABEL_CHARS + 2);
+
+        timer.current_task = Some("写论文".to_string());
+        timer.start_pomodoro();
+        let running = build_menu(&timer.status(), &recent, 0, 0, TimeFormat::Hr);
+        assert_eq!(
+            running[0],
+            TrayMenuEntry::Label("🍅 专注中 · 写论文".to_string())
+        );
+        assert_eq!(
+            actions(&running)[..3],
+            [
+                (TrayAction::Pause, true),
+                (TrayAction::Stop, true),
+                (TrayAction::Restart(0), false),
+            ]
+        );
+
+        timer.pause();
+        assert_eq!(
+            actions(&build_menu(&timer.status(), &[], 0, 0, TimeFormat::Hr))[..2],
+            [(TrayAction::Resume, true), (TrayAction::Stop, true)]
+        );
+        let paused = MenuShape::of(&timer.status());
+
+        timer.start_break();
+        let on_break = build_menu(&timer.status(), &recent, 0, 0, TimeFormat::Hr);
+        assert_eq!(actions(&on_break)[0], (TrayAction::SkipBreak, true));
+        assert_eq!(actions(&on_break)[1], (TrayAction::Restart(0), true));
+        assert_ne!(MenuShape::of(&timer.status()), paused);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod app;
+pub mod icon;
+pub mod menu;
+
+pub use app::*;
+pub use icon::*;
+pub use menu::*;
@@ -0,0 +1,664 @@
+use super::{
+    adjust_project_time, get_records_file_path, get_storage_dir, project_display_name,
+    read_all_records, remove_record, DeviceRecord, LiveSession, PomodoroRecord, TimeFormat,
+};
+use crate::timer::{TimerMode, TimerPhase};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Overlaps shorter than this are clock skew or back-to-back sessions
+pub const MIN_OVERLAP_MS: u64 = 60_000;
+/// The plugin's timer is ignored once it claims to have run this long,
+/// which happens when Obsidian quits mid-session
+pub const PLUGIN_SESSION_MAX_MS: u64 = 12 * 60 * 60 * 1000;
+/// Stands in for a device hash on the plugin's own timer
+pub const PLUGIN_DEVICE_HASH: &str = "lifeos-plugin";
+pub const PLUGIN_DEVICE_NAME: &str = "LifeOS 插件";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictResolution {
+    /// Count the overlapping time once
+    Merge,
+    /// Both sessions really happened; count them in full
+    KeepBoth,
+    /// One of the records should not have been kept
+    Discard,
+}
+
+/// How the user settled one pair of overlapping records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedConflict {
+    /// The two record ids, sorted
+    pub record_ids: [String; 2],
+    pub resolution: ConflictResolution,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discarded_id: Option<String>,
+    pub resolved_at: u64,
+    pub device_hash: String,
+}
+
+/// Resolutions shared in the storage folder so every device counts the
+/// same records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictsFile {
+    pub version: u32,
+    pub resolved: Vec<ResolvedConflict>,
+}
+
+impl ConflictsFile {
+    pub fn new() -> Self {
+        Self {
+            version: 1,
+            resolved: Vec::new(),
+        }
+    }
+
+    pub fn resolution_for(&self, a: &str, b: &str) -> Option<ConflictResolution> {
+        let ids = pair_ids(a, b);
+        self.resolved
+            .iter()
+            .find(|r| r.record_ids == ids)
+            .map(|r| r.resolution)
+    }
+
+    pub fn is_discarded(&self, id: &str) -> bool {
+        self.resolved
+            .iter()
+            .any(|r| r.discarded_id.as_deref() == Some(id))
+    }
+}
+
+fn pair_ids(a: &str, b: &str) -> [String; 2] {
+    if a <= b {
+        [a.to_string(), b.to_string()]
+    } else {
+        [b.to_string(), a.to_string()]
+    }
+}
+
+/// Two records from the same stretch of time, `first` starting earlier
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordConflict {
+    pub first: DeviceRecord,
+    pub second: DeviceRecord,
+    pub overlap_minutes: u32,
+}
+
+/// The user's answer to a conflict, as sent by the popover
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictChoice {
+    pub first_id: String,
+    pub second_id: String,
+    pub resolution: ConflictResolution,
+    /// Which of the two records to drop when discarding
+    pub discard_id: Option<String>,
+}
+
+fn get_conflicts_file_path(vault_path: &str) -> PathBuf {
+    get_storage_dir(vault_path).join("pomodoro-conflicts.json")
+}
+
+pub fn read_conflicts(vault_path: &str) -> Result<ConflictsFile, String> {
+    let path = get_conflicts_file_path(vault_path);
+    if !path.exists() {
+        return Ok(ConflictsFile::new());
+    }
+
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read conflicts: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse conflicts: {}", e))
+}
+
+/// Store a resolution, replacing any earlier one for the same pair
+pub fn record_resolution(vault_path: &str, resolved: ResolvedConflict) -> Result<(), String> {
+    let mut file = read_conflicts(vault_path)?;
+    file.resolved
+        .retain(|r| r.record_ids != resolved.record_ids);
+    file.resolved.push(resolved);
+
+    std::fs::create_dir_all(get_storage_dir(vault_path))
+        .map_err(|e| format!("Failed to create storage dir: {}", e))?;
+    let path = get_conflicts_file_path(vault_path);
+    let content = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize conflicts: {}", e))?;
+
+    // Write atomically: write to temp file, then rename
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, content).map_err(|e| format!("Failed to write temp file: {}", e))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to rename temp file: {}", e))
+}
+
+fn overlap_ms(a: &PomodoroRecord, b: &PomodoroRecord) -> u64 {
+    a.end_time
+        .min(b.end_time)
+        .saturating_sub(a.start_time.max(b.start_time))
+}
+
+fn to_minutes(ms: u64) -> u32 {
+    ((ms + 30_000) / 60_000) as u32
+}
+
+fn sorted_by_start(records: &mut [DeviceRecord]) {
+    records.sort_by_key(|r| (r.record.start_time, r.record.end_time));
+}
+
+/// Overlapping pairs of records the user has not resolved yet
+pub fn find_conflicts(
+    records: &[DeviceRecord],
+    resolutions: &ConflictsFile,
+) -> Vec<RecordConflict> {
+    let mut sorted: Vec<DeviceRecord> = records
+        .iter()
+        .filter(|r| !resolutions.is_discarded(&r.record.id))
+        .cloned()
+        .collect();
+    sorted_by_start(&mut sorted);
+
+    let mut conflicts = Vec::new();
+    for (i, a) in sorted.iter().enumerate() {
+        let later = sorted[i + 1..]
+            .iter()
+            .take_while(|b| b.record.start_time < a.record.end_time);
+        for b in later {
+            let overlap = overlap_ms(&a.record, &b.record);
+            if overlap >= MIN_OVERLAP_MS
+                && resolutions
+                    .resolution_for(&a.record.id, &b.record.id)
+                    .is_none()
+            {
+                conflicts.push(RecordConflict {
+                    first: a.clone(),
+                    second: b.clone(),
+                    overlap_minutes: to_minutes(overlap),
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// The records as they should count toward totals: discarded records are
+/// dropped and time already covered by an earlier record is cut from the
+/// start of the later one, unless the pair was kept in full
+pub fn counted_records(
+    mut records: Vec<DeviceRecord>,
+    resolutions: &ConflictsFile,
+) -> Vec<DeviceRecord> {
+    records.retain(|r| !resolutions.is_discarded(&r.record.id));
+    sorted_by_start(&mut records);
+
+    let mut counted: Vec<DeviceRecord> = Vec::with_capacity(records.len());
+    // Counted records that may still overlap the ones after them
+    let mut open: Vec<usize> = Vec::new();
+    for mut r in records {
+        open.retain(|&i| counted[i].record.end_time > r.record.start_time);
+        let covered_until = open
+            .iter()
+            .map(|&i| &counted[i].record)
+            .filter(|c| {
+                overlap_ms(c, &r.record) >= MIN_OVERLAP_MS
+                    && resolutions.resolution_for(&c.id, &r.record.id)
+                        != Some(ConflictResolution::KeepBoth)
+            })
+            .map(|c| c.end_time)
+            .max();
+
+        if let Some(until) = covered_until {
+            if until >= r.record.end_time {
+                continue;
+            }
+            let cut = to_minutes(until - r.record.start_time);
+            r.record.start_time = until;
+            r.record.duration = r.record.duration.saturating_sub(cut);
+            if r.record.duration == 0 {
+                continue;
+            }
+        }
+        open.push(counted.len());
+        counted.push(r);
+    }
+    counted
+}
+
+/// Every device's records with overlaps counted once, for totals and stats
+pub fn read_counted_records(
+    vault_path: &str,
+    device_hash: &str,
+) -> Result<Vec<DeviceRecord>, String> {
+    let records = read_all_records(vault_path, device_hash)?;
+    let resolutions = read_conflicts(vault_path).unwrap_or_else(|e| {
+        log::warn!("{}", e);
+        ConflictsFile::new()
+    });
+    Ok(counted_records(records, &resolutions))
+}
+
+/// Settle a conflict for every device. Merging takes the overlap off the
+/// later record's project in the daily note; discarding takes the whole
+/// record off and deletes it when it belongs to this device. A pair is
+/// settled once, so the daily note is never adjusted twice for it. Returns
+/// the records whose notes need refreshing.
+pub fn resolve_conflict(
+    vault_path: &str,
+    device_hash: &str,
+    choice: &ConflictChoice,
+    format: TimeFormat,
+    now_ms: u64,
+) -> Result<Vec<PomodoroRecord>, String> {
+    let (first_id, second_id) = (choice.first_id.as_str(), choice.second_id.as_str());
+    let resolution = choice.resolution;
+    if read_conflicts(vault_path)?
+        .resolution_for(first_id, second_id)
+        .is_some()
+    {
+        return Err("Conflict already resolved".to_string());
+    }
+    let records = read_all_records(vault_path, device_hash)?;
+    let find = |id: &str| {
+        records
+            .iter()
+            .find(|r| r.record.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Record not found: {}", id))
+    };
+    let (a, b) = (find(first_id)?, find(second_id)?);
+    let overlap = overlap_ms(&a.record, &b.record);
+    if overlap == 0 {
+        return Err("Records do not overlap".to_string());
+    }
+
+    let discarded = match (resolution, choice.discard_id.as_deref()) {
+        (ConflictResolution::Discard, Some(id)) if id == first_id => Some(a.clone()),
+        (ConflictResolution::Discard, Some(id)) if id == second_id => Some(b.clone()),
+        (ConflictResolution::Discard, _) => {
+            return Err("Choose which record to discard".to_string())
+        }
+        _ => None,
+    };
+
+    record_resolution(
+        vault_path,
+        ResolvedConflict {
+            record_ids: pair_ids(first_id, second_id),
+            resolution,
+            discarded_id: discarded.as_ref().map(|d| d.record.id.clone()),
+            resolved_at: now_ms,
+            device_hash: device_hash.to_string(),
+        },
+    )?;
+
+    // The daily note counted both records in full
+    let take_off = |record: &PomodoroRecord, minutes: u32| {
+        if let Some(ref pp) = record.project_path {
+            let name = project_display_name(vault_path, pp);
+            let _ = adjust_project_time(
+                vault_path,
+                &record.date,
+                pp,
+                &name,
+                -(minutes as i32),
+                format,
+            );
+        }
+    };
+    let touched = match (resolution, discarded) {
+        (ConflictResolution::Merge, _) => {
+            let later = if a.record.start_time <= b.record.start_time {
+                b.record
+            } else {
+                a.record
+            };
+            take_off(&later, to_minutes(overlap).min(later.duration));
+            vec![later]
+        }
+        (ConflictResolution::Discard, Some(d)) => {
+            take_off(&d.record, d.record.duration);
+            if d.device_hash == device_hash {
+                remove_record(
+                    &get_records_file_path(vault_path, device_hash),
+                    &d.record.id,
+                )?;
+            }
+            vec![d.record]
+        }
+        _ => Vec::new(),
+    };
+    Ok(touched)
+}
+
+/// The plugin's own timer as a session, read from `pomodoroState` in its
+/// `data.json`. The plugin only saves that state on changes, so it has no
+/// heartbeat and is trusted until `PLUGIN_SESSION_MAX_MS` has passed.
+pub fn plugin_session_from_json(json: &serde_json::Value, now_ms: u64) -> Option<LiveSession> {
+    let state = json.get("pomodoroState")?;
+    let flag = |key: &str| state.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+    let text = |key: &str| {
+        state
+            .get(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    if !flag("isRunning") {
+        return None;
+    }
+    let started_at = state.get("startTime").and_then(|v| v.as_u64())?;
+    if now_ms.saturating_sub(started_at) > PLUGIN_SESSION_MAX_MS {
+        return None;
+    }
+
+    let phase = if flag("isBreak") {
+        TimerPhase::ShortBreak
+    } else if flag("isPaused") {
+        TimerPhase::Paused
+    } else {
+        TimerPhase::Running
+    };
+    let mode = match state.get("mode").and_then(|v| v.as_str()) {
+        Some("stopwatch") => TimerMode::Stopwatch,
+        _ => TimerMode::Pomodoro,
+    };
+    Some(LiveSession {
+        device_hash: PLUGIN_DEVICE_HASH.to_string(),
+        device_name: PLUGIN_DEVICE_NAME.to_string(),
+        phase,
+        mode,
+        started_at,
+        expected_end: state.get("endTime").and_then(|v| v.as_u64()),
+        task: text("taskText"),
+        project: None,
+        project_path: text("projectPath"),
+        pomodoro_count: 0,
+        updated_at: now_ms,
+    })
+}
+
+pub fn read_plugin_session(vault_path: &str, now_ms: u64) -> Option<LiveSession> {
+    let path = Path::new(vault_path)
+        .join(".obsidian")
+        .join("plugins")
+        .join("lifeos-pro")
+        .join("data.json");
+    let content = std::fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    plugin_session_from_json(&json, now_ms)
+}
+
+/// Other timers counting focus time while `own` is
+pub fn live_conflicts(
+    own: &LiveSession,
+    others: Vec<LiveSession>,
+    now_ms: u64,
+) -> Vec<LiveSession> {
+    if !own.is_focus() {
+        return Vec::new();
+    }
+    others
+        .into_iter()
+        .filter(|s| s.device_hash != own.device_hash && s.is_focus() && !s.is_stale(now_ms))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::{read_records, write_records, RecordsFile};
+
+    const MIN: u64 = 60_000;
+
+    fn record(device: &str, id: &str, start_min: u64, end_min: u64) -> DeviceRecord {
+        DeviceRecord {
+            device_hash: device.to_string(),
+            record: PomodoroRecord {
+                id: id.to_string(),
+                date: "2026-01-05".to_string(),
+                start_time: start_min * MIN,
+                end_time: end_min * MIN,
+                duration: (end_min - start_min) as u32,
+                mode: "pomodoro".to_string(),
+                status: "completed".to_string(),
+                project_path: None,
+                task_text: None,
+                pomodoro_index: None,
+                source: None,
+            },
+        }
+    }
+
+    fn resolved(
+        a: &str,
+        b: &str,
+        resolution: ConflictResolution,
+        discarded: Option<&str>,
+    ) -> ResolvedConflict {
+        ResolvedConflict {
+            record_ids: pair_ids(a, b),
+            resolution,
+            discarded_id: discarded.map(String::from),
+            resolved_at: 0,
+            device_hash: "x".to_string(),
+        }
+    }
+
+    fn durations(records: &[DeviceRecord]) -> Vec<(&str, u32)> {
+        records
+            .iter()
+            .map(|r| (r.record.id.as_str(), r.record.duration))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        // Under a minute of overlap is not a conflict
+        let mut next = record("a", "next", 45, 70);
+        next.record.start_time -= 30_000;
+        let records = vec![
+            record("b", "late", 20, 45),
+            record("a", "early", 0, 25),
+            next,
+        ];
+        let conflicts = find_conflicts(&records, &ConflictsFile::new());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].first.record.id, "early");
+        assert_eq!(conflicts[0].second.record.id, "late");
+        assert_eq!(conflicts[0].overlap_minutes, 5);
+
+        let mut file = ConflictsFile::new();
+        file.resolved.push(resolved(
+            "late",
+            "early",
+            ConflictResolution::KeepBoth,
+            None,
+        ));
+        assert!(find_conflicts(&records, &file).is_empty());
+    }
+
+    #[test]
+    fn test_counted_records_trim_overlaps() {
+        let records = vec![
+            record("a", "1", 0, 25),
+            record("b", "2", 10, 35),
+            // Entirely inside the first
+            record("c", "3", 5, 20),
+            record("a", "4", 60, 85),
+        ];
+        let counted = counted_records(records.clone(), &ConflictsFile::new());
+        assert_eq!(durations(&counted), vec![("1", 25), ("2", 10), ("4", 25)]);
+        assert_eq!(counted[1].record.start_time, 25 * MIN);
+
+        let mut file = ConflictsFile::new();
+        file.resolved
+            .push(resolved("1", "2", ConflictResolution::KeepBoth, None));
+        file.resolved
+            .push(resolved("1", "3", ConflictResolution::Discard, Some("3")));
+        let counted = counted_records(records, &file);
+        assert_eq!(durations(&counted), vec![("1", 25), ("2", 25), ("4", 25)]);
+    }
+
+    #[test]
+    fn test_resolve_conflict() {
+        let vault =
+            std::env::temp_dir().join(format!("bar-tomato-conflict-{}", uuid::Uuid::new_v4()));
+        let vault_path = vault.to_string_lossy().to_string();
+        let on_a = |device: &str, id: &str, start_min: u64, end_min: u64| {
+            let mut r = record(device, id, start_min, end_min).record;
+            r.project_path = Some("1. 项目/A/A.README.md".to_string());
+            r
+        };
+        let mut mine = RecordsFile::new();
+        mine.records = vec![on_a("me", "mine", 0, 25), on_a("me", "mine2", 60, 85)];
+        write_records(&get_records_file_path(&vault_path, "me"), &mine).unwrap();
+        let mut theirs = RecordsFile::new();
+        theirs.records = vec![
+            on_a("them", "theirs", 5, 30),
+            on_a("them", "theirs2", 70, 95),
+        ];
+        write_records(&get_records_file_path(&vault_path, "them"), &theirs).unwrap();
+        // The daily note counted all four records in full
+        let note = vault.join("0. 周期笔记/2026/Daily/01/2026-01-05.md");
+        std::fs::create_dir_all(note.parent().unwrap()).unwrap();
+        std::fs::write(
+            &note,
+            "## 项目列表\n1. [[1. 项目/A/A.README.md|A]] 1hr40\n\n1hr40\n",
+        )
+        .unwrap();
+        let note_total = || {
+            let content = std::fs::read_to_string(&note).unwrap();
+            content.lines().last().unwrap().to_string()
+        };
+
+        let mut choice = ConflictChoice {
+            first_id: "mine".to_string(),
+            second_id: "theirs".to_string(),
+            resolution: ConflictResolution::Discard,
+            discard_id: None,
+        };
+        assert!(resolve_conflict(&vault_path, "me", &choice, TimeFormat::default(), 1).is_err());
+
+        choice.resolution = ConflictResolution::Merge;
+        let touched =
+            resolve_conflict(&vault_path, "me", &choice, TimeFormat::default(), 1).unwrap();
+        assert_eq!(touched[0].id, "theirs");
+        assert_eq!(note_total(), "1hr20");
+
+        // Settled once: a second answer changes neither the note nor the records
+        choice.resolution = ConflictResolution::Discard;
+        choice.discard_id = Some("mine".to_string());
+        assert_eq!(
+            resolve_conflict(&vault_path, "me", &choice, TimeFormat::default(), 2).unwrap_err(),
+            "Conflict already resolved"
+        );
+        assert_eq!(note_total(), "1hr20");
+        assert_eq!(
+            read_conflicts(&vault_path)
+                .unwrap()
+                .resolution_for("mine", "theirs"),
+            Some(ConflictResolution::Merge)
+        );
+
+        let choice = ConflictChoice {
+            first_id: "mine2".to_string(),
+            second_id: "theirs2".to_string(),
+            resolution: ConflictResolution::Discard,
+            discard_id: Some("mine2".to_string()),
+        };
+        resolve_conflict(&vault_path, "me", &choice, TimeFormat::default(), 3).unwrap();
+        assert_eq!(note_total(), "0hr55");
+        let kept = read_records(&get_records_file_path(&vault_path, "me")).unwrap();
+        assert_eq!(kept.records.len(), 1);
+        let counted = read_counted_records(&vault_path, "me").unwrap();
+        assert_eq!(
+            durations(&counted),
+            vec![("mine", 25), ("theirs", 5), ("theirs2", 25)]
+        );
+
+        std::fs::remove_dir_all(&vault).ok();
+    }
+
+    #[test]
+    fn test_live_conflicts() {
+        let now = 100 * MIN;
+        let other = LiveSession {
+            device_hash: "other".to_string(),
+            device_name: "Desktop".to_string(),
+            phase: TimerPhase::Running,
+            mode: TimerMode::Pomodoro,
+            started_at: now - 10 * MIN,
+            expected_end: None,
+            task: Some("写论文".to_string()),
+            project: None,
+            project_path: None,
+            pomodoro_count: 0,
+            updated_at: now,
+        };
+
+        let mut own = other.clone();
+        own.device_hash = "me".to_string();
+        let mut on_break = other.clone();
+        on_break.device_hash = "third".to_string();
+        on_break.phase = TimerPhase::ShortBreak;
+        let mut stale = other.clone();
+        stale.device_hash = "gone".to_string();
+        stale.updated_at = 0;
+
+        let others = vec![other.clone(), own.clone(), on_break, stale];
+        assert_eq!(live_conflicts(&own, others.clone(), now), vec![other]);
+        own.phase = TimerPhase::LongBreak;
+        assert!(live_conflicts(&own, others, now).is_empty());
+    }
+
+    #[test]
+    fn test_plugin_timer_overlaps_ours() {
+        let now = 100 * MIN;
+        let json = serde_json::json!({
+            "pomodoroDuration": 25,
+            "pomodoroState": {
+                "isRunning": true,
+                "startTime": now - 10 * MIN,
+                "endTime": now + 15 * MIN,
+                "taskText": "写论文"
+            }
+        });
+        let plugin = plugin_session_from_json(&json, now).unwrap();
+        assert_eq!(plugin.device_hash, PLUGIN_DEVICE_HASH);
+        assert_eq!(plugin.phase, TimerPhase::Running);
+        assert_eq!(plugin.task.as_deref(), Some("写论文"));
+        assert!(plugin_session_from_json(&json, now + PLUGIN_SESSION_MAX_MS).is_none());
+        assert!(plugin_session_from_json(&serde_json::json!({}), now).is_none());
+
+        // Our stopwatch started while the plugin's pomodoro was running
+        let ours = LiveSession {
+            device_hash: "me".to_string(),
+            device_name: "Laptop".to_string(),
+            phase: TimerPhase::Running,
+            mode: TimerMode::Stopwatch,
+            started_at: now - 5 * MIN,
+            expected_end: None,
+            task: None,
+            project: None,
+            project_path: None,
+            pomodoro_count: 0,
+            updated_at: now,
+        };
+        assert_eq!(
+            live_conflicts(&ours, vec![plugin.clone()], now),
+            vec![plugin.clone()]
+        );
+
+        // Read from the plugin's data.json like the app does
+        let vault =
+            std::env::temp_dir().join(format!("bar-tomato-plugin-{}", uuid::Uuid::new_v4()));
+        let data = vault.join(".obsidian/plugins/lifeos-pro");
+        std::fs::create_dir_all(&data).unwrap();
+        std::fs::write(data.join("data.json"), json.to_string()).unwrap();
+        let vault_path = vault.to_string_lossy().to_string();
+        assert_eq!(read_plugin_session(&vault_path, now), Some(plugin));
+
+        std::fs::remove_dir_all(&vault).ok();
+    }
+}
@@ -1,16 +1,23 @@
+use super::TimeFormat;
 use regex::Regex;
 use std::path::Path;
+use std::sync::OnceLock;
+use walkdir::WalkDir;
 
 /// Format minutes as `Xhr##` format (e.g., "0hr25", "1hr58", "3hr10")
 pub fn format_time(total_minutes: u32) -> String {
-    let hours = total_minutes / 60;
-    let mins = total_minutes % 60;
-    format!("{}hr{:02}", hours, mins)
+    TimeFormat::Hr.format(total_minutes)
 }
 
-/// Parse time string `Xhr##` back to total minutes
+/// Parse a time value written in any supported format back to total minutes.
+/// `Xhr##` values are still found inside longer text, as before formats
+/// were configurable.
 pub fn parse_time(s: &str) -> Option<u32> {
-    let re = Regex::new(r"(\d+)hr(\d+)").ok()?;
+    static LEGACY_RE: OnceLock<Regex> = OnceLock::new();
+    if let Some((_, minutes)) = TimeFormat::detect(s) {
+        return Some(minutes);
+    }
+    let re = LEGACY_RE.get_or_init(|| Regex::new(r"(\d+)hr(\d+)").unwrap());
     let caps = re.captures(s)?;
     let hours: u32 = caps.get(1)?.as_str().parse().ok()?;
     let mins: u32 = caps.get(2)?.as_str().parse().ok()?;
@@ -23,6 +30,13 @@ pub fn time_add(existing: &str, added_minutes: u32) -> String {
     format_time(existing_mins + added_minutes)
 }
 
+/// Apply a signed minute delta to a time value, clamping at zero, and write
+/// the result in `format`
+pub fn time_adjust(existing: &str, delta_minutes: i32, format: TimeFormat) -> String {
+    let existing_mins = parse_time(existing).unwrap_or(0) as i64;
+    format.format((existing_mins + delta_minutes as i64).max(0) as u32)
+}
+
 /// Get daily note path for a given date
 pub fn daily_note_path(vault_path: &str, date: &str) -> String {
     // date format: YYYY-MM-DD
@@ -51,6 +65,27 @@ pub fn update_project_time(
     project_path: &str,
     display_name: &str,
     added_minutes: u32,
+    format: TimeFormat,
+) -> Result<bool, String> {
+    adjust_project_time(
+        vault_path,
+        date,
+        project_path,
+        display_name,
+        added_minutes as i32,
+        format,
+    )
+}
+
+/// Like `update_project_time`, but the delta may be negative (used when a
+/// record is edited). Project times and the total never drop below zero.
+pub fn adjust_project_time(
+    vault_path: &str,
+    date: &str,
+    project_path: &str,
+    display_name: &str,
+    delta_minutes: i32,
+    format: TimeFormat,
 ) -> Result<bool, String> {
     let note_path = daily_note_path(vault_path, date);
     let path = Path::new(&note_path);
@@ -59,20 +94,36 @@ pub fn update_project_time(
         return Ok(false);
     }
 
+    rewrite_note(path, |content| {
+        update_project_section(content, project_path, display_name, delta_minutes, format)
+    })?;
+    Ok(true)
+}
+
+/// Read a note, transform it and write the result back. If the note's mtime
+/// changes while transforming (e.g. Obsidian saved it), the read is retried
+/// so the other write is not lost. Returns Ok(false) when nothing changed.
+pub fn rewrite_note<F>(path: &Path, transform: F) -> Result<bool, String>
+where
+    F: Fn(&str) -> Result<String, String>,
+{
     // Retry logic with mtime check
     for attempt in 0..3 {
-        let mtime_before = std::fs::metadata(&note_path)
+        let mtime_before = std::fs::metadata(path)
             .map_err(|e| format!("Failed to read metadata: {}", e))?
             .modified()
             .map_err(|e| format!("Failed to read mtime: {}", e))?;
 
-        let content = std::fs::read_to_string(&note_path)
-            .map_err(|e| format!("Failed to read daily note: {}", e))?;
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read note: {}", e))?;
 
-        let updated = update_project_section(&content, project_path, display_name, added_minutes)?;
+        let updated = transform(&content)?;
+        if updated == content {
+            return Ok(false);
+        }
 
         // Check mtime hasn't changed
-        let mtime_after = std::fs::metadata(&note_path)
+        let mtime_after = std::fs::metadata(path)
             .map_err(|e| format!("Failed to read metadata: {}", e))?
             .modified()
             .map_err(|e| format!("Failed to read mtime: {}", e))?;
@@ -81,20 +132,20 @@ pub fn update_project_time(
             continue; // File changed, retry
         }
 
-        std::fs::write(&note_path, updated)
-            .map_err(|e| format!("Failed to write daily note: {}", e))?;
+        std::fs::write(path, updated).map_err(|e| format!("Failed to write note: {}", e))?;
 
         return Ok(true);
     }
 
-    Err("Failed to update daily note after 3 attempts".to_string())
+    Err("Failed to update note after 3 attempts".to_string())
 }
 
 fn update_project_section(
     content: &str,
     project_path: &str,
     display_name: &str,
-    added_minutes: u32,
+    delta_minutes: i32,
+    format: TimeFormat,
 ) -> Result<String, String> {
     let lines: Vec<&str> = content.lines().collect();
 
@@ -136,9 +187,10 @@ fn update_project_section(
     ))
     .map_err(|e| format!("Regex error: {}", e))?;
 
-    // Regex for existing time entry: `N. [[projectPath|displayName]] XhrYY`
+    // Regex for existing time entry: `N. [[projectPath|displayName]] <time>`,
+    // with the time in any supported format
     let time_entry_re = Regex::new(&format!(
-        r"^(\d+)\.\s+\[\[{}[|]{}\]\]\s+(\d+hr\d+)",
+        r"^(\d+)\.\s+\[\[{}[|]{}\]\]\s+(\S+)",
         regex::escape(project_path),
         regex::escape(display_name)
     ))
@@ -163,19 +215,23 @@ fn update_project_section(
         }
 
         // Check if this is an existing time entry for this project
-        if let Some(caps) = time_entry_re.captures(line) {
+        if let Some(caps) = time_entry_re
+            .captures(line)
+            .filter(|c| parse_time(c.get(2).unwrap().as_str()).is_some())
+        {
             // Found existing time entry, update time
             let old_time = caps.get(2).unwrap().as_str();
-            let new_time = time_add(old_time, added_minutes);
+            let new_time = time_adjust(old_time, delta_minutes, format);
             let updated_line = line.replace(old_time, &new_time);
             new_section_lines.push(updated_line);
             found_existing = true;
         }
         // Check if this is an existing short format entry for this project
-        else if let Some(caps) = short_entry_re.captures(line) {
+        else if delta_minutes > 0 && short_entry_re.is_match(line) {
             // Found existing short entry, add time to it
+            let caps = short_entry_re.captures(line).unwrap();
             let num = caps.get(1).unwrap().as_str();
-            let time_str = format_time(added_minutes);
+            let time_str = format.format(delta_minutes as u32);
             let updated_line = format!(
                 "{}. [[{}|{}]] {}",
                 num, project_path, display_name, time_str
@@ -184,19 +240,18 @@ fn update_project_section(
             found_existing = true;
         } else {
             // Check if this is the total time line (standalone time like "1hr58")
-            let trimmed = line.trim();
-            if !trimmed.is_empty()
-                && parse_time(trimmed).is_some()
-                && !trimmed.starts_with('[')
-                && !trimmed.starts_with('#')
-                && !trimmed.contains("[[")
-            {
+            if is_total_line(line) {
                 total_line_idx = Some(i);
             }
             new_section_lines.push(line.to_string());
         }
     }
 
+    // Nothing to subtract from a project that has no entry yet
+    if !found_existing && delta_minutes <= 0 {
+        return Ok(content.to_string());
+    }
+
     if !found_existing {
         // Add new project time entry before the total line
         let new_entry = format!(
@@ -204,7 +259,7 @@ fn update_project_section(
             max_num + 1,
             project_path,
             display_name,
-            format_time(added_minutes)
+            format.format(delta_minutes as u32)
         );
 
         if let Some(total_idx) = total_line_idx {
@@ -224,25 +279,21 @@ fn update_project_section(
     }
 
     // Calculate new total from all project time entries
-    let time_entry_re_all = Regex::new(r"\]\]\s+(\d+hr\d+)").unwrap();
-    let mut total_mins: u32 = 0;
-    for line in &new_section_lines {
-        if let Some(caps) = time_entry_re_all.captures(line) {
-            if let Some(mins) = parse_time(caps.get(1).unwrap().as_str()) {
-                total_mins += mins;
-            }
-        }
-    }
+    let total_mins: u32 = new_section_lines
+        .iter()
+        .filter_map(|line| entry_time(line))
+        .map(|(_, mins)| mins)
+        .sum();
 
     // Update or add total line
     if let Some(total_idx) = total_line_idx {
         if total_idx < new_section_lines.len() {
-            new_section_lines[total_idx] = format_time(total_mins);
+            new_section_lines[total_idx] = format.format(total_mins);
         }
     } else {
         // Add empty line then total
         new_section_lines.push(String::new());
-        new_section_lines.push(format_time(total_mins));
+        new_section_lines.push(format.format(total_mins));
     }
 
     // Rebuild full content
@@ -260,6 +311,112 @@ fn update_project_section(
     Ok(result_lines.join("\n"))
 }
 
+/// The time token after a project link (`...]] 1hr58`) and its minutes
+fn entry_time(line: &str) -> Option<(&str, u32)> {
+    static ENTRY_TIME_RE: OnceLock<Regex> = OnceLock::new();
+    let re = ENTRY_TIME_RE.get_or_init(|| Regex::new(r"\]\]\s+(\S+)").unwrap());
+    let token = re.captures(line)?.get(1)?.as_str();
+    parse_time(token).map(|mins| (token, mins))
+}
+
+/// Whether a project-list line is the standalone total, written exactly as
+/// this app writes it in one of the time formats (e.g. `1hr58`, `118min`)
+fn is_total_line(line: &str) -> bool {
+    let token = line.trim_end();
+    TimeFormat::detect(token).is_some_and(|(format, mins)| format.format(mins) == token)
+}
+
+/// Rewrite every time in the "项目列表" section into `format`. Returns the
+/// content unchanged when the note has no such section or is already
+/// written in `format`.
+pub fn convert_project_section(content: &str, format: TimeFormat) -> String {
+    let mut in_section = false;
+    let mut changed = false;
+    let lines: Vec<String> = content
+        .split('\n')
+        .map(|line| {
+            if line.starts_with("## ") {
+                in_section = line.trim() == "## 项目列表";
+                return line.to_string();
+            }
+            if !in_section {
+                return line.to_string();
+            }
+            let converted = if let Some((token, mins)) = entry_time(line) {
+                // Replace the token right after the link, not an earlier match
+                let at = line.find("]]").unwrap();
+                let (head, tail) = line.split_at(at);
+                format!("{}{}", head, tail.replacen(token, &format.format(mins), 1))
+            } else if is_total_line(line) {
+                let trimmed = line.trim_end();
+                let suffix = &line[trimmed.len()..];
+                format!("{}{}", format.format(parse_time(trimmed).unwrap()), suffix)
+            } else {
+                return line.to_string();
+            };
+            changed |= converted != line;
+            converted
+        })
+        .collect();
+
+    if changed {
+        lines.join("\n")
+    } else {
+        content.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeFormatMigration {
+    pub notes_scanned: usize,
+    /// Notes converted, or for a preview the notes that would be
+    pub notes_changed: usize,
+    /// False for a preview
+    pub applied: bool,
+}
+
+/// Convert the project-list times of every daily note in the vault to
+/// `format`. With `apply` false nothing is written and the summary only
+/// counts the notes that would change.
+pub fn migrate_time_format(
+    vault_path: &str,
+    format: TimeFormat,
+    apply: bool,
+) -> Result<TimeFormatMigration, String> {
+    let root = Path::new(vault_path).join("0. 周期笔记");
+    let mut summary = TimeFormatMigration {
+        applied: apply,
+        ..Default::default()
+    };
+
+    for entry in WalkDir::new(&root).into_iter().flatten() {
+        let path = entry.path();
+        let is_daily = path.components().any(|c| c.as_os_str() == "Daily");
+        if !is_daily || path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+
+        summary.notes_scanned += 1;
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                log::warn!("Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if convert_project_section(&content, format) == content {
+            continue;
+        }
+        let changed = !apply || rewrite_note(path, |c| Ok(convert_project_section(c, format)))?;
+        if changed {
+            summary.notes_changed += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
 /// Check the "使用番茄钟" habit checkbox in the daily note
 pub fn check_pomodoro_habit(vault_path: &str, date: &str) -> Result<bool, String> {
     let note_path = daily_note_path(vault_path, date);
@@ -317,4 +474,100 @@ mod tests {
         assert_eq!(time_add("0hr50", 25), "1hr15");
         assert_eq!(time_add("1hr58", 13), "2hr11");
     }
+
+    #[test]
+    fn test_time_adjust() {
+        assert_eq!(time_adjust("1hr15", -25, TimeFormat::Hr), "0hr50");
+        assert_eq!(time_adjust("0hr10", -25, TimeFormat::Hr), "0hr00");
+        assert_eq!(time_adjust("0hr50", 10, TimeFormat::Hr), "1hr00");
+        assert_eq!(time_adjust("0:50", 10, TimeFormat::HoursMinutes), "1h");
+    }
+
+    const NOTE: &str = "# 2026-01-05\n\n## 项目列表\n1. [[1. 项目/A/A.README.md|A]] 0hr50\n2. [[1. 项目/B/B.README.md|B]] 0hr25\n\n1hr15\n\n## 习惯\n";
+
+    #[test]
+    fn test_adjust_existing_entry_updates_total() {
+        let updated =
+            update_project_section(NOTE, "1. 项目/A/A.README.md", "A", -25, TimeFormat::Hr)
+                .unwrap();
+        assert!(updated.contains("1. [[1. 项目/A/A.README.md|A]] 0hr25"));
+        assert!(updated.contains("\n0hr50\n"));
+    }
+
+    #[test]
+    fn test_negative_delta_without_entry_is_noop() {
+        let updated =
+            update_project_section(NOTE, "1. 项目/C/C.README.md", "C", -25, TimeFormat::Hr)
+                .unwrap();
+        assert_eq!(updated, NOTE);
+    }
+
+    #[test]
+    fn test_mixed_formats_add_up() {
+        let note = "## 项目列表\n1. [[1. 项目/A/A.README.md|A]] 1h30m\n2. [[1. 项目/B/B.README.md|B]] 0hr25\n\n1hr55\n";
+        let updated =
+            update_project_section(note, "1. 项目/B/B.README.md", "B", 5, TimeFormat::Colon)
+                .unwrap();
+        assert!(updated.contains("|B]] 0:30"));
+        assert!(updated.ends_with("\n2:00"));
+    }
+
+    #[test]
+    fn test_convert_project_section() {
+        let converted = convert_project_section(NOTE, TimeFormat::Minutes);
+        assert_eq!(
+            converted,
+            "# 2026-01-05\n\n## 项目列表\n1. [[1. 项目/A/A.README.md|A]] 50min\n2. [[1. 项目/B/B.README.md|B]] 25min\n\n75min\n\n## 习惯\n"
+        );
+        assert_eq!(
+            convert_project_section(&converted, TimeFormat::Minutes),
+            converted
+        );
+        assert_eq!(convert_project_section(&converted, TimeFormat::Hr), NOTE);
+    }
+
+    #[test]
+    fn test_total_line_must_match_written_format() {
+        assert!(is_total_line("1hr58"));
+        assert!(is_total_line("118min\r"));
+        assert!(is_total_line("1.97h"));
+        // Loosely time-like text the user wrote is not the total
+        assert!(!is_total_line("  1hr58"));
+        assert!(!is_total_line("1hr5"));
+        assert!(!is_total_line("0.5h"));
+        assert!(!is_total_line("90 min"));
+        assert!(!is_total_line("- 1:58"));
+
+        let note = "## 项目列表\n1. [[1. 项目/A/A.README.md|A]] 0hr50\n备注 0.5h\n\n0hr50\n";
+        assert_eq!(
+            convert_project_section(note, TimeFormat::Minutes),
+            "## 项目列表\n1. [[1. 项目/A/A.README.md|A]] 50min\n备注 0.5h\n\n50min\n"
+        );
+    }
+
+    #[test]
+    fn test_migrate_time_format_previews_first() {
+        let vault =
+            std::env::temp_dir().join(format!("bar-tomato-migrate-{}", uuid::Uuid::new_v4()));
+        let vault_path = vault.to_string_lossy().to_string();
+        let daily = vault.join("0. 周期笔记/2026/Daily");
+        std::fs::create_dir_all(&daily).unwrap();
+        std::fs::write(daily.join("2026-01-05.md"), NOTE).unwrap();
+
+        let preview = migrate_time_format(&vault_path, TimeFormat::Minutes, false).unwrap();
+        assert_eq!((preview.notes_scanned, preview.notes_changed), (1, 1));
+        assert!(!preview.applied);
+        assert_eq!(
+            std::fs::read_to_string(daily.join("2026-01-05.md")).unwrap(),
+            NOTE
+        );
+
+        let applied = migrate_time_format(&vault_path, TimeFormat::Minutes, true).unwrap();
+        assert_eq!(applied.notes_changed, 1);
+        assert!(std::fs::read_to_string(daily.join("2026-01-05.md"))
+            .unwrap()
+            .contains("\n75min\n"));
+
+        std::fs::remove_dir_all(&vault).ok();
+    }
 }
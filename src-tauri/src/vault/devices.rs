@@ -0,0 +1,83 @@
+use super::get_storage_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Human-readable names for device hashes, shared in the storage folder so
+/// every device can label the others' records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevicesFile {
+    pub version: u32,
+    pub devices: BTreeMap<String, DeviceInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInfo {
+    pub name: String,
+    pub last_seen: u64,
+}
+
+impl DevicesFile {
+    pub fn new() -> Self {
+        Self {
+            version: 1,
+            devices: BTreeMap::new(),
+        }
+    }
+
+    /// Name for a device hash, falling back to a short hash prefix
+    pub fn name_for(&self, device_hash: &str) -> String {
+        self.devices
+            .get(device_hash)
+            .map(|d| d.name.clone())
+            .unwrap_or_else(|| device_hash.chars().take(8).collect())
+    }
+}
+
+fn get_devices_file_path(vault_path: &str) -> String {
+    get_storage_dir(vault_path)
+        .join("pomodoro-devices.json")
+        .to_string_lossy()
+        .to_string()
+}
+
+pub fn read_devices(vault_path: &str) -> Result<DevicesFile, String> {
+    let path = get_devices_file_path(vault_path);
+    if !std::path::Path::new(&path).exists() {
+        return Ok(DevicesFile::new());
+    }
+
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read devices: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse devices: {}", e))
+}
+
+/// Store (or refresh) this device's name next to its hash
+pub fn register_device(vault_path: &str, device_hash: &str, name: &str) -> Result<(), String> {
+    let mut devices = read_devices(vault_path).unwrap_or_else(|_| DevicesFile::new());
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    devices.devices.insert(
+        device_hash.to_string(),
+        DeviceInfo {
+            name: name.to_string(),
+            last_seen: now_ms,
+        },
+    );
+
+    let path = get_devices_file_path(vault_path);
+    std::fs::create_dir_all(get_storage_dir(vault_path))
+        .map_err(|e| format!("Failed to create storage dir: {}", e))?;
+    let content = serde_json::to_string_pretty(&devices)
+        .map_err(|e| format!("Failed to serialize devices: {}", e))?;
+
+    // Write atomically: write to temp file, then rename
+    let tmp_path = format!("{}.tmp", path);
+    std::fs::write(&tmp_path, content).map_err(|e| format!("Failed to write temp file: {}", e))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to rename temp file: {}", e))?;
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+/// Split a note into its YAML frontmatter (without the `---` fences) and the
+/// rest of the file. Returns `None` when the note has no frontmatter.
+pub fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return Some((yaml, body));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Read a top-level scalar frontmatter value, with surrounding quotes removed
+pub fn get_frontmatter_value(content: &str, key: &str) -> Option<String> {
+    let (yaml, _) = split_frontmatter(content)?;
+    yaml.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        if k != key {
+            return None;
+        }
+        let v = v.trim();
+        let v = v
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .or_else(|| v.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+            .unwrap_or(v);
+        if v.is_empty() {
+            None
+        } else {
+            Some(v.to_string())
+        }
+    })
+}
+
+/// A value written by `set_frontmatter_values`
+#[derive(Debug, Clone, PartialEq)]
+pub enum YamlValue {
+    Number(u64),
+    Text(String),
+    List(Vec<String>),
+}
+
+/// Quote a YAML scalar when writing it bare would change its meaning
+fn yaml_scalar(text: &str) -> String {
+    let plain_type = matches!(
+        text.to_lowercase().as_str(),
+        "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+    ) || text.parse::<f64>().is_ok();
+    let needs_quotes = text.is_empty()
+        || plain_type
+        || text.starts_with(|c: char| "[]{}&*!|>'\"%@`#,?:-".contains(c) || c.is_whitespace())
+        || text.ends_with(char::is_whitespace)
+        || text.contains(": ")
+        || text.contains(" #")
+        || text.contains('\n');
+    if needs_quotes {
+        format!(
+            "\"{}\"",
+            text.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        )
+    } else {
+        text.to_string()
+    }
+}
+
+fn render_entry(key: &str, value: &YamlValue, list_indent: &str) -> Vec<String> {
+    match value {
+        YamlValue::Number(n) => vec![format!("{}: {}", key, n)],
+        YamlValue::Text(t) => vec![format!("{}: {}", key, yaml_scalar(t))],
+        YamlValue::List(items) if items.is_empty() => vec![format!("{}: []", key)],
+        YamlValue::List(items) => std::iter::once(format!("{}:", key))
+            .chain(
+                items
+                    .iter()
+                    .map(|i| format!("{}- {}", list_indent, yaml_scalar(i))),
+            )
+            .collect(),
+    }
+}
+
+/// Whether a line continues the value of the top-level key above it
+fn is_continuation(line: &str) -> bool {
+    line.starts_with([' ', '\t']) && !line.trim().is_empty()
+        || line.starts_with("- ")
+        || line == "-"
+}
+
+/// Set top-level frontmatter keys, leaving every other line untouched.
+/// Existing keys are rewritten in place (including their nested lines);
+/// new keys are appended. A frontmatter block is added if the note has none.
+pub fn set_frontmatter_values(content: &str, values: &[(&str, YamlValue)]) -> String {
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let (yaml, body) = match split_frontmatter(content) {
+        Some(parts) => parts,
+        None => {
+            let lines: Vec<String> = values
+                .iter()
+                .flat_map(|(k, v)| render_entry(k, v, "  "))
+                .collect();
+            return format!(
+                "---{nl}{}{nl}---{nl}{}",
+                lines.join(newline),
+                content,
+                nl = newline
+            );
+        }
+    };
+
+    let mut lines: Vec<String> = yaml.lines().map(String::from).collect();
+    for (key, value) in values {
+        let prefix = format!("{}:", key);
+        let start = lines.iter().position(|l| {
+            l.strip_prefix(&prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+        });
+        match start {
+            Some(start) => {
+                let end = start
+                    + 1
+                    + lines[start + 1..]
+                        .iter()
+                        .take_while(|l| is_continuation(l))
+                        .count();
+                // Keep the list indentation the note already uses
+                let indent = lines[start + 1..end]
+                    .iter()
+                    .find_map(|l| {
+                        let trimmed = l.trim_start();
+                        trimmed
+                            .starts_with('-')
+                            .then(|| l[..l.len() - trimmed.len()].to_string())
+                    })
+                    .unwrap_or_else(|| "  ".to_string());
+                lines.splice(start..end, render_entry(key, value, &indent));
+            }
+            None => lines.extend(render_entry(key, value, "  ")),
+        }
+    }
+
+    format!(
+        "---{nl}{}{nl}---{nl}{}",
+        lines.join(newline),
+        body,
+        nl = newline
+    )
+}
+
+/// Apply `set_frontmatter_values` to a note on disk. Returns Ok(false) when
+/// the note does not exist or nothing changed, so the file is left alone.
+pub fn update_note_frontmatter(
+    note_path: &std::path::Path,
+    values: &[(&str, YamlValue)],
+) -> Result<bool, String> {
+    if !note_path.exists() {
+        return Ok(false);
+    }
+    super::rewrite_note(note_path, |content| {
+        Ok(set_frontmatter_values(content, values))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_frontmatter_values_preserves_other_keys() {
+        let note = "---\ntags: [daily]\nfocus_projects:\n- \"[[Old]]\"\n- \"[[Older]]\"\ncreated: 2026-01-05 # keep\n---\n# 2026-01-05\n";
+        let out = set_frontmatter_values(
+            note,
+            &[
+                ("pomodoros", YamlValue::Number(3)),
+                (
+                    "focus_projects",
+                    YamlValue::List(vec!["[[1. 项目/科研-A/A.README|科研-A]]".to_string()]),
+                ),
+            ],
+        );
+        assert_eq!(
+            out,
+            "---\ntags: [daily]\nfocus_projects:\n- \"[[1. 项目/科研-A/A.README|科研-A]]\"\ncreated: 2026-01-05 # keep\npomodoros: 3\n---\n# 2026-01-05\n"
+        );
+        // Writing the same values again changes nothing
+        assert_eq!(
+            set_frontmatter_values(&out, &[("pomodoros", YamlValue::Number(3))]),
+            out
+        );
+    }
+
+    #[test]
+    fn test_set_frontmatter_values_adds_block_and_quotes() {
+        let out = set_frontmatter_values(
+            "# Note\r\n",
+            &[
+                ("last_focused", YamlValue::Text("2026-01-05".to_string())),
+                ("label", YamlValue::Text("true".to_string())),
+                ("focus_projects", YamlValue::List(vec![])),
+            ],
+        );
+        assert_eq!(
+            out,
+            "---\r\nlast_focused: 2026-01-05\r\nlabel: \"true\"\r\nfocus_projects: []\r\n---\r\n# Note\r\n"
+        );
+        assert_eq!(yaml_scalar("a: b"), "\"a: b\"");
+        assert_eq!(yaml_scalar("say \"hi\""), "say \"hi\"");
+        assert_eq!(yaml_scalar("# x"), "\"# x\"");
+    }
+
+    #[test]
+    fn test_get_frontmatter_value() {
+        let note = "---\ntags: [daily]\nfocus_goal: \"3h\"\n---\n# 2026-01-10\n";
+        assert_eq!(
+            get_frontmatter_value(note, "focus_goal").as_deref(),
+            Some("3h")
+        );
+        assert_eq!(get_frontmatter_value(note, "missing"), None);
+        assert_eq!(
+            get_frontmatter_value("# no frontmatter", "focus_goal"),
+            None
+        );
+    }
+}
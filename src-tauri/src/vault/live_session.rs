@@ -0,0 +1,196 @@
+use super::get_storage_dir;
+use crate::timer::{TimerMode, TimerPhase, TimerStatus};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// How often a running session's file is rewritten to show the app is alive
+pub const LIVE_SESSION_HEARTBEAT_MS: u64 = 60_000;
+/// Sessions not rewritten for this long belong to an app that has died
+pub const LIVE_SESSION_STALE_MS: u64 = 3 * LIVE_SESSION_HEARTBEAT_MS;
+
+/// What a device is timing right now, kept in the storage folder as
+/// `current-session.{hash}.json` for the other devices to read
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveSession {
+    pub device_hash: String,
+    pub device_name: String,
+    pub phase: TimerPhase,
+    pub mode: TimerMode,
+    /// Unix milliseconds when the pomodoro, stopwatch or break started
+    pub started_at: u64,
+    /// When the pomodoro or break is due to end; None for stopwatches and
+    /// while paused
+    pub expected_end: Option<u64>,
+    pub task: Option<String>,
+    pub project: Option<String>,
+    pub project_path: Option<String>,
+    pub pomodoro_count: u32,
+    /// Unix milliseconds of the last write
+    pub updated_at: u64,
+}
+
+impl LiveSession {
+    /// The session for `status`, or None when the timer is idle. `previous`
+    /// carries the start time over pauses unless a new session `started`.
+    pub fn from_status(
+        status: &TimerStatus,
+        previous: Option<&LiveSession>,
+        started: bool,
+        device_hash: &str,
+        device_name: &str,
+        now_ms: u64,
+    ) -> Option<Self> {
+        if status.phase == TimerPhase::Idle {
+            return None;
+        }
+        let previous = previous.filter(|_| !started);
+        let started_at = previous
+            .map(|p| p.started_at)
+            .unwrap_or_else(|| now_ms.saturating_sub(status.elapsed_secs * 1000));
+        let expected_end = match (status.phase, status.remaining_secs) {
+            (TimerPhase::Paused, _) => None,
+            (_, Some(remaining)) if status.overtime_secs == 0 => Some(now_ms + remaining * 1000),
+            // In overtime the original end stands
+            _ => previous.and_then(|p| p.expected_end),
+        };
+
+        Some(Self {
+            device_hash: device_hash.to_string(),
+            device_name: device_name.to_string(),
+            phase: status.phase,
+            mode: status.mode,
+            started_at,
+            expected_end,
+            task: status.current_task.clone(),
+            project: status.current_project.clone(),
+            project_path: status.current_project_path.clone(),
+            pomodoro_count: status.pomodoro_count,
+            updated_at: now_ms,
+        })
+    }
+
+    pub fn is_stale(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.updated_at) > LIVE_SESSION_STALE_MS
+    }
+
+    pub fn needs_heartbeat(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.updated_at) >= LIVE_SESSION_HEARTBEAT_MS
+    }
+
+    /// Whether this is focus time rather than a break
+    pub fn is_focus(&self) -> bool {
+        matches!(self.phase, TimerPhase::Running | TimerPhase::Paused)
+    }
+}
+
+fn get_live_session_path(vault_path: &str, device_hash: &str) -> PathBuf {
+    get_storage_dir(vault_path).join(format!("current-session.{}.json", device_hash))
+}
+
+pub fn write_live_session(vault_path: &str, session: &LiveSession) -> Result<(), String> {
+    std::fs::create_dir_all(get_storage_dir(vault_path))
+        .map_err(|e| format!("Failed to create storage dir: {}", e))?;
+    let path = get_live_session_path(vault_path, &session.device_hash);
+    let content = serde_json::to_string_pretty(session)
+        .map_err(|e| format!("Failed to serialize live session: {}", e))?;
+
+    // Write atomically so other devices never read half a session
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, content).map_err(|e| format!("Failed to write temp file: {}", e))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to rename temp file: {}", e))
+}
+
+/// Remove this device's session file; a missing file is fine
+pub fn clear_live_session(vault_path: &str, device_hash: &str) -> Result<(), String> {
+    match std::fs::remove_file(get_live_session_path(vault_path, device_hash)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove live session: {}", e)),
+    }
+}
+
+/// Every device's session file, stale ones included
+pub fn read_live_sessions(vault_path: &str) -> Vec<LiveSession> {
+    let Ok(entries) = std::fs::read_dir(get_storage_dir(vault_path)) else {
+        return Vec::new();
+    };
+    let mut sessions: Vec<LiveSession> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with("current-session.") && name.ends_with(".json")
+        })
+        .filter_map(|entry| {
+            let content = std::fs::read_to_string(entry.path()).ok()?;
+            serde_json::from_str(&content).ok()
+        })
+        .collect();
+    sessions.sort_by(|a, b| a.device_hash.cmp(&b.device_hash));
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+    use std::time::Duration;
+
+    #[test]
+    fn test_session_follows_the_timer() {
+        let mut timer = TimerState::new();
+        let now = 10_000_000;
+        assert!(
+            LiveSession::from_status(&timer.status(), None, false, "h", "Laptop", now).is_none()
+        );
+
+        timer.current_task = Some("写论文".to_string());
+        timer.start_pomodoro();
+        let started =
+            LiveSession::from_status(&timer.status(), None, true, "h", "Laptop", now).unwrap();
+        assert_eq!(started.started_at, now);
+        assert_eq!(started.expected_end, Some(now + 25 * 60 * 1000));
+        assert_eq!(started.task.as_deref(), Some("写论文"));
+
+        // Paused ten minutes in: no end time, start time kept
+        timer.pause_elapsed = Duration::from_secs(600);
+        timer.pause();
+        let later = now + 15 * 60 * 1000;
+        let paused =
+            LiveSession::from_status(&timer.status(), Some(&started), false, "h", "Laptop", later)
+                .unwrap();
+        assert_eq!(paused.phase, TimerPhase::Paused);
+        assert_eq!(paused.started_at, now);
+        assert_eq!(paused.expected_end, None);
+
+        timer.start_break();
+        let on_break =
+            LiveSession::from_status(&timer.status(), Some(&paused), true, "h", "Laptop", later)
+                .unwrap();
+        assert_eq!(on_break.started_at, later);
+        assert!(!on_break.is_focus());
+
+        assert!(!on_break.is_stale(later + LIVE_SESSION_STALE_MS));
+        assert!(on_break.is_stale(later + LIVE_SESSION_STALE_MS + 1));
+        assert!(on_break.needs_heartbeat(later + LIVE_SESSION_HEARTBEAT_MS));
+    }
+
+    #[test]
+    fn test_write_read_clear() {
+        let vault = std::env::temp_dir().join(format!("bar-tomato-live-{}", uuid::Uuid::new_v4()));
+        let vault_path = vault.to_string_lossy().to_string();
+        let mut timer = TimerState::new();
+        timer.start_stopwatch();
+        let session =
+            LiveSession::from_status(&timer.status(), None, true, "abc", "Laptop", 5_000).unwrap();
+
+        write_live_session(&vault_path, &session).unwrap();
+        assert_eq!(read_live_sessions(&vault_path), vec![session]);
+
+        clear_live_session(&vault_path, "abc").unwrap();
+        clear_live_session(&vault_path, "abc").unwrap();
+        assert!(read_live_sessions(&vault_path).is_empty());
+
+        std::fs::remove_dir_all(&vault).unwrap();
+    }
+}
@@ -1,11 +1,27 @@
 pub mod config;
+pub mod conflicts;
 pub mod daily_note;
+pub mod devices;
+pub mod frontmatter;
+pub mod live_session;
+pub mod note_stats;
 pub mod pomodoro_records;
 pub mod projects;
+pub mod review_note;
+pub mod session;
 pub mod tasks;
+pub mod time_format;
 
 pub use config::*;
+pub use conflicts::*;
 pub use daily_note::*;
+pub use devices::*;
+pub use frontmatter::*;
+pub use live_session::*;
+pub use note_stats::*;
 pub use pomodoro_records::*;
 pub use projects::*;
+pub use review_note::*;
+pub use session::*;
 pub use tasks::*;
+pub use time_format::*;
@@ -0,0 +1,197 @@
+use super::{
+    daily_note_path, existing_path_in_vault, update_note_frontmatter, PomodoroRecord, YamlValue,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Focus totals written into a daily note's frontmatter
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyNoteStats {
+    pub pomodoros: u64,
+    pub focus_minutes: u64,
+    /// Project links, most focused first
+    pub focus_projects: Vec<String>,
+}
+
+/// Obsidian link to a project README, e.g. `[[1. 项目/科研-A/A.README|科研-A]]`
+pub fn project_link(project_path: &str, display_name: &str) -> String {
+    format!(
+        "[[{}|{}]]",
+        project_path.trim_end_matches(".md"),
+        display_name
+    )
+}
+
+/// Totals for one day across all records. `project_names` maps README paths
+/// to display names; unknown projects fall back to the folder name.
+pub fn daily_note_stats(
+    records: &[PomodoroRecord],
+    date: &str,
+    project_names: &HashMap<String, String>,
+) -> DailyNoteStats {
+    let day: Vec<&PomodoroRecord> = records.iter().filter(|r| r.date == date).collect();
+
+    let mut per_project: HashMap<&str, u32> = HashMap::new();
+    for r in &day {
+        if let Some(ref pp) = r.project_path {
+            *per_project.entry(pp.as_str()).or_insert(0) += r.duration;
+        }
+    }
+    let mut projects: Vec<(&str, u32)> = per_project.into_iter().collect();
+    projects.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    DailyNoteStats {
+        pomodoros: day
+            .iter()
+            .filter(|r| r.mode == "pomodoro" && r.status == "completed")
+            .count() as u64,
+        focus_minutes: day.iter().map(|r| r.duration as u64).sum(),
+        focus_projects: projects
+            .into_iter()
+            .map(|(pp, _)| {
+                let name = project_names
+                    .get(pp)
+                    .cloned()
+                    .unwrap_or_else(|| super::project_folder(pp).to_string());
+                project_link(pp, &name)
+            })
+            .collect(),
+    }
+}
+
+/// Write `pomodoros`, `focus_minutes` and `focus_projects` into the daily
+/// note's frontmatter. Returns Ok(false) if the daily note doesn't exist.
+pub fn sync_daily_frontmatter(
+    vault_path: &str,
+    date: &str,
+    records: &[PomodoroRecord],
+    project_names: &HashMap<String, String>,
+) -> Result<bool, String> {
+    let note_path = daily_note_path(vault_path, date);
+    if note_path.is_empty() {
+        return Ok(false);
+    }
+    let stats = daily_note_stats(records, date, project_names);
+    update_note_frontmatter(
+        Path::new(&note_path),
+        &[
+            ("pomodoros", YamlValue::Number(stats.pomodoros)),
+            ("focus_minutes", YamlValue::Number(stats.focus_minutes)),
+            ("focus_projects", YamlValue::List(stats.focus_projects)),
+        ],
+    )
+}
+
+/// Write `total_focus_minutes` and `last_focused` into a project README.
+/// READMEs that don't exist or resolve outside the vault are skipped.
+pub fn sync_readme_frontmatter(
+    vault_path: &str,
+    project_path: &str,
+    records: &[PomodoroRecord],
+) -> Result<bool, String> {
+    if !project_path.ends_with(".md") || !Path::new(vault_path).join(project_path).exists() {
+        return Ok(false);
+    }
+    let readme = match existing_path_in_vault(vault_path, project_path) {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("Skipping README frontmatter: {}", e);
+            return Ok(false);
+        }
+    };
+    let project: Vec<&PomodoroRecord> = records
+        .iter()
+        .filter(|r| r.project_path.as_deref() == Some(project_path))
+        .collect();
+    let total: u64 = project.iter().map(|r| r.duration as u64).sum();
+
+    let mut values = vec![("total_focus_minutes", YamlValue::Number(total))];
+    if let Some(last) = project.iter().map(|r| r.date.as_str()).max() {
+        values.push(("last_focused", YamlValue::Text(last.to_string())));
+    }
+    update_note_frontmatter(&readme, &values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(date: &str, duration: u32, mode: &str, project: Option<&str>) -> PomodoroRecord {
+        PomodoroRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            date: date.to_string(),
+            start_time: 0,
+            end_time: 0,
+            duration,
+            mode: mode.to_string(),
+            status: "completed".to_string(),
+            project_path: project.map(String::from),
+            task_text: None,
+            pomodoro_index: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_daily_note_stats() {
+        let a = "1. 项目/科研-A/A.README.md";
+        let b = "1. 项目/写作-B/B.README.md";
+        let records = vec![
+            record("2026-01-05", 25, "pomodoro", Some(a)),
+            record("2026-01-05", 60, "stopwatch", Some(b)),
+            record("2026-01-05", 25, "pomodoro", None),
+            record("2026-01-06", 25, "pomodoro", Some(a)),
+        ];
+        let names = HashMap::from([(b.to_string(), "Blog".to_string())]);
+        let stats = daily_note_stats(&records, "2026-01-05", &names);
+        assert_eq!(stats.pomodoros, 2);
+        assert_eq!(stats.focus_minutes, 110);
+        assert_eq!(
+            stats.focus_projects,
+            vec![
+                "[[1. 项目/写作-B/B.README|Blog]]",
+                "[[1. 项目/科研-A/A.README|科研-A]]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sync_readme_frontmatter() {
+        let vault =
+            std::env::temp_dir().join(format!("bar-tomato-readme-{}", uuid::Uuid::new_v4()));
+        let vault_path = vault.to_string_lossy().to_string();
+        let readme = "1. 项目/科研-A/A.README.md";
+        std::fs::create_dir_all(vault.join("1. 项目/科研-A")).unwrap();
+        std::fs::write(vault.join(readme), "---\nstatus: active\n---\n# A\n").unwrap();
+
+        let records = vec![
+            record("2026-01-05", 25, "pomodoro", Some(readme)),
+            record("2026-01-07", 60, "stopwatch", Some(readme)),
+            record("2026-01-08", 25, "pomodoro", None),
+        ];
+        assert!(sync_readme_frontmatter(&vault_path, readme, &records).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(vault.join(readme)).unwrap(),
+            "---\nstatus: active\ntotal_focus_minutes: 85\nlast_focused: 2026-01-07\n---\n# A\n"
+        );
+        // Unchanged totals leave the file alone
+        assert!(!sync_readme_frontmatter(&vault_path, readme, &records).unwrap());
+        // Folder-only project paths have no README to write
+        assert!(!sync_readme_frontmatter(&vault_path, "1. 项目/科研-A/", &records).unwrap());
+
+        // Nor is anything outside the vault touched
+        let outside = vault.with_extension("x.md");
+        std::fs::write(&outside, "# X\n").unwrap();
+        let name = outside.file_name().unwrap().to_string_lossy().to_string();
+        let escaping = format!("../{}", name);
+        let absolute = outside.to_string_lossy().to_string();
+        for path in [escaping.as_str(), absolute.as_str()] {
+            let records = vec![record("2026-01-05", 25, "pomodoro", Some(path))];
+            assert!(!sync_readme_frontmatter(&vault_path, path, &records).unwrap());
+        }
+        assert_eq!(std::fs::read_to_string(&outside).unwrap(), "# X\n");
+
+        std::fs::remove_file(&outside).ok();
+        std::fs::remove_dir_all(&vault).ok();
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +18,16 @@ pub struct PomodoroRecord {
     pub task_text: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pomodoro_index: Option<u32>,
+    /// Where the record came from when it was not timed live (e.g. "manual")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl PomodoroRecord {
+    /// Whether this record's time span intersects `[start_ms, end_ms)`
+    pub fn overlaps(&self, start_ms: u64, end_ms: u64) -> bool {
+        self.start_time < end_ms && start_ms < self.end_time
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,19 +45,31 @@ impl RecordsFile {
     }
 }
 
+/// A record tagged with the device whose records file it was read from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceRecord {
+    pub device_hash: String,
+    #[serde(flatten)]
+    pub record: PomodoroRecord,
+}
+
+/// LifeOS storage folder shared by the plugin and every device
+pub fn get_storage_dir(vault_path: &str) -> PathBuf {
+    Path::new(vault_path)
+        .join(".obsidian")
+        .join("plugins")
+        .join("lifeos-pro")
+        .join("storage")
+}
+
 pub fn get_records_file_path(vault_path: &str, device_hash: &str) -> String {
     let vault_name = Path::new(vault_path)
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("vault");
 
-    let storage_dir = Path::new(vault_path)
-        .join(".obsidian")
-        .join("plugins")
-        .join("lifeos-pro")
-        .join("storage");
-
-    storage_dir
+    get_storage_dir(vault_path)
         .join(format!(
             "pomodoro-records-{}.{}.json",
             vault_name, device_hash
@@ -109,3 +132,153 @@ pub fn append_record(file_path: &str, record: PomodoroRecord) -> Result<(), Stri
     records.records.push(record);
     write_records(file_path, &records)
 }
+
+/// Find the first record overlapping `[start_ms, end_ms)`, ignoring `exclude_id`
+pub fn find_overlap<'a>(
+    records: &'a [PomodoroRecord],
+    start_ms: u64,
+    end_ms: u64,
+    exclude_id: Option<&str>,
+) -> Option<&'a PomodoroRecord> {
+    records
+        .iter()
+        .filter(|r| exclude_id != Some(r.id.as_str()))
+        .find(|r| r.overlaps(start_ms, end_ms))
+}
+
+/// Replace the record with the same id. Returns the previous version.
+pub fn replace_record(file_path: &str, record: PomodoroRecord) -> Result<PomodoroRecord, String> {
+    let mut records = read_records(file_path)?;
+    let slot = records
+        .records
+        .iter_mut()
+        .find(|r| r.id == record.id)
+        .ok_or_else(|| format!("Record not found: {}", record.id))?;
+    let previous = std::mem::replace(slot, record);
+    write_records(file_path, &records)?;
+    Ok(previous)
+}
+
+/// Remove the record with this id. Returns the removed record.
+pub fn remove_record(file_path: &str, id: &str) -> Result<PomodoroRecord, String> {
+    let mut records = read_records(file_path)?;
+    let index = records
+        .records
+        .iter()
+        .position(|r| r.id == id)
+        .ok_or_else(|| format!("Record not found: {}", id))?;
+    let removed = records.records.remove(index);
+    write_records(file_path, &records)?;
+    Ok(removed)
+}
+
+/// Extract the device hash from a `pomodoro-records-{vault}.{hash}.json` name
+pub fn device_hash_from_file_name(file_name: &str) -> Option<&str> {
+    file_name
+        .strip_prefix("pomodoro-records-")?
+        .strip_suffix(".json")?
+        .rsplit_once('.')
+        .map(|(_, hash)| hash)
+        .filter(|hash| !hash.is_empty())
+}
+
+/// Find every device's records file in the storage folder as `(device_hash, path)`
+pub fn list_records_files(vault_path: &str) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = std::fs::read_dir(get_storage_dir(vault_path))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let hash = device_hash_from_file_name(&name)?.to_string();
+                    Some((hash, entry.path().to_string_lossy().to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Read and merge the records of all devices, deduplicated by record id.
+/// This device's file is read first so its copy wins on duplicates.
+pub fn read_all_records(vault_path: &str, device_hash: &str) -> Result<Vec<DeviceRecord>, String> {
+    let mut files = list_records_files(vault_path);
+    files.sort_by_key(|(hash, _)| hash != device_hash);
+
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    for (hash, path) in files {
+        let file = match read_records(&path) {
+            Ok(f) => f,
+            Err(e) => {
+                log::warn!("Skipping records file {}: {}", path, e);
+                continue;
+            }
+        };
+        for record in file.records {
+            if seen.insert(record.id.clone()) {
+                merged.push(DeviceRecord {
+                    device_hash: hash.clone(),
+                    record,
+                });
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str) -> PomodoroRecord {
+        PomodoroRecord {
+            id: id.to_string(),
+            date: "2026-01-05".to_string(),
+            start_time: 0,
+            end_time: 60_000,
+            duration: 1,
+            mode: "pomodoro".to_string(),
+            status: "completed".to_string(),
+            project_path: None,
+            task_text: None,
+            pomodoro_index: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_device_hash_from_file_name() {
+        assert_eq!(
+            device_hash_from_file_name("pomodoro-records-My.Vault.abc123.json"),
+            Some("abc123")
+        );
+        assert_eq!(device_hash_from_file_name("pomodoro-records-vault.json.tmp"), None);
+        assert_eq!(device_hash_from_file_name("other.json"), None);
+    }
+
+    #[test]
+    fn test_read_all_records_dedupes_by_id() {
+        let vault = std::env::temp_dir().join(format!("bar-tomato-test-{}", uuid::Uuid::new_v4()));
+        let vault_path = vault.to_string_lossy().to_string();
+
+        let mut a = RecordsFile::new();
+        a.records = vec![record("1"), record("2")];
+        write_records(&get_records_file_path(&vault_path, "aaa"), &a).unwrap();
+        let mut b = RecordsFile::new();
+        b.records = vec![record("2"), record("3")];
+        write_records(&get_records_file_path(&vault_path, "bbb"), &b).unwrap();
+
+        let merged = read_all_records(&vault_path, "bbb").unwrap();
+        let mut ids: Vec<_> = merged
+            .iter()
+            .map(|r| (r.record.id.as_str(), r.device_hash.as_str()))
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec![("1", "aaa"), ("2", "bbb"), ("3", "bbb")]);
+
+        std::fs::remove_dir_all(&vault).ok();
+    }
+}
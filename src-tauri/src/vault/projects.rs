@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -8,6 +8,23 @@ pub struct Project {
     pub display_name: String,
     pub path: String,
     pub readme_path: String,
+    /// Weekly budget usage, filled in by commands that know the records
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetStatus>,
+}
+
+/// A project's weekly budget and how much of it this week's records used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub project_path: String,
+    pub project_name: String,
+    pub budget_minutes: u32,
+    pub used_minutes: u32,
+    /// Negative once the project is over budget
+    pub remaining_minutes: i64,
+    pub percent: f64,
+    pub over: bool,
 }
 
 pub fn scan_projects(vault_path: &str) -> Result<Vec<Project>, String> {
@@ -72,9 +89,100 @@ pub fn scan_projects(vault_path: &str) -> Result<Vec<Project>, String> {
             display_name: folder_name.clone(),
             path: format!("1. 项目/{}", entry.file_name().to_string_lossy()),
             readme_path: relative_readme,
+            budget: None,
         });
     }
 
     projects.sort_by(|a, b| a.name.cmp(&b.name));
     Ok(projects)
 }
+
+/// Resolve the display name for a record's `project_path` (a README path).
+/// Falls back to the project folder name when the project no longer exists.
+pub fn project_display_name(vault_path: &str, project_path: &str) -> String {
+    if let Ok(projects) = scan_projects(vault_path) {
+        if let Some(p) = projects
+            .iter()
+            .find(|p| p.readme_path == project_path || p.path == project_path)
+        {
+            return p.display_name.clone();
+        }
+    }
+
+    project_folder(project_path).to_string()
+}
+
+/// Project folder name from a record's project path, e.g. `科学研究-DualBasic`
+pub fn project_folder(project_path: &str) -> &str {
+    project_path
+        .trim_end_matches('/')
+        .split('/')
+        .nth(1)
+        .unwrap_or(project_path)
+}
+
+/// The existing file a vault-relative path from a record names. Records
+/// also arrive through deep links, the HTTP API and imports, so absolute
+/// paths, `..` and symlinks that lead outside the vault are refused.
+pub fn existing_path_in_vault(vault_path: &str, path: &str) -> Result<PathBuf, String> {
+    let outside = || format!("Path is outside the vault: {}", path);
+    if Path::new(path).is_absolute() {
+        return Err(outside());
+    }
+    let vault = Path::new(vault_path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve vault: {}", e))?;
+    let resolved = vault
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", path, e))?;
+    if !resolved.starts_with(&vault) || resolved == vault {
+        return Err(outside());
+    }
+    Ok(resolved)
+}
+
+/// The unparsed `weekly_budget` frontmatter value from a project README
+pub fn read_readme_budget(vault_path: &str, readme_path: &str) -> Option<String> {
+    if !readme_path.ends_with(".md") {
+        return None;
+    }
+    let path = match existing_path_in_vault(vault_path, readme_path) {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("Skipping budget: {}", e);
+            return None;
+        }
+    };
+    let content = std::fs::read_to_string(path).ok()?;
+    super::get_frontmatter_value(&content, "weekly_budget")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readme_budget_stays_in_vault() {
+        let root = std::env::temp_dir().join(format!("bar-tomato-budget-{}", uuid::Uuid::new_v4()));
+        let vault = root.join("vault");
+        let readme = "1. 项目/A/A.README.md";
+        std::fs::create_dir_all(vault.join("1. 项目/A")).unwrap();
+        let note = "---\nweekly_budget: 5h\n---\n";
+        std::fs::write(vault.join(readme), note).unwrap();
+        std::fs::write(root.join("x.md"), note).unwrap();
+        let vault_path = vault.to_string_lossy().to_string();
+
+        assert_eq!(
+            read_readme_budget(&vault_path, readme).as_deref(),
+            Some("5h")
+        );
+        assert!(read_readme_budget(&vault_path, "../x.md").is_none());
+        let absolute = root.join("x.md").to_string_lossy().to_string();
+        assert!(read_readme_budget(&vault_path, &absolute).is_none());
+        assert!(existing_path_in_vault(&vault_path, "1. 项目/A/../../../x.md").is_err());
+        assert!(existing_path_in_vault(&vault_path, "1. 项目/A/../A/A.README.md").is_ok());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,143 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Markers around the generated review; everything outside them is left alone
+pub const REVIEW_START: &str = "<!-- bar-tomato:review:start -->";
+pub const REVIEW_END: &str = "<!-- bar-tomato:review:end -->";
+
+/// Weekly periodic note path for a title such as `2026-W02`
+pub fn weekly_note_path(vault_path: &str, title: &str) -> String {
+    periodic_note_path(vault_path, title, "Weekly")
+}
+
+/// Monthly periodic note path for a title such as `2026-01`
+pub fn monthly_note_path(vault_path: &str, title: &str) -> String {
+    periodic_note_path(vault_path, title, "Monthly")
+}
+
+fn periodic_note_path(vault_path: &str, title: &str, folder: &str) -> String {
+    let year = title.get(..4).unwrap_or(title);
+    Path::new(vault_path)
+        .join("0. 周期笔记")
+        .join(year)
+        .join(folder)
+        .join(format!("{}.md", title))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Resolve a user-chosen review note path: relative paths are taken from the
+/// vault root, and paths that lead outside the vault are rejected
+pub fn review_note_path_in_vault(vault_path: &str, path: &str) -> Result<String, String> {
+    let vault = Path::new(vault_path);
+    let joined = vault.join(path);
+
+    // Resolve `.` and `..` by hand since the note may not exist yet
+    let mut resolved = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return Err(format!("Review note path is outside the vault: {}", path));
+                }
+            }
+            other => resolved.push(other),
+        }
+    }
+
+    if !resolved.starts_with(vault) || resolved == vault {
+        return Err(format!("Review note path is outside the vault: {}", path));
+    }
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+/// Put `block` between the review markers, replacing what was there before.
+/// Notes without markers get the region appended at the end.
+pub fn replace_review_region(content: &str, block: &str) -> String {
+    let region = format!("{}\n{}\n{}", REVIEW_START, block.trim_end(), REVIEW_END);
+
+    if let Some(start) = content.find(REVIEW_START) {
+        if let Some(end_offset) = content[start..].find(REVIEW_END) {
+            let end = start + end_offset + REVIEW_END.len();
+            return format!("{}{}{}", &content[..start], region, &content[end..]);
+        }
+    }
+
+    if content.trim().is_empty() {
+        return format!("{}\n", region);
+    }
+    format!("{}\n\n{}\n", content.trim_end(), region)
+}
+
+/// Write the review into the note at `path`, creating it if needed.
+/// Returns true if the note was created.
+pub fn write_review_note(path: &str, block: &str) -> Result<bool, String> {
+    let note = Path::new(path);
+    let created = !note.exists();
+    let content = if created {
+        String::new()
+    } else {
+        std::fs::read_to_string(note).map_err(|e| format!("Failed to read review note: {}", e))?
+    };
+
+    let updated = replace_review_region(&content, block);
+    if updated == content {
+        return Ok(false);
+    }
+
+    if let Some(parent) = note.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create review note folder: {}", e))?;
+    }
+    std::fs::write(note, updated).map_err(|e| format!("Failed to write review note: {}", e))?;
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_review_region_is_idempotent() {
+        let note = "# 2026-W02\n\n## 反思\n写点什么\n";
+        let once = replace_review_region(note, "总专注：1hr00");
+        assert_eq!(
+            once,
+            format!(
+                "# 2026-W02\n\n## 反思\n写点什么\n\n{}\n总专注：1hr00\n{}\n",
+                REVIEW_START, REVIEW_END
+            )
+        );
+        assert_eq!(replace_review_region(&once, "总专注：1hr00"), once);
+
+        // Only the region changes; text after it survives
+        let edited = format!("{}\n## 下周计划\n", once);
+        let again = replace_review_region(&edited, "总专注：2hr00");
+        assert!(again.contains("总专注：2hr00"));
+        assert!(!again.contains("1hr00"));
+        assert!(again.ends_with("## 下周计划\n"));
+        assert!(again.starts_with("# 2026-W02\n\n## 反思\n写点什么\n"));
+    }
+
+    #[test]
+    fn test_periodic_note_paths() {
+        assert!(weekly_note_path("/v", "2026-W02").ends_with("0. 周期笔记/2026/Weekly/2026-W02.md"));
+        assert!(monthly_note_path("/v", "2026-01").ends_with("0. 周期笔记/2026/Monthly/2026-01.md"));
+    }
+
+    #[test]
+    fn test_review_note_path_stays_in_vault() {
+        assert_eq!(
+            review_note_path_in_vault("/v", "Reviews/./2026.md").unwrap(),
+            "/v/Reviews/2026.md"
+        );
+        assert_eq!(
+            review_note_path_in_vault("/v", "/v/Reviews/2026.md").unwrap(),
+            "/v/Reviews/2026.md"
+        );
+        assert!(review_note_path_in_vault("/v", "../outside.md").is_err());
+        assert!(review_note_path_in_vault("/v", "Reviews/../../outside.md").is_err());
+        assert!(review_note_path_in_vault("/v", "/etc/review.md").is_err());
+        assert!(review_note_path_in_vault("/v", "/v/../v2/review.md").is_err());
+    }
+}
@@ -0,0 +1,408 @@
+use super::{
+    adjust_project_time, append_record, check_pomodoro_habit, find_overlap, get_records_file_path,
+    project_display_name, read_records, replace_record, update_project_time, PomodoroRecord,
+    TimeFormat,
+};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// Persist a finished session: append the record, add its time to the daily
+/// note's project list (written in `format`) and tick the pomodoro habit.
+pub fn save_session(
+    vault_path: &str,
+    device_hash: &str,
+    record: PomodoroRecord,
+    project_name: Option<&str>,
+    format: TimeFormat,
+) -> Result<(), String> {
+    let records_path = get_records_file_path(vault_path, device_hash);
+    let date = record.date.clone();
+    let duration = record.duration;
+    let project_path = record.project_path.clone();
+
+    append_record(&records_path, record)?;
+
+    // Update daily note project time
+    if let (Some(ref pp), Some(pn)) = (&project_path, project_name) {
+        let _ = update_project_time(vault_path, &date, pp, pn, duration, format);
+    }
+
+    // Check pomodoro habit
+    let _ = check_pomodoro_habit(vault_path, &date);
+
+    Ok(())
+}
+
+/// A session entered after the fact. Times are local `HH:MM`; any two of
+/// start, end and duration are enough. An end before the start is on the
+/// next day, for sessions that cross midnight. With only a duration the
+/// session is assumed to end now, which is only allowed for today.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualEntry {
+    pub date: String,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub duration: Option<u32>,
+    pub mode: String,
+    pub project: Option<String>,
+    pub project_path: Option<String>,
+    pub task: Option<String>,
+}
+
+impl ManualEntry {
+    /// Resolve the entry to `(start_ms, end_ms, duration_mins)`
+    pub fn resolve_span(&self, now: DateTime<Local>) -> Result<(u64, u64, u32), String> {
+        let date = NaiveDate::parse_from_str(&self.date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date: {}", self.date))?;
+
+        let to_ms = |s: &str, date: NaiveDate| -> Result<u64, String> {
+            let time = NaiveTime::parse_from_str(s, "%H:%M")
+                .map_err(|_| format!("Invalid time: {}", s))?;
+            Local
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .map(|dt| dt.timestamp_millis() as u64)
+                .ok_or_else(|| format!("Invalid local time: {} {}", self.date, s))
+        };
+
+        let (start_ms, end_ms) = match (&self.start, &self.end, self.duration) {
+            (Some(s), Some(e), _) => {
+                let (start, end) = (to_ms(s, date)?, to_ms(e, date)?);
+                match date.succ_opt() {
+                    Some(next) if end < start => (start, to_ms(e, next)?),
+                    _ => (start, end),
+                }
+            }
+            (Some(s), None, Some(d)) => {
+                let start = to_ms(s, date)?;
+                (start, start + d as u64 * 60_000)
+            }
+            (None, Some(e), Some(d)) => {
+                let end = to_ms(e, date)?;
+                (end.saturating_sub(d as u64 * 60_000), end)
+            }
+            (None, None, Some(d)) => {
+                if now.date_naive() != date {
+                    return Err("A start or end time is required for past dates".to_string());
+                }
+                let end = now.timestamp_millis() as u64;
+                (end.saturating_sub(d as u64 * 60_000), end)
+            }
+            _ => return Err("Provide start/end times or a duration".to_string()),
+        };
+
+        if end_ms <= start_ms {
+            return Err("End time must be after start time".to_string());
+        }
+        if end_ms > now.timestamp_millis() as u64 {
+            return Err("Session cannot end in the future".to_string());
+        }
+
+        let duration = ((end_ms - start_ms) / 60_000) as u32;
+        if duration == 0 {
+            return Err("Session must be at least one minute".to_string());
+        }
+
+        Ok((start_ms, end_ms, duration))
+    }
+}
+
+/// Validate a manual entry and write it through the same pipeline as a timed
+/// session. Fails if it overlaps an existing record on this device.
+pub fn add_manual_session(
+    vault_path: &str,
+    device_hash: &str,
+    entry: &ManualEntry,
+    format: TimeFormat,
+) -> Result<PomodoroRecord, String> {
+    if entry.mode != "pomodoro" && entry.mode != "stopwatch" {
+        return Err(format!("Invalid mode: {}", entry.mode));
+    }
+
+    let (start_ms, end_ms, duration) = entry.resolve_span(Local::now())?;
+
+    let records_path = get_records_file_path(vault_path, device_hash);
+    let records = read_records(&records_path)?;
+    if let Some(other) = find_overlap(&records.records, start_ms, end_ms, None) {
+        return Err(format!("Overlaps existing record {}", other.id));
+    }
+
+    // Number pomodoros by their position within the day
+    let pomodoro_index = if entry.mode == "pomodoro" {
+        let earlier = records
+            .records
+            .iter()
+            .filter(|r| r.date == entry.date && r.mode == "pomodoro" && r.start_time < start_ms)
+            .count() as u32;
+        Some(earlier + 1)
+    } else {
+        None
+    };
+
+    let record = PomodoroRecord {
+        id: Uuid::new_v4().to_string(),
+        date: entry.date.clone(),
+        start_time: start_ms,
+        end_time: end_ms,
+        duration,
+        mode: entry.mode.clone(),
+        status: "completed".to_string(),
+        project_path: entry.project_path.clone(),
+        task_text: entry.task.clone(),
+        pomodoro_index,
+        source: Some("manual".to_string()),
+    };
+
+    let project_name = match (&entry.project, &entry.project_path) {
+        (Some(name), _) => Some(name.clone()),
+        (None, Some(pp)) => Some(project_display_name(vault_path, pp)),
+        (None, None) => None,
+    };
+
+    save_session(
+        vault_path,
+        device_hash,
+        record.clone(),
+        project_name.as_deref(),
+        format,
+    )?;
+    Ok(record)
+}
+
+/// Changes to an existing record. `None` leaves a field unchanged; an empty
+/// string clears the project or task. `project` is only a display name, so it
+/// must come with the `project_path` it belongs to.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordUpdate {
+    pub project: Option<String>,
+    pub project_path: Option<String>,
+    pub task_text: Option<String>,
+    pub duration: Option<u32>,
+}
+
+/// Edit a record on this device and move its minutes in the daily note to
+/// match the new project and duration.
+pub fn edit_session(
+    vault_path: &str,
+    device_hash: &str,
+    id: &str,
+    update: &RecordUpdate,
+    format: TimeFormat,
+) -> Result<PomodoroRecord, String> {
+    let records_path = get_records_file_path(vault_path, device_hash);
+    let records = read_records(&records_path)?;
+    let old = records
+        .records
+        .iter()
+        .find(|r| r.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Record not found: {}", id))?;
+
+    if update.project.as_deref().is_some_and(|p| !p.is_empty()) && update.project_path.is_none() {
+        return Err("A project name needs its project path".to_string());
+    }
+
+    let mut new = old.clone();
+    if let Some(ref pp) = update.project_path {
+        new.project_path = if pp.is_empty() {
+            None
+        } else {
+            Some(pp.clone())
+        };
+    }
+    if let Some(ref task) = update.task_text {
+        new.task_text = if task.is_empty() {
+            None
+        } else {
+            Some(task.clone())
+        };
+    }
+    if let Some(duration) = update.duration {
+        if duration == 0 {
+            return Err("Duration must be at least one minute".to_string());
+        }
+        new.duration = duration;
+        new.end_time = new.start_time + duration as u64 * 60_000;
+        if new.end_time > Local::now().timestamp_millis() as u64 {
+            return Err("Session cannot end in the future".to_string());
+        }
+        if let Some(other) = find_overlap(&records.records, new.start_time, new.end_time, Some(id))
+        {
+            return Err(format!("Overlaps existing record {}", other.id));
+        }
+    }
+
+    replace_record(&records_path, new.clone())?;
+
+    // Keep the daily note totals in line with the edited record
+    if old.project_path != new.project_path || old.duration != new.duration {
+        let new_name = match (&update.project, &new.project_path) {
+            (Some(name), Some(_)) if !name.is_empty() => Some(name.clone()),
+            (_, Some(pp)) => Some(project_display_name(vault_path, pp)),
+            _ => None,
+        };
+
+        match (&old.project_path, &new.project_path) {
+            (Some(op), Some(np)) if op == np => {
+                let delta = new.duration as i32 - old.duration as i32;
+                let _ = adjust_project_time(
+                    vault_path,
+                    &new.date,
+                    np,
+                    new_name.as_deref().unwrap_or(np),
+                    delta,
+                    format,
+                );
+            }
+            _ => {
+                if let Some(ref op) = old.project_path {
+                    let old_name = project_display_name(vault_path, op);
+                    let _ = adjust_project_time(
+                        vault_path,
+                        &old.date,
+                        op,
+                        &old_name,
+                        -(old.duration as i32),
+                        format,
+                    );
+                }
+                if let Some(ref np) = new.project_path {
+                    let _ = adjust_project_time(
+                        vault_path,
+                        &new.date,
+                        np,
+                        new_name.as_deref().unwrap_or(np),
+                        new.duration as i32,
+                        format,
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(start: Option<&str>, end: Option<&str>, duration: Option<u32>) -> ManualEntry {
+        ManualEntry {
+            date: "2026-01-05".to_string(),
+            start: start.map(String::from),
+            end: end.map(String::from),
+            duration,
+            mode: "pomodoro".to_string(),
+            project: None,
+            project_path: None,
+            task: None,
+        }
+    }
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 6, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_span_variants() {
+        let (s1, e1, d1) = entry(Some("09:00"), Some("09:25"), None)
+            .resolve_span(now())
+            .unwrap();
+        assert_eq!((e1 - s1, d1), (25 * 60_000, 25));
+
+        let (s2, e2, _) = entry(Some("09:00"), None, Some(25))
+            .resolve_span(now())
+            .unwrap();
+        let (s3, e3, _) = entry(None, Some("09:25"), Some(25))
+            .resolve_span(now())
+            .unwrap();
+        assert_eq!((s1, e1), (s2, e2));
+        assert_eq!((s1, e1), (s3, e3));
+    }
+
+    #[test]
+    fn test_resolve_span_crosses_midnight() {
+        let (start, end, duration) = entry(Some("23:30"), Some("00:30"), None)
+            .resolve_span(now())
+            .unwrap();
+        assert_eq!(duration, 60);
+        let started = Local.timestamp_millis_opt(start as i64).unwrap();
+        assert_eq!(
+            started.format("%Y-%m-%d %H:%M").to_string(),
+            "2026-01-05 23:30"
+        );
+        assert_eq!(end - start, 60 * 60_000);
+
+        // Tonight's session hasn't ended yet
+        let mut tonight = entry(Some("23:30"), Some("00:30"), None);
+        tonight.date = "2026-01-06".to_string();
+        assert_eq!(
+            tonight.resolve_span(now()).unwrap_err(),
+            "Session cannot end in the future"
+        );
+    }
+
+    #[test]
+    fn test_resolve_span_rejects_invalid() {
+        assert!(entry(Some("09:00"), Some("09:00"), None)
+            .resolve_span(now())
+            .is_err());
+        assert!(entry(None, None, Some(25)).resolve_span(now()).is_err());
+        assert!(entry(Some("9am"), None, Some(25))
+            .resolve_span(now())
+            .is_err());
+    }
+
+    #[test]
+    fn test_edit_session_validates_update() {
+        let vault = std::env::temp_dir().join(format!("bar-tomato-edit-{}", Uuid::new_v4()));
+        let vault_path = vault.to_string_lossy().to_string();
+        let record = |id: &str, start_min: u64| PomodoroRecord {
+            id: id.to_string(),
+            date: "2026-01-05".to_string(),
+            start_time: 1_767_600_000_000 + start_min * 60_000,
+            end_time: 1_767_600_000_000 + (start_min + 25) * 60_000,
+            duration: 25,
+            mode: "pomodoro".to_string(),
+            status: "completed".to_string(),
+            project_path: None,
+            task_text: None,
+            pomodoro_index: None,
+            source: None,
+        };
+        let mut file = crate::vault::RecordsFile::new();
+        file.records = vec![record("a", 0), record("b", 30)];
+        crate::vault::write_records(&get_records_file_path(&vault_path, "dev"), &file).unwrap();
+
+        let edit = |update: RecordUpdate| {
+            edit_session(&vault_path, "dev", "a", &update, TimeFormat::default())
+        };
+        let name_only = RecordUpdate {
+            project: Some("Bar Tomato".to_string()),
+            ..Default::default()
+        };
+        assert!(edit(name_only).is_err());
+        let into_next = RecordUpdate {
+            duration: Some(35),
+            ..Default::default()
+        };
+        assert!(edit(into_next).is_err());
+        let into_future = RecordUpdate {
+            duration: Some(u32::MAX / 2),
+            ..Default::default()
+        };
+        assert!(edit(into_future).is_err());
+
+        let longer = RecordUpdate {
+            duration: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(edit(longer).unwrap().duration, 30);
+
+        std::fs::remove_dir_all(&vault).ok();
+    }
+}
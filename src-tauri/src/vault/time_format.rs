@@ -0,0 +1,141 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// How durations are written in the daily note's project list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeFormat {
+    /// `1hr58`, the LifeOS default
+    #[default]
+    Hr,
+    /// `1h58m`
+    HoursMinutes,
+    /// `1:58`
+    Colon,
+    /// `1.97h`
+    DecimalHours,
+    /// `118min`
+    Minutes,
+}
+
+impl TimeFormat {
+    /// Every format, in the order `detect` tries them
+    pub const ALL: [TimeFormat; 5] = [
+        TimeFormat::Hr,
+        TimeFormat::Colon,
+        TimeFormat::HoursMinutes,
+        TimeFormat::DecimalHours,
+        TimeFormat::Minutes,
+    ];
+
+    pub fn format(&self, total_minutes: u32) -> String {
+        let hours = total_minutes / 60;
+        let mins = total_minutes % 60;
+        match self {
+            TimeFormat::Hr => format!("{}hr{:02}", hours, mins),
+            TimeFormat::HoursMinutes => match (hours, mins) {
+                (0, m) => format!("{}m", m),
+                (h, 0) => format!("{}h", h),
+                (h, m) => format!("{}h{}m", h, m),
+            },
+            TimeFormat::Colon => format!("{}:{:02}", hours, mins),
+            TimeFormat::DecimalHours => format!("{:.2}h", total_minutes as f64 / 60.0),
+            TimeFormat::Minutes => format!("{}min", total_minutes),
+        }
+    }
+
+    /// Parse a whole token written in this format
+    pub fn parse(&self, s: &str) -> Option<u32> {
+        let s = s.trim();
+        if s.is_empty() {
+            return None;
+        }
+        let caps = self.regex().captures(s)?;
+        let num = |i: usize| caps.get(i).map(|m| m.as_str());
+        match self {
+            TimeFormat::Hr | TimeFormat::Colon => {
+                Some(num(1)?.parse::<u32>().ok()? * 60 + num(2)?.parse::<u32>().ok()?)
+            }
+            TimeFormat::HoursMinutes => {
+                let hours = num(1).map_or(Some(0), |h| h.parse::<u32>().ok())?;
+                let mins = num(2).map_or(Some(0), |m| m.parse::<u32>().ok())?;
+                Some(hours * 60 + mins)
+            }
+            TimeFormat::DecimalHours => Some((num(1)?.parse::<f64>().ok()? * 60.0).round() as u32),
+            TimeFormat::Minutes => num(1)?.parse().ok(),
+        }
+    }
+
+    /// Compiled once per format instead of on every parse
+    fn regex(&self) -> &'static Regex {
+        static REGEXES: OnceLock<[Regex; 5]> = OnceLock::new();
+        let regexes = REGEXES.get_or_init(|| {
+            [
+                Regex::new(r"^(\d+)hr(\d{1,2})$").unwrap(),
+                Regex::new(r"^(?:(\d+)h)?(?:(\d+)m)?$").unwrap(),
+                Regex::new(r"^(\d+):(\d{2})$").unwrap(),
+                Regex::new(r"^(\d+\.\d+)h$").unwrap(),
+                Regex::new(r"^(\d+)\s?min$").unwrap(),
+            ]
+        });
+        match self {
+            TimeFormat::Hr => &regexes[0],
+            TimeFormat::HoursMinutes => &regexes[1],
+            TimeFormat::Colon => &regexes[2],
+            TimeFormat::DecimalHours => &regexes[3],
+            TimeFormat::Minutes => &regexes[4],
+        }
+    }
+
+    /// Recognize which format a token is written in, with its value
+    pub fn detect(s: &str) -> Option<(TimeFormat, u32)> {
+        TimeFormat::ALL
+            .iter()
+            .find_map(|f| f.parse(s).map(|m| (*f, m)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_round_trip() {
+        for format in TimeFormat::ALL {
+            for minutes in [0, 25, 60, 118, 190] {
+                let text = format.format(minutes);
+                assert_eq!(
+                    TimeFormat::detect(&text).map(|(_, m)| m),
+                    Some(minutes),
+                    "{:?} {}",
+                    format,
+                    text
+                );
+            }
+        }
+        assert_eq!(TimeFormat::HoursMinutes.format(118), "1h58m");
+        assert_eq!(TimeFormat::Colon.format(118), "1:58");
+        assert_eq!(TimeFormat::DecimalHours.format(118), "1.97h");
+        assert_eq!(TimeFormat::Minutes.format(118), "118min");
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(TimeFormat::detect("1hr58"), Some((TimeFormat::Hr, 118)));
+        assert_eq!(
+            TimeFormat::detect("2h"),
+            Some((TimeFormat::HoursMinutes, 120))
+        );
+        assert_eq!(
+            TimeFormat::detect("1.5h"),
+            Some((TimeFormat::DecimalHours, 90))
+        );
+        assert_eq!(
+            TimeFormat::detect("45 min"),
+            Some((TimeFormat::Minutes, 45))
+        );
+        assert_eq!(TimeFormat::detect("[[A]]"), None);
+        assert_eq!(TimeFormat::detect("45"), None);
+    }
+}
@@ -1,5 +1,42 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { TimerStatus, PomodoroConfig, Project, VaultTask, TodayStats } from '../types';
+import type {
+  TimerStatus,
+  PomodoroConfig,
+  Project,
+  VaultTask,
+  TodayStats,
+  PomodoroRecord,
+  ManualEntry,
+  RecordUpdate,
+  StatsQuery,
+  StatsReport,
+  StreakStats,
+  HeatmapDay,
+  FocusDistribution,
+  TrendPoint,
+  GroupBy,
+  Weekday,
+  DailyGoal,
+  GoalProgress,
+  BudgetStatus,
+  BudgetSummary,
+  ExportOptions,
+  ExportSummary,
+  ImportOptions,
+  ImportSummary,
+  ReviewOptions,
+  ReviewSummary,
+  TimeFormat,
+  TimeFormatMigration,
+  HttpApiInfo,
+  AutostartState,
+  ShellHook,
+  Webhook,
+  WebhookSettings,
+  LiveSession,
+  RecordConflict,
+  ConflictChoice,
+} from '../types';
 
 export async function getTimerStatus(): Promise<TimerStatus> {
   return invoke('get_timer_status');
@@ -37,6 +74,14 @@ export async function completePomodoro(): Promise<void> {
   return invoke('complete_pomodoro');
 }
 
+export async function addManualEntry(entry: ManualEntry): Promise<PomodoroRecord> {
+  return invoke('add_manual_entry', { entry });
+}
+
+export async function updateRecord(id: string, update: RecordUpdate): Promise<PomodoroRecord> {
+  return invoke('update_record', { id, update });
+}
+
 export async function skipBreak(): Promise<void> {
   return invoke('skip_break');
 }
@@ -69,18 +114,174 @@ export async function getTrayTitle(): Promise<string> {
   return invoke('get_tray_title');
 }
 
-export async function getTodayStats(): Promise<TodayStats> {
-  return invoke('get_today_stats');
+export async function getTodayStats(byDevice?: boolean): Promise<TodayStats> {
+  return invoke('get_today_stats', { byDevice });
+}
+
+export async function getStats(query: StatsQuery): Promise<StatsReport> {
+  return invoke('get_stats', { query });
+}
+
+export async function getStreaks(thresholdMinutes?: number): Promise<StreakStats> {
+  return invoke('get_streaks', { thresholdMinutes });
+}
+
+export async function getHeatmap(days?: number): Promise<HeatmapDay[]> {
+  return invoke('get_heatmap', { days });
+}
+
+export async function getFocusDistribution(from: string, to: string): Promise<FocusDistribution> {
+  return invoke('get_focus_distribution', { from, to });
+}
+
+export async function getSessionLengthTrend(
+  from: string,
+  to: string,
+  groupBy: GroupBy,
+  weekStart?: Weekday
+): Promise<TrendPoint[]> {
+  return invoke('get_session_length_trend', { from, to, groupBy, weekStart });
+}
+
+export async function getDailyGoal(): Promise<DailyGoal | null> {
+  return invoke('get_daily_goal');
+}
+
+export async function setDailyGoal(goal: DailyGoal | null): Promise<GoalProgress | null> {
+  return invoke('set_daily_goal', { goal });
+}
+
+export async function getBudgetSummary(): Promise<BudgetSummary> {
+  return invoke('get_budget_summary');
+}
+
+export async function setProjectBudget(
+  projectPath: string,
+  minutes: number | null
+): Promise<BudgetStatus | null> {
+  return invoke('set_project_budget', { projectPath, minutes });
+}
+
+export async function exportRecords(options: ExportOptions): Promise<ExportSummary> {
+  return invoke('export_records', { options });
+}
+
+export async function exportCalendar(
+  from: string,
+  to: string,
+  path: string
+): Promise<ExportSummary> {
+  return invoke('export_calendar', { from, to, path });
+}
+
+export async function setCalendarSync(
+  path: string | null,
+  days?: number
+): Promise<ExportSummary | null> {
+  return invoke('set_calendar_sync', { path, days });
+}
+
+export async function importSessions(options: ImportOptions): Promise<ImportSummary> {
+  return invoke('import_sessions', { options });
+}
+
+export async function generateReview(options: ReviewOptions): Promise<ReviewSummary> {
+  return invoke('generate_review', { options });
+}
+
+export async function getReviewTemplate(): Promise<string> {
+  return invoke('get_review_template');
+}
+
+export async function setReviewTemplate(path: string | null): Promise<void> {
+  return invoke('set_review_template', { path });
+}
+
+/** Saves the format and previews how many daily notes `migrateTimeFormat` would convert */
+export async function setTimeFormat(format: TimeFormat): Promise<TimeFormatMigration> {
+  return invoke('set_time_format', { format });
+}
+
+export async function migrateTimeFormat(): Promise<TimeFormatMigration> {
+  return invoke('migrate_time_format');
+}
+
+export async function getHttpApi(): Promise<HttpApiInfo> {
+  return invoke('get_http_api');
+}
+
+export async function setHttpApi(
+  enabled: boolean,
+  port?: number,
+  regenerateToken = false
+): Promise<HttpApiInfo> {
+  return invoke('set_http_api', { enabled, port, regenerateToken });
+}
+
+/** Path of the panel status file, or null when it is turned off */
+export async function getStatusFile(): Promise<string | null> {
+  return invoke('get_status_file');
+}
+
+export async function setStatusFile(enabled: boolean): Promise<string | null> {
+  return invoke('set_status_file', { enabled });
+}
+
+/** Path of the vault's command inbox, or null when it is turned off */
+export async function getCommandInbox(): Promise<string | null> {
+  return invoke('get_command_inbox');
+}
+
+export async function setCommandInbox(enabled: boolean): Promise<string | null> {
+  return invoke('set_command_inbox', { enabled });
+}
+
+/** Sessions running on other devices */
+export async function getLiveSessions(): Promise<LiveSession[]> {
+  return invoke('get_live_sessions');
+}
+
+/** Unresolved overlapping records from the last `days` days */
+export async function getConflicts(days?: number): Promise<RecordConflict[]> {
+  return invoke('get_conflicts', { days });
+}
+
+export async function resolveConflict(choice: ConflictChoice): Promise<void> {
+  return invoke('resolve_conflict', { choice });
+}
+
+export async function getShellHooks(): Promise<ShellHook[]> {
+  return invoke('get_shell_hooks');
+}
+
+export async function setShellHooks(hooks: ShellHook[]): Promise<void> {
+  return invoke('set_shell_hooks', { hooks });
+}
+
+export async function getWebhooks(): Promise<WebhookSettings> {
+  return invoke('get_webhooks');
+}
+
+export async function setWebhooks(webhooks: Webhook[]): Promise<WebhookSettings> {
+  return invoke('set_webhooks', { webhooks });
+}
+
+export async function setNoteFrontmatter(daily: boolean, readme: boolean): Promise<void> {
+  return invoke('set_note_frontmatter', { daily, readme });
+}
+
+export async function setDeviceName(name: string | null): Promise<void> {
+  return invoke('set_device_name', { name });
 }
 
 export async function setDialogOpen(open: boolean): Promise<void> {
   return invoke('set_dialog_open', { open });
 }
 
-export async function setAutostart(enabled: boolean): Promise<void> {
+export async function setAutostart(enabled: boolean): Promise<AutostartState> {
   return invoke('set_autostart', { enabled });
 }
 
-export async function getAutostart(): Promise<boolean> {
+export async function getAutostart(): Promise<AutostartState> {
   return invoke('get_autostart');
 }
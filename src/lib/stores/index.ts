@@ -1,5 +1,14 @@
 import { writable, derived, get } from 'svelte/store';
-import type { TimerStatus, PomodoroConfig, Project, VaultTask, TodayStats } from '../types';
+import type {
+  TimerStatus,
+  PomodoroConfig,
+  Project,
+  VaultTask,
+  TodayStats,
+  AutostartState,
+  LiveSession,
+  RecordConflict,
+} from '../types';
 import * as api from '../api/tauri';
 import { listen } from '@tauri-apps/api/event';
 
@@ -14,6 +23,8 @@ export const timerStatus = writable<TimerStatus>({
   currentTask: null,
   currentProject: null,
   currentProjectPath: null,
+  goal: null,
+  projectBudget: null,
 });
 
 // Config store
@@ -41,8 +52,14 @@ export const todayStats = writable<TodayStats>({
   pomodoroCount: 0,
 });
 
+// Overlapping records waiting for the user to resolve them
+export const conflicts = writable<RecordConflict[]>([]);
+
+// Timers elsewhere counting focus time alongside this one
+export const liveConflicts = writable<LiveSession[]>([]);
+
 // Autostart store
-export const autostart = writable<boolean>(false);
+export const autostart = writable<AutostartState>('disabled');
 
 // Derived: formatted remaining time
 export const formattedTime = derived(timerStatus, ($status) => {
@@ -98,6 +115,8 @@ export async function initializeStores() {
 
       const stats = await api.getTodayStats();
       todayStats.set(stats);
+
+      conflicts.set(await api.getConflicts());
     }
 
     // Get initial timer status
@@ -132,6 +151,15 @@ export async function refreshTodayStats() {
   }
 }
 
+// Refresh unresolved conflicts
+export async function refreshConflicts() {
+  try {
+    conflicts.set(await api.getConflicts());
+  } catch (e) {
+    console.error('Failed to refresh conflicts:', e);
+  }
+}
+
 // Set up event listeners
 export function setupEventListeners() {
   listen('timer-tick', () => {
@@ -141,9 +169,14 @@ export function setupEventListeners() {
   listen('pomodoro-complete', () => {
     refreshTimerStatus();
     refreshTodayStats();
+    refreshConflicts();
   });
 
   listen('break-complete', () => {
     refreshTimerStatus();
   });
+
+  listen<LiveSession[]>('timer-conflict', (event) => {
+    liveConflicts.set(event.payload);
+  });
 }
@@ -11,6 +11,8 @@ export interface TimerStatus {
   currentTask: string | null;
   currentProject: string | null;
   currentProjectPath: string | null;
+  goal: GoalProgress | null;
+  projectBudget: BudgetStatus | null;
 }
 
 export interface PomodoroConfig {
@@ -27,6 +29,7 @@ export interface Project {
   displayName: string;
   path: string;
   readmePath: string;
+  budget?: BudgetStatus;
 }
 
 export interface VaultTask {
@@ -40,4 +43,330 @@ export interface VaultTask {
 export interface TodayStats {
   totalMinutes: number;
   pomodoroCount: number;
+  devices?: DeviceStats[];
+}
+
+export interface DeviceStats {
+  deviceHash: string;
+  deviceName: string;
+  isCurrent: boolean;
+  totalMinutes: number;
+  pomodoroCount: number;
+}
+
+export interface PomodoroRecord {
+  id: string;
+  date: string;
+  startTime: number;
+  endTime: number;
+  duration: number;
+  mode: TimerMode;
+  status: string;
+  projectPath?: string;
+  taskText?: string;
+  pomodoroIndex?: number;
+  source?: string;
+}
+
+export interface ManualEntry {
+  date: string;
+  start?: string;
+  end?: string;
+  duration?: number;
+  mode: TimerMode;
+  project?: string;
+  projectPath?: string;
+  task?: string;
+}
+
+export interface RecordUpdate {
+  project?: string;
+  projectPath?: string;
+  taskText?: string;
+  duration?: number;
+}
+
+export type GroupBy = 'day' | 'week' | 'month' | 'project' | 'area' | 'task' | 'mode';
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+
+export interface StatsQuery {
+  from: string;
+  to: string;
+  groupBy: GroupBy;
+  statuses?: string[];
+  weekStart?: Weekday;
+}
+
+export interface StatsTotals {
+  totalMinutes: number;
+  sessionCount: number;
+  pomodoroCount: number;
+  completedCount: number;
+  averageMinutes: number;
+  completionRate: number;
+}
+
+export interface StatsGroup extends StatsTotals {
+  key: string;
+  label: string;
+}
+
+export interface StatsReport extends StatsTotals {
+  from: string;
+  to: string;
+  groupBy: GroupBy;
+  groups: StatsGroup[];
+}
+
+export interface StreakStats {
+  thresholdMinutes: number;
+  current: number;
+  longest: number;
+  longestStart: string | null;
+  longestEnd: string | null;
+}
+
+export interface HeatmapDay {
+  date: string;
+  minutes: number;
+  count: number;
+  level: number;
+}
+
+export interface FocusDistribution {
+  byHour: number[];
+  byWeekday: number[];
+}
+
+export interface TrendPoint {
+  key: string;
+  label: string;
+  averageMinutes: number;
+  sessionCount: number;
+}
+
+export type GoalUnit = 'pomodoros' | 'minutes';
+
+export interface DailyGoal {
+  unit: GoalUnit;
+  target: number;
+  weekdayTargets?: Partial<Record<Weekday, number>>;
+  showInTray?: boolean;
+  useDailyNote?: boolean;
+}
+
+export interface GoalProgress {
+  date: string;
+  unit: GoalUnit;
+  target: number;
+  done: number;
+  percent: number;
+  reached: boolean;
+}
+
+export interface BudgetStatus {
+  projectPath: string;
+  projectName: string;
+  budgetMinutes: number;
+  usedMinutes: number;
+  remainingMinutes: number;
+  percent: number;
+  over: boolean;
+}
+
+export interface BudgetSummary {
+  weekStart: string;
+  weekEnd: string;
+  over: BudgetStatus[];
+  under: BudgetStatus[];
+}
+
+export type ExportFormat = 'csv' | 'jsonLines' | 'json';
+export type ExportColumn =
+  | 'id'
+  | 'date'
+  | 'start'
+  | 'end'
+  | 'duration'
+  | 'mode'
+  | 'status'
+  | 'project'
+  | 'projectPath'
+  | 'area'
+  | 'task'
+  | 'pomodoroIndex'
+  | 'device'
+  | 'source';
+
+export interface ExportOptions {
+  from: string;
+  to: string;
+  format: ExportFormat;
+  columns?: ExportColumn[];
+  path: string;
+}
+
+export interface ExportSummary {
+  path: string;
+  count: number;
+}
+
+export type ImportFormat = 'toggl' | 'csv' | 'json';
+export type DurationUnit = 'seconds' | 'minutes';
+
+/** CSV header name for each value; start/end may be times combined with date */
+export interface ColumnMapping {
+  date?: string;
+  start?: string;
+  end?: string;
+  endDate?: string;
+  duration?: string;
+  project?: string;
+  task?: string;
+  mode?: string;
+}
+
+export interface ImportOptions {
+  path: string;
+  format: ImportFormat;
+  mapping?: ColumnMapping;
+  durationUnit?: DurationUnit;
+  dryRun?: boolean;
+}
+
+export interface ImportRowError {
+  row: number;
+  reason: string;
+}
+
+export interface ImportSummary {
+  totalRows: number;
+  imported: number;
+  duplicates: number;
+  errors: ImportRowError[];
+  matchedProjects: string[];
+  unmatchedProjects: string[];
+  totalMinutes: number;
+  firstDate: string | null;
+  lastDate: string | null;
+  written: boolean;
+}
+
+export type ReviewPeriod = 'week' | 'month';
+
+export interface ReviewOptions {
+  period: ReviewPeriod;
+  /** Any day in the period, YYYY-MM-DD; defaults to today */
+  date?: string;
+  /** Write here instead of the weekly/monthly periodic note */
+  path?: string;
+}
+
+export interface ReviewSummary {
+  path: string;
+  title: string;
+  from: string;
+  to: string;
+  created: boolean;
+}
+
+/** How project times are written in daily notes: 1hr58, 1h58m, 1:58, 1.97h, 118min */
+export type TimeFormat = 'hr' | 'hoursMinutes' | 'colon' | 'decimalHours' | 'minutes';
+
+export interface TimeFormatMigration {
+  notesScanned: number;
+  /** Notes converted, or for a preview the notes that would be */
+  notesChanged: number;
+  applied: boolean;
+}
+
+/** Login item state; `stale` means it points at an old executable path */
+export type AutostartState = 'enabled' | 'disabled' | 'stale' | 'unsupported';
+
+/** Local HTTP control API on 127.0.0.1 */
+export interface HttpApiInfo {
+  enabled: boolean;
+  port: number;
+  /** Sent as `Authorization: Bearer`; only `/events` also accepts `?token=`, for EventSource */
+  token: string | null;
+  running: boolean;
+}
+
+/** Timer events that trigger shell hooks */
+export type TimerEvent =
+  | 'pomodoro_started'
+  | 'stopwatch_started'
+  | 'pomodoro_completed'
+  | 'break_started'
+  | 'break_completed'
+  | 'session_stopped'
+  | 'paused'
+  | 'resumed';
+
+/** Command run on timer events, with `BAR_TOMATO_*` variables and JSON on stdin */
+export interface ShellHook {
+  command: string;
+  /** Empty means every event */
+  events: TimerEvent[];
+  timeoutSecs: number;
+  enabled: boolean;
+}
+
+/**
+ * Endpoint that receives timer events and written records as JSON. With a
+ * secret set, `X-Bar-Tomato-Signature: sha256=<hmac>` signs
+ * `{X-Bar-Tomato-Timestamp}.{body}`
+ */
+export interface Webhook {
+  url: string;
+  secret?: string | null;
+  /** Empty means every event */
+  events: TimerEvent[];
+  records: boolean;
+  enabled: boolean;
+}
+
+export interface WebhookSettings {
+  webhooks: Webhook[];
+  /** Deliveries waiting for a retry */
+  pending: number;
+}
+
+/** Session another device is timing, from its `current-session.{hash}.json` */
+export interface LiveSession {
+  deviceHash: string;
+  deviceName: string;
+  phase: TimerPhase;
+  mode: TimerMode;
+  startedAt: number;
+  expectedEnd: number | null;
+  task: string | null;
+  project: string | null;
+  projectPath: string | null;
+  pomodoroCount: number;
+  updatedAt: number;
+}
+
+/** A record tagged with the device whose records file it came from */
+export interface DeviceRecord extends PomodoroRecord {
+  deviceHash: string;
+}
+
+export type ConflictResolution = 'merge' | 'keepBoth' | 'discard';
+
+/** Two records timed over the same stretch, `first` starting earlier */
+export interface RecordConflict {
+  first: DeviceRecord;
+  second: DeviceRecord;
+  overlapMinutes: number;
+  firstDeviceName: string;
+  secondDeviceName: string;
+}
+
+export interface ConflictChoice {
+  firstId: string;
+  secondId: string;
+  resolution: ConflictResolution;
+  /** Which of the two records to drop when discarding */
+  discardId?: string;
 }